@@ -0,0 +1,224 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Parsing of client properties, e.g. for configuring a Kafka client.
+//!
+//! Properties files follow the format of Java's `java.util.Properties`, i.e. they may contain comments,
+//! blank lines, escape sequences and values spanning multiple lines. Property values may refer to
+//! environment variables by means of `${VAR_NAME}`, which allows secrets like SASL passwords to be
+//! injected via the environment instead of being contained in the file.
+
+/// Parses the content of a properties file into a list of key/value pairs.
+///
+/// The pairs are returned in the order in which they are defined in the content.
+pub fn parse_properties(content: &str) -> Vec<(String, String)> {
+    let mut properties = Vec::new();
+    let mut lines = content.lines();
+    while let Some(line) = next_logical_line(&mut lines) {
+        let (key, value) = split_key_value(&line);
+        properties.push((unescape(key), unescape(value)));
+    }
+    properties
+}
+
+/// Gets the next logical line, skipping blank lines and comments.
+///
+/// A logical line consists of a natural line and all following natural lines, as long as the
+/// preceding line ends with an odd number of backslashes.
+fn next_logical_line<'a, I: Iterator<Item = &'a str>>(lines: &mut I) -> Option<String> {
+    loop {
+        let line = lines.next()?.trim_start();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+            continue;
+        }
+        let mut logical_line = String::new();
+        let mut current = line;
+        loop {
+            let trailing_backslashes = current.chars().rev().take_while(|c| *c == '\\').count();
+            if trailing_backslashes % 2 == 0 {
+                logical_line.push_str(current);
+                return Some(logical_line);
+            }
+            // strip the line continuation character
+            logical_line.push_str(&current[..current.len() - 1]);
+            match lines.next() {
+                Some(next) => current = next.trim_start(),
+                None => return Some(logical_line),
+            }
+        }
+    }
+}
+
+/// Splits a logical line into the (still escaped) key and value.
+///
+/// The key ends at the first unescaped `=`, `:` or whitespace character.
+fn split_key_value(line: &str) -> (&str, &str) {
+    let mut escaped = false;
+    let mut key_end = line.len();
+    for (idx, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == '=' || c == ':' || c.is_whitespace() {
+            key_end = idx;
+            break;
+        }
+    }
+    let key = &line[..key_end];
+    let mut rest = line[key_end..].trim_start();
+    if let Some(stripped) = rest.strip_prefix(['=', ':']) {
+        rest = stripped.trim_start();
+    }
+    (key, rest)
+}
+
+/// Replaces the escape sequences contained in a key or value.
+fn unescape(escaped: &str) -> String {
+    let mut result = String::with_capacity(escaped.len());
+    let mut chars = escaped.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => result.push('\t'),
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('f') => result.push('\u{c}'),
+            Some('u') => {
+                let code: String = chars.by_ref().take(4).collect();
+                match u32::from_str_radix(&code, 16).ok().and_then(char::from_u32) {
+                    Some(unicode_char) => result.push(unicode_char),
+                    None => {
+                        result.push_str("\\u");
+                        result.push_str(&code);
+                    }
+                }
+            }
+            Some(other) => result.push(other),
+            None => {}
+        }
+    }
+    result
+}
+
+/// Replaces references to variables of the form `${VAR_NAME}` with the variables' values.
+///
+/// # Errors
+///
+/// Returns an error if a referenced variable is not defined or if a reference is not terminated.
+pub fn interpolate<F>(value: &str, lookup: F) -> Result<String, String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        let reference = &rest[start + 2..];
+        let end = reference
+            .find('}')
+            .ok_or_else(|| format!("unterminated variable reference in [{value}]"))?;
+        let name = &reference[..end];
+        let variable_value =
+            lookup(name).ok_or_else(|| format!("variable [{name}] is not defined"))?;
+        result.push_str(&variable_value);
+        rest = &reference[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Replaces references to environment variables of the form `${VAR_NAME}` contained in the
+/// values of properties with the variables' values.
+///
+/// # Errors
+///
+/// Returns an error if a value refers to an environment variable that is not defined or
+/// contains a reference that is not terminated.
+pub fn interpolate_env(properties: Vec<(String, String)>) -> Result<Vec<(String, String)>, String> {
+    properties
+        .into_iter()
+        .map(|(key, value)| {
+            interpolate(&value, |name| std::env::var(name).ok())
+                .map(|value| (key.clone(), value))
+                .map_err(|e| format!("invalid value of property [{key}]: {e}"))
+        })
+        .collect()
+}
+
+/// Parses a `key=value` pair as provided on the command line.
+pub fn parse_key_value(property: &str) -> Result<(String, String), String> {
+    property
+        .split_once('=')
+        .map(|(key, value)| (key.trim().to_string(), value.to_string()))
+        .filter(|(key, _value)| !key.is_empty())
+        .ok_or_else(|| "property must be of the form KEY=VALUE".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_properties_supports_java_syntax() {
+        let content = r#"
+# a comment
+! another comment
+
+bootstrap.servers = broker1:9092,\
+                    broker2:9092
+group.id:fms-consumer
+security.protocol SASL_SSL
+sasl.jaas.config=a\=b\tcA
+empty.value=
+"#;
+        let properties = parse_properties(content);
+        assert_eq!(
+            properties,
+            vec![
+                (
+                    "bootstrap.servers".to_string(),
+                    "broker1:9092,broker2:9092".to_string()
+                ),
+                ("group.id".to_string(), "fms-consumer".to_string()),
+                ("security.protocol".to_string(), "SASL_SSL".to_string()),
+                ("sasl.jaas.config".to_string(), "a=b\tcA".to_string()),
+                ("empty.value".to_string(), String::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_interpolate_replaces_variables() {
+        let lookup = |name: &str| (name == "SASL_PASSWORD").then(|| "secret".to_string());
+        assert_eq!(
+            interpolate("pwd=${SASL_PASSWORD};", lookup),
+            Ok("pwd=secret;".to_string())
+        );
+        assert_eq!(
+            interpolate("no variables", lookup),
+            Ok("no variables".to_string())
+        );
+        assert!(interpolate("${UNDEFINED}", lookup).is_err());
+        assert!(interpolate("${SASL_PASSWORD", lookup).is_err());
+    }
+}
@@ -17,38 +17,228 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
-use std::thread;
+use std::time::Duration;
 
 use clap::{Parser, Subcommand};
-use fms_proto::fms::VehicleStatus;
+use fms_proto::fms::{ConnectionStatus, VehicleStatus};
+use fms_zenoh::vehicle_status::{
+    LatestVehicleStatus, VehicleStatusQueryable, VehicleStatusQueryableConfig,
+};
 use fms_zenoh::ZenohTransportConfig;
+use influx_client::batch::BatchWriterConfig;
 use influx_client::connection::InfluxConnectionConfig;
 use influx_client::writer::InfluxWriter;
-use log::info;
+use log::{debug, info, warn};
+use tokio::signal::unix::{signal, Signal, SignalKind};
 
 use up_rust::{UListener, UMessage, UTransport, UUri};
-use up_transport_hono_kafka::{HonoKafkaTransport, HonoKafkaTransportConfig};
-use up_transport_zenoh::UPTransportZenoh;
+use up_transport_file::{FileTransport, FileTransportConfig};
+use up_transport_hono_kafka::{processing, tenant, HonoKafkaTransport, HonoKafkaTransportConfig};
+use up_transport_kafka::{KafkaTransport, KafkaTransportConfig};
+use up_transport_mqtt5::{Mqtt5Transport, Mqtt5TransportConfig};
+use up_transport_zenoh::{zenoh_config, UPTransportZenoh};
+use vin_binding::VinBindings;
+
+mod vin_binding;
+
+const PARAM_SHUTDOWN_TIMEOUT: &str = "shutdown-timeout";
+const PARAM_VIN_BINDINGS_FILE: &str = "vin-bindings-file";
+
+const METRICS_LOG_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The writers to use for writing the data of the different tenants to the InfluxDB server.
+struct InfluxWriters {
+    default: InfluxWriter,
+    by_tenant: HashMap<String, InfluxWriter>,
+}
+
+impl InfluxWriters {
+    fn new(
+        connection_config: &InfluxConnectionConfig,
+        tenant_buckets: &[(String, String)],
+        batch_config: &BatchWriterConfig,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let new_writer = |connection_config: &InfluxConnectionConfig| {
+            if batch_config.enabled {
+                InfluxWriter::new_batched(connection_config, batch_config)
+            } else {
+                InfluxWriter::new(connection_config)
+            }
+        };
+        let mut by_tenant = HashMap::new();
+        for (tenant_id, bucket) in tenant_buckets {
+            info!("writing data of tenant [{tenant_id}] to bucket [{bucket}]");
+            let writer = new_writer(&connection_config.with_bucket(bucket))?;
+            by_tenant.insert(tenant_id.to_owned(), writer);
+        }
+        Ok(InfluxWriters {
+            default: new_writer(connection_config)?,
+            by_tenant,
+        })
+    }
+
+    fn all(&self) -> impl Iterator<Item = (&str, &InfluxWriter)> {
+        std::iter::once(("default", &self.default)).chain(
+            self.by_tenant
+                .iter()
+                .map(|(tenant_id, writer)| (tenant_id.as_str(), writer)),
+        )
+    }
+
+    /// Logs the number of measurements that have not been written to the InfluxDB server yet.
+    ///
+    /// The log line is the consumer's only interface for monitoring the queue depth.
+    fn log_metrics(&self) {
+        for (tenant_id, writer) in self.all() {
+            if let Some(metrics) = writer.metrics() {
+                info!(
+                    "InfluxDB writer [tenant: {tenant_id}] queue depth: {}, spilled: {}",
+                    metrics.queue_depth, metrics.spilled
+                );
+            }
+        }
+    }
+
+    /// Logs the number of buffered measurements that have neither been written to the
+    /// InfluxDB server nor spilled to disk and are therefore lost.
+    fn log_discarded(&self) {
+        for (tenant_id, writer) in self.all() {
+            if let Some(metrics) = writer.metrics().filter(|metrics| metrics.queue_depth > 0) {
+                warn!(
+                    "discarding {} buffered measurements [tenant: {tenant_id}] that have not been written to the InfluxDB server",
+                    metrics.queue_depth
+                );
+            }
+        }
+    }
+
+    /// Writes the buffered measurements of all writers to the InfluxDB server.
+    async fn close(&self) {
+        for (_tenant_id, writer) in self.all() {
+            writer.close().await;
+        }
+    }
+
+    /// Gets the writer to use for the data contained in a message.
+    ///
+    /// The writer is determined by the tenant of the device that has sent the message, which
+    /// is part of the authority of the message's source URI, i.e. `<device-id>.<tenant-id>`.
+    /// The default writer is used for tenants that no bucket has been configured for.
+    fn for_message(&self, msg: &UMessage) -> &InfluxWriter {
+        tenant::split_device_authority(source_authority(msg))
+            .and_then(|(_device_id, tenant_id)| self.by_tenant.get(tenant_id))
+            .unwrap_or(&self.default)
+    }
+}
 
 struct VehicleStatusListener {
-    influx_writer: InfluxWriter,
+    influx_writers: Arc<InfluxWriters>,
+    vin_bindings: Option<Arc<VinBindings>>,
+    latest_vehicle_status: Option<Arc<LatestVehicleStatus>>,
+}
+
+impl VehicleStatusListener {
+    /// Checks if the device that has sent a message is allowed to report the status of the vehicle.
+    ///
+    /// The device is identified by the authority of the message's source URI.
+    fn is_authorized(&self, msg: &UMessage, vehicle_status: &VehicleStatus) -> bool {
+        let Some(vin_bindings) = self.vin_bindings.as_ref() else {
+            return true;
+        };
+        vin_bindings.is_bound(source_authority(msg), &vehicle_status.vin)
+    }
+}
+
+fn source_authority(msg: &UMessage) -> &str {
+    msg.attributes
+        .source
+        .as_ref()
+        .map_or("", |source| source.authority_name.as_str())
 }
 
 #[async_trait::async_trait]
 impl UListener for VehicleStatusListener {
     async fn on_receive(&self, msg: UMessage) {
         if let Ok(vehicle_status) = msg.extract_protobuf::<VehicleStatus>() {
-            self.influx_writer
+            if !self.is_authorized(&msg, &vehicle_status) {
+                warn!(
+                    "rejecting status of vehicle [VIN: {}] reported by device [{}] that the vehicle is not bound to",
+                    vehicle_status.vin,
+                    source_authority(&msg)
+                );
+                return;
+            }
+            if let Some(latest_vehicle_status) = self.latest_vehicle_status.as_ref() {
+                latest_vehicle_status.update(&vehicle_status);
+            }
+            if self
+                .influx_writers
+                .for_message(&msg)
                 .write_vehicle_status(&vehicle_status)
-                .await;
+                .await
+                .is_err()
+            {
+                // make sure that the vehicle status is delivered again
+                // if the transport supports it
+                processing::report_failure();
+            }
         } else {
             info!("ignoring event with invalid/unknown payload");
         }
     }
 }
 
+/// Writes the connection status of devices for all vehicles that are bound to them.
+///
+/// The listener requires VIN bindings because otherwise there is no way of telling
+/// which vehicles a device reports the status for.
+struct ConnectionStatusListener {
+    influx_writers: Arc<InfluxWriters>,
+    vin_bindings: Arc<VinBindings>,
+}
+
+impl ConnectionStatusListener {
+    /// Gets the VINs of the vehicles that the device which has sent a connection status is bound to.
+    ///
+    /// The device is identified by the authority of the message's source URI.
+    fn vins<'a>(&'a self, msg: &UMessage) -> Vec<&'a str> {
+        self.vin_bindings.vins(source_authority(msg)).collect()
+    }
+}
+
+#[async_trait::async_trait]
+impl UListener for ConnectionStatusListener {
+    async fn on_receive(&self, msg: UMessage) {
+        let Ok(connection_status) = msg.extract_protobuf::<ConnectionStatus>() else {
+            info!("ignoring connection status event with invalid/unknown payload");
+            return;
+        };
+        let vins = self.vins(&msg);
+        if vins.is_empty() {
+            debug!(
+                "ignoring connection status of device [{}] that no vehicle is bound to",
+                source_authority(&msg)
+            );
+            return;
+        }
+        let influx_writer = self.influx_writers.for_message(&msg);
+        for vin in vins {
+            if influx_writer
+                .write_connection_status(vin, &connection_status)
+                .await
+                .is_err()
+            {
+                processing::report_failure();
+                return;
+            }
+        }
+    }
+}
+
 /// Receives FMS related VSS data points via Hono's Kafka based Telemetry API or Eclipse Zenoh instance
 /// and writes them to an InfluxDB server.
 #[derive(Parser)]
@@ -58,13 +248,39 @@ struct FmsConsumerCommand {
     #[arg(long = "topic-filter", value_name = "URI", env = "TOPIC_FILTER", default_value = "up://*/D100/1/D100", value_parser = up_rust::UUri::from_str )]
     vehicle_status_topic_filter: UUri,
 
+    /// The topic URI pattern to use for consuming connection status events.
+    #[arg(long = "connection-status-topic-filter", value_name = "URI", env = "CONNECTION_STATUS_TOPIC_FILTER", default_value = "up://*/D102/1/D102", value_parser = up_rust::UUri::from_str )]
+    connection_status_topic_filter: UUri,
+
     /// The local uService address.
     #[arg(long = "uservice-uri", value_name = "URI", env = "USERVICE_URI", default_value = "up://fms-consumer/D101/1/0", value_parser = up_rust::UUri::from_str )]
     local_uservice_uri: UUri,
 
+    /// The maximum time to wait for vehicle status events that are currently being processed
+    /// to be written to the InfluxDB server when shutting down, e.g. 10s or 1m.
+    #[arg(long = PARAM_SHUTDOWN_TIMEOUT, value_name = "DURATION_SPEC", env = "SHUTDOWN_TIMEOUT", default_value = "10s", value_parser = |s: &str| duration_str::parse(s) )]
+    shutdown_timeout: Duration,
+
+    /// The path to a file that contains the VINs of the vehicles that each device is allowed to report
+    /// the status for. Each line of the file consists of a device ID followed by the VINs bound to the device,
+    /// separated by whitespace. If set, the status of a vehicle is only written to the InfluxDB server if
+    /// the vehicle is bound to the device identified by the authority of the message's source URI.
+    /// Note that only the hono transport prevents devices from spoofing the source URI.
+    #[arg(long = PARAM_VIN_BINDINGS_FILE, value_name = "FILE", env = "VIN_BINDINGS_FILE", value_parser = clap::builder::PathBufValueParser::new())]
+    vin_bindings_file: Option<PathBuf>,
+
     #[command(flatten)]
     influxdb_connection: InfluxConnectionConfig,
 
+    /// The bucket to write the data of a tenant to on the InfluxDB server, separated by commas.
+    /// May also be used multiple times. The data of tenants that no bucket has been set for is
+    /// written to the default bucket.
+    #[arg(long = "tenant-bucket", value_name = "TENANT=BUCKET", env = "TENANT_BUCKETS", value_delimiter = ',', value_parser = parse_tenant_bucket)]
+    tenant_buckets: Vec<(String, String)>,
+
+    #[command(flatten)]
+    influxdb_batching: BatchWriterConfig,
+
     #[command(subcommand)]
     transport: TransportType,
 }
@@ -72,13 +288,47 @@ struct FmsConsumerCommand {
 #[derive(Subcommand)]
 #[command(subcommand_required = true)]
 enum TransportType {
+    /// Reads VSS data from stdin or from a file (and the files it has been rotated to)
+    /// that has been written by the FMS Forwarder's file transport.
+    #[command(name = "file")]
+    File(FileTransportConfig),
+
     /// Consumes VSS data using the Eclipse Hono/Kafka based uProtocol transport.
     #[command(name = "hono")]
     Hono(HonoKafkaTransportConfig),
 
+    /// Consumes VSS data using an Apache Kafka based uProtocol transport.
+    #[command(name = "kafka")]
+    Kafka(KafkaTransportConfig),
+
+    /// Consumes VSS data using a generic MQTT 5 based uProtocol transport.
+    #[command(name = "mqtt")]
+    Mqtt(Mqtt5TransportConfig),
+
     /// Consumes VSS data using the Eclipse Zenoh based uProtocol transport.
     #[command(name = "zenoh")]
-    Zenoh(ZenohTransportConfig),
+    Zenoh {
+        #[command(flatten)]
+        config: ZenohTransportConfig,
+
+        #[command(flatten)]
+        status_queryable: VehicleStatusQueryableConfig,
+    },
+}
+
+fn parse_tenant_bucket(s: &str) -> Result<(String, String), String> {
+    config_properties::parse_key_value(s)
+        .ok()
+        .filter(|(_tenant_id, bucket)| !bucket.is_empty())
+        .ok_or_else(|| "must be of the form TENANT=BUCKET".to_string())
+}
+
+/// Waits for the process to receive a SIGTERM or SIGINT signal.
+async fn termination_requested(mut sigterm: Signal) {
+    tokio::select! {
+        _ = sigterm.recv() => info!("received SIGTERM"),
+        _ = tokio::signal::ctrl_c() => info!("received SIGINT"),
+    }
 }
 
 #[tokio::main]
@@ -86,30 +336,190 @@ pub async fn main() -> Result<(), Box<dyn std::error::Error>> {
     env_logger::init();
 
     let command = FmsConsumerCommand::parse();
+    let sigterm = signal(SignalKind::terminate())?;
 
+    let mut file_transport = None;
+    let mut hono_transport = None;
+    let mut kafka_transport = None;
+    let mut mqtt_transport = None;
+    let mut latest_vehicle_status = None;
+    let mut vehicle_status_queryable = None;
     let transport: Arc<dyn UTransport> = match command.transport {
-        TransportType::Hono(config) => HonoKafkaTransport::new(config).map(Arc::new)?,
-        TransportType::Zenoh(config) => {
-            let config = config.try_into()?;
+        TransportType::File(config) => {
+            let transport = FileTransport::new(&config).map(Arc::new)?;
+            file_transport = Some(transport.clone());
+            transport
+        }
+        TransportType::Hono(config) => {
+            if command.influxdb_batching.enabled && config.uses_manual_commit() {
+                // buffered measurements would be committed before they have been written
+                return Err(
+                    "--influxdb-batching cannot be used together with --kafka-manual-commit".into(),
+                );
+            }
+            let transport = HonoKafkaTransport::new(config).map(Arc::new)?;
+            hono_transport = Some(transport.clone());
+            transport
+        }
+        TransportType::Kafka(config) => {
+            let transport = KafkaTransport::new(&config).map(Arc::new)?;
+            kafka_transport = Some(transport.clone());
+            transport
+        }
+        TransportType::Mqtt(config) => {
+            let transport = Mqtt5Transport::new(&config).await.map(Arc::new)?;
+            mqtt_transport = Some(transport.clone());
+            transport
+        }
+        TransportType::Zenoh {
+            config,
+            status_queryable,
+        } => {
+            let config: zenoh_config::Config = config.try_into()?;
+            if let Some(key_prefix) = status_queryable.key_prefix {
+                let latest = Arc::new(LatestVehicleStatus::default());
+                vehicle_status_queryable = Some(
+                    VehicleStatusQueryable::declare(&config, &key_prefix, latest.clone()).await?,
+                );
+                latest_vehicle_status = Some(latest);
+            }
             UPTransportZenoh::new(config, command.local_uservice_uri)
                 .await
                 .map(Arc::new)?
         }
     };
 
-    let influx_writer = InfluxWriter::new(&command.influxdb_connection)?;
-    let listener = Arc::new(VehicleStatusListener { influx_writer });
+    let influx_writers = Arc::new(InfluxWriters::new(
+        &command.influxdb_connection,
+        &command.tenant_buckets,
+        &command.influxdb_batching,
+    )?);
+    let metrics_logger = command.influxdb_batching.enabled.then(|| {
+        let influx_writers = influx_writers.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(METRICS_LOG_INTERVAL);
+            loop {
+                interval.tick().await;
+                influx_writers.log_metrics();
+            }
+        })
+    });
+    let vin_bindings = match command.vin_bindings_file {
+        Some(path) => {
+            let vin_bindings = VinBindings::from_file(&path)?;
+            if hono_transport.is_none() {
+                // only the Hono transport derives the source URI from the authenticated device ID
+                warn!("VIN bindings are checked against the source URI set by the sender, which offers no protection against devices spoofing their identity with transports other than hono");
+            }
+            info!(
+                "using VIN bindings of {} devices from file {}",
+                vin_bindings.device_count(),
+                path.display()
+            );
+            Some(Arc::new(vin_bindings))
+        }
+        None => None,
+    };
+    let listener = Arc::new(VehicleStatusListener {
+        influx_writers: influx_writers.clone(),
+        vin_bindings: vin_bindings.clone(),
+        latest_vehicle_status,
+    });
+    let connection_status_listener = vin_bindings.map(|vin_bindings| {
+        Arc::new(ConnectionStatusListener {
+            influx_writers: influx_writers.clone(),
+            vin_bindings,
+        })
+    });
     info!(
         "Registering listener for vehicle status events [source filter: {}]",
         &command.vehicle_status_topic_filter.to_uri(false)
     );
     transport
-        .register_listener(&command.vehicle_status_topic_filter, None, listener)
+        .register_listener(&command.vehicle_status_topic_filter, None, listener.clone())
         .await
         .map_err(Box::new)?;
-    // do not let the Notifier that we use to receive and process
-    // Vehicle status notifications go out of scope
-    thread::park();
+    if let Some(connection_status_listener) = connection_status_listener.as_ref() {
+        info!(
+            "Registering listener for connection status events [source filter: {}]",
+            &command.connection_status_topic_filter.to_uri(false)
+        );
+        transport
+            .register_listener(
+                &command.connection_status_topic_filter,
+                None,
+                connection_status_listener.clone(),
+            )
+            .await
+            .map_err(Box::new)?;
+    } else {
+        info!("not recording connection status of devices because no VIN bindings have been configured");
+    }
 
+    // all listeners are in place, so that no message can get lost
+    if let Some(transport) = hono_transport.as_ref() {
+        transport.start();
+    }
+    if let Some(transport) = file_transport.as_ref() {
+        transport.start_replay();
+    }
+
+    termination_requested(sigterm).await;
+    info!("stopping FMS consumer");
+    let shutdown = async {
+        if let Some(transport) = hono_transport {
+            // stop consuming new messages while letting the listener
+            // finish writing the vehicle status currently being processed
+            transport.shutdown().await;
+        }
+        if let Some(transport) = kafka_transport {
+            transport.shutdown().await;
+        }
+        if let Err(e) = transport
+            .unregister_listener(&command.vehicle_status_topic_filter, None, listener)
+            .await
+        {
+            warn!(
+                "failed to unregister listener for vehicle status events: {}",
+                e
+            );
+        }
+        if let Some(connection_status_listener) = connection_status_listener {
+            if let Err(e) = transport
+                .unregister_listener(
+                    &command.connection_status_topic_filter,
+                    None,
+                    connection_status_listener,
+                )
+                .await
+            {
+                warn!(
+                    "failed to unregister listener for connection status events: {}",
+                    e
+                );
+            }
+        }
+        if let Some(transport) = mqtt_transport {
+            if let Err(e) = transport.disconnect(command.shutdown_timeout).await {
+                warn!("failed to disconnect from MQTT broker: {}", e);
+            }
+        }
+        if let Some(metrics_logger) = metrics_logger {
+            metrics_logger.abort();
+        }
+        // write the measurements that are still buffered (if any)
+        influx_writers.close().await;
+    };
+    if tokio::time::timeout(command.shutdown_timeout, shutdown)
+        .await
+        .is_err()
+    {
+        warn!("shutdown timeout has elapsed before all vehicle status events have been processed");
+        influx_writers.log_discarded();
+    }
+    // dropping the transport and the queryable closes their sessions (if any)
+    drop(vehicle_status_queryable);
+    drop(transport);
+    info!("FMS consumer has been stopped");
     Ok(())
 }
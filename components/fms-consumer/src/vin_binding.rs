@@ -0,0 +1,142 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Bindings of vehicles (VINs) to the devices that are allowed to report their status.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use up_transport_hono_kafka::tenant;
+
+/// The VINs that each device is allowed to report vehicle status for.
+///
+/// The bindings are read from a text file containing one line per device. Each line consists of
+/// the device ID followed by the VINs bound to the device, separated by whitespace, e.g.
+///
+/// ```text
+/// # device ID   VINs
+/// truck-4711    WBA12345678901234 WBA98765432109876
+/// ```
+///
+/// Empty lines and lines starting with `#` are ignored.
+///
+/// Devices are looked up by the authority of the messages that they have sent. An authority of the form
+/// `<device-id>.<tenant-id>` that is not bound to any VINs itself is also looked up by its device ID, so
+/// that the bindings may use the device ID only, e.g. if data of a single tenant is consumed.
+#[derive(Debug, Default)]
+pub struct VinBindings {
+    vins_by_device: HashMap<String, HashSet<String>>,
+}
+
+impl VinBindings {
+    /// Reads VIN bindings from a file.
+    pub fn from_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(Self::parse(&content))
+    }
+
+    fn parse(content: &str) -> Self {
+        let mut vins_by_device: HashMap<String, HashSet<String>> = HashMap::new();
+        content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .for_each(|line| {
+                let mut tokens = line.split_whitespace();
+                if let Some(device_id) = tokens.next() {
+                    vins_by_device
+                        .entry(device_id.to_string())
+                        .or_default()
+                        .extend(tokens.map(str::to_string));
+                }
+            });
+        VinBindings { vins_by_device }
+    }
+
+    /// Returns the number of devices that VINs are bound to.
+    pub fn device_count(&self) -> usize {
+        self.vins_by_device.len()
+    }
+
+    fn vins_of(&self, device_id: &str) -> Option<&HashSet<String>> {
+        self.vins_by_device.get(device_id).or_else(|| {
+            tenant::split_device_authority(device_id)
+                .and_then(|(device_id, _tenant_id)| self.vins_by_device.get(device_id))
+        })
+    }
+
+    /// Gets the VINs of the vehicles that are bound to a device.
+    pub fn vins<'a>(&'a self, device_id: &str) -> impl Iterator<Item = &'a str> {
+        self.vins_of(device_id)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+
+    /// Checks if a device is allowed to report the status of a vehicle.
+    pub fn is_bound(&self, device_id: &str, vin: &str) -> bool {
+        self.vins_of(device_id)
+            .is_some_and(|vins| vins.contains(vin))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bindings() {
+        let bindings = VinBindings::parse(
+            r#"
+            # device ID   VINs
+            truck-1       VIN1 VIN2
+
+            truck-2       VIN3
+            truck-1       VIN4
+            "#,
+        );
+        assert_eq!(bindings.device_count(), 2);
+        assert!(bindings.is_bound("truck-1", "VIN1"));
+        assert!(bindings.is_bound("truck-1", "VIN2"));
+        assert!(bindings.is_bound("truck-1", "VIN4"));
+        assert!(bindings.is_bound("truck-2", "VIN3"));
+        assert!(!bindings.is_bound("truck-2", "VIN1"));
+        assert!(!bindings.is_bound("unknown", "VIN1"));
+        assert_eq!(bindings.vins("truck-2").collect::<Vec<_>>(), vec!["VIN3"]);
+        assert_eq!(bindings.vins("unknown").count(), 0);
+    }
+
+    #[test]
+    fn test_bindings_match_device_id_of_tenant_authority() {
+        let bindings = VinBindings::parse(
+            r#"
+            truck-1             VIN1
+            truck-2.customer-a  VIN2
+            "#,
+        );
+        assert!(bindings.is_bound("truck-1.customer-a", "VIN1"));
+        assert!(bindings.is_bound("truck-2.customer-a", "VIN2"));
+        assert!(!bindings.is_bound("truck-2.customer-b", "VIN2"));
+        assert!(!bindings.is_bound("truck-2", "VIN2"));
+        assert_eq!(
+            bindings.vins("truck-1.customer-b").collect::<Vec<_>>(),
+            vec!["VIN1"]
+        );
+    }
+}
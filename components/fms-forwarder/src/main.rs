@@ -17,22 +17,36 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{str::FromStr, sync::Arc};
+use std::{str::FromStr, sync::Arc, time::Duration};
 
 use clap::{Parser, Subcommand};
 use fms_proto::fms::VehicleStatus;
+use fms_zenoh::vehicle_status::{
+    LatestVehicleStatus, VehicleStatusQueryable, VehicleStatusQueryableConfig,
+};
 use fms_zenoh::ZenohTransportConfig;
+use hono_wire_format::vehicle_status_priority;
 use log::{info, warn};
-use tokio::sync::mpsc;
+use tokio::{
+    signal::unix::{signal, Signal, SignalKind},
+    sync::{mpsc, watch},
+    time::Instant,
+};
 use up_rust::{
     communication::{CallOptions, Publisher, SimplePublisher, UPayload},
     LocalUriProvider, StaticUriProvider, UTransport, UUri,
 };
-use up_transport_hono_mqtt::{HonoMqttTransport, HonoMqttTransportConfig};
-use up_transport_zenoh::UPTransportZenoh;
+use up_transport_file::{FileTransport, FileTransportConfig};
+use up_transport_hono_http::{HonoHttpTransport, HonoHttpTransportConfig};
+use up_transport_hono_mqtt::{ConnectionState, HonoMqttTransport, HonoMqttTransportConfig};
+use up_transport_kafka::{KafkaTransport, KafkaTransportConfig};
+use up_transport_mqtt5::{Mqtt5Transport, Mqtt5TransportConfig};
+use up_transport_zenoh::{zenoh_config, UPTransportZenoh};
 
 mod vehicle_abstraction;
 
+const PARAM_SHUTDOWN_TIMEOUT: &str = "shutdown-timeout";
+
 /// Forwards FMS related VSS data points to a back end system using uProtocol.
 #[derive(Parser)]
 #[command(version, about, long_about = None, arg_required_else_help = true)]
@@ -41,6 +55,11 @@ struct FmsForwarderCommand {
     #[arg(long = "topic", value_name = "URI", env = "TOPIC", default_value = "up://fms-forwarder/D100/1/D100", value_parser = up_rust::UUri::from_str )]
     vehicle_status_topic: UUri,
 
+    /// The maximum time to wait for queued vehicle status events to be published
+    /// when shutting down, e.g. 10s or 1m.
+    #[arg(long = PARAM_SHUTDOWN_TIMEOUT, value_name = "DURATION_SPEC", env = "SHUTDOWN_TIMEOUT", default_value = "10s", value_parser = |s: &str| duration_str::parse(s) )]
+    shutdown_timeout: Duration,
+
     #[command(flatten)]
     databroker_connection: vehicle_abstraction::KuksaDatabrokerClientConfig,
 
@@ -51,13 +70,83 @@ struct FmsForwarderCommand {
 #[derive(Subcommand)]
 #[command(subcommand_required = true)]
 enum TransportType {
+    /// Writes VSS data to stdout or a (rotating) file, e.g. for capturing data in the field.
+    #[command(name = "file")]
+    File(FileTransportConfig),
+
     /// Forwards VSS data via Eclipse uProtocol using Eclipse Hono based transport.
     #[command(name = "hono")]
     Hono(HonoMqttTransportConfig),
 
+    /// Forwards VSS data via Eclipse uProtocol using Eclipse Hono's HTTP adapter.
+    #[command(name = "hono-http")]
+    HonoHttp(HonoHttpTransportConfig),
+
+    /// Forwards VSS data via Eclipse uProtocol using an Apache Kafka based transport.
+    #[command(name = "kafka")]
+    Kafka(KafkaTransportConfig),
+
+    /// Forwards VSS data via Eclipse uProtocol using a generic MQTT 5 based transport.
+    #[command(name = "mqtt")]
+    Mqtt(Mqtt5TransportConfig),
+
     /// Forwards VSS data via Eclipse uProtocol using Eclipse Zenoh based transport.
     #[command(name = "zenoh")]
-    Zenoh(ZenohTransportConfig),
+    Zenoh {
+        #[command(flatten)]
+        config: ZenohTransportConfig,
+
+        #[command(flatten)]
+        status_queryable: VehicleStatusQueryableConfig,
+    },
+}
+
+async fn publish_vehicle_status(
+    publisher: &SimplePublisher,
+    resource_id: u16,
+    vehicle_status: VehicleStatus,
+) {
+    let priority = vehicle_status_priority(&vehicle_status);
+    match UPayload::try_from_protobuf(vehicle_status) {
+        Ok(payload) => {
+            if let Err(e) = publisher
+                .publish(
+                    resource_id,
+                    CallOptions::for_publish(None, None, Some(priority)),
+                    Some(payload),
+                )
+                .await
+            {
+                warn!("failed to publish vehicle status event: {}", e);
+            }
+        }
+        Err(e) => {
+            warn!("failed to serialize vehicle status: {}", e);
+        }
+    }
+}
+
+/// Waits for the state of the connection to the MQTT endpoint to change.
+///
+/// Never completes if the forwarder does not use an MQTT based transport.
+async fn connection_state_changed(
+    connection_state: &mut Option<watch::Receiver<ConnectionState>>,
+) -> Option<ConnectionState> {
+    match connection_state {
+        Some(receiver) => match receiver.changed().await {
+            Ok(()) => Some(*receiver.borrow_and_update()),
+            Err(_) => None,
+        },
+        None => std::future::pending().await,
+    }
+}
+
+/// Waits for the process to receive a SIGTERM or SIGINT signal.
+async fn termination_requested(mut sigterm: Signal) {
+    tokio::select! {
+        _ = sigterm.recv() => info!("received SIGTERM"),
+        _ = tokio::signal::ctrl_c() => info!("received SIGINT"),
+    }
 }
 
 #[tokio::main]
@@ -65,11 +154,39 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     env_logger::init();
     let command = FmsForwarderCommand::parse();
     let uri_provider = StaticUriProvider::try_from(&command.vehicle_status_topic).map(Arc::new)?;
+    let sigterm = signal(SignalKind::terminate())?;
 
+    let mut hono_transport = None;
+    let mut mqtt_transport = None;
+    let mut latest_vehicle_status: Option<Arc<LatestVehicleStatus>> = None;
+    let mut vehicle_status_queryable = None;
     let transport: Arc<dyn UTransport> = match command.transport {
-        TransportType::Hono(config) => HonoMqttTransport::new(&config).await.map(Arc::new)?,
-        TransportType::Zenoh(config) => {
-            let zenoh_config = config.try_into()?;
+        TransportType::File(config) => FileTransport::new(&config).map(Arc::new)?,
+        TransportType::Hono(config) => {
+            let transport = HonoMqttTransport::new(&config).await.map(Arc::new)?;
+            hono_transport = Some(transport.clone());
+            transport
+        }
+        TransportType::HonoHttp(config) => HonoHttpTransport::new(&config).map(Arc::new)?,
+        TransportType::Kafka(config) => KafkaTransport::new(&config).map(Arc::new)?,
+        TransportType::Mqtt(config) => {
+            let transport = Mqtt5Transport::new(&config).await.map(Arc::new)?;
+            mqtt_transport = Some(transport.clone());
+            transport
+        }
+        TransportType::Zenoh {
+            config,
+            status_queryable,
+        } => {
+            let zenoh_config: zenoh_config::Config = config.try_into()?;
+            if let Some(key_prefix) = status_queryable.key_prefix {
+                let latest = Arc::new(LatestVehicleStatus::default());
+                vehicle_status_queryable = Some(
+                    VehicleStatusQueryable::declare(&zenoh_config, &key_prefix, latest.clone())
+                        .await?,
+                );
+                latest_vehicle_status = Some(latest);
+            }
             UPTransportZenoh::new(zenoh_config, uri_provider.get_source_uri())
                 .await
                 .map(Arc::new)?
@@ -83,24 +200,103 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let (tx, mut rx) = mpsc::channel::<VehicleStatus>(30);
     vehicle_abstraction::init(&command.databroker_connection, tx).await?;
 
-    while let Some(vehicle_status) = rx.recv().await {
-        match UPayload::try_from_protobuf(vehicle_status) {
-            Ok(payload) => {
-                if let Err(e) = publisher
-                    .publish(
-                        origin_resource_id,
-                        CallOptions::for_publish(None, None, None),
-                        Some(payload),
-                    )
-                    .await
-                {
-                    warn!("failed to publish vehicle status event: {}", e);
+    // vehicle status is only published while the transport is connected,
+    // otherwise it is kept in the queue and the Databroker is not being polled,
+    // unless the transport persists the messages that it cannot deliver right away
+    let mut connection_state = hono_transport
+        .as_ref()
+        .map(|transport| transport.connection_state())
+        .or_else(|| {
+            mqtt_transport
+                .as_ref()
+                .map(|transport| transport.connection_state())
+        });
+    let pause_while_disconnected = hono_transport
+        .as_ref()
+        .map(|transport| !transport.has_persistent_buffer())
+        .or_else(|| {
+            mqtt_transport
+                .as_ref()
+                .map(|transport| !transport.has_persistent_buffer())
+        })
+        .unwrap_or(false);
+    let termination = termination_requested(sigterm);
+    tokio::pin!(termination);
+    loop {
+        let connected = !pause_while_disconnected
+            || connection_state
+                .as_ref()
+                .is_none_or(|state| *state.borrow() == ConnectionState::Connected);
+        tokio::select! {
+            _ = &mut termination => break,
+            state = connection_state_changed(&mut connection_state) => match state {
+                Some(ConnectionState::Connected) => {
+                    info!("connected to MQTT endpoint, publishing vehicle status");
                 }
+                Some(state) if pause_while_disconnected => {
+                    warn!(
+                        "not connected to MQTT endpoint ({:?}), pausing publishing of vehicle status [queued: {}]",
+                        state,
+                        rx.len()
+                    );
+                }
+                Some(state) => {
+                    warn!(
+                        "not connected to MQTT endpoint ({:?}), storing vehicle status until it can be delivered",
+                        state
+                    );
+                }
+                None => connection_state = None,
+            },
+            next = rx.recv(), if connected => match next {
+                Some(vehicle_status) => {
+                    if let Some(latest_vehicle_status) = latest_vehicle_status.as_ref() {
+                        latest_vehicle_status.update(&vehicle_status);
+                    }
+                    publish_vehicle_status(&publisher, origin_resource_id, vehicle_status).await;
+                }
+                None => break,
+            },
+        }
+    }
+
+    info!("stopping FMS forwarder");
+    let deadline = Instant::now() + command.shutdown_timeout;
+    // stop accepting new vehicle status from the Databroker
+    // but publish the ones that have already been queued
+    rx.close();
+    let drain_queue = async {
+        while let Some(vehicle_status) = rx.recv().await {
+            if let Some(latest_vehicle_status) = latest_vehicle_status.as_ref() {
+                latest_vehicle_status.update(&vehicle_status);
             }
-            Err(e) => {
-                warn!("failed to serialize vehicle status: {}", e);
-            }
+            publish_vehicle_status(&publisher, origin_resource_id, vehicle_status).await;
+        }
+    };
+    if tokio::time::timeout_at(deadline, drain_queue)
+        .await
+        .is_err()
+    {
+        warn!(
+            "shutdown timeout has elapsed, discarding {} queued vehicle status events",
+            rx.len()
+        );
+    }
+
+    let remaining_time = deadline.saturating_duration_since(Instant::now());
+    if let Some(transport) = hono_transport {
+        if let Err(e) = transport.disconnect(remaining_time).await {
+            warn!("failed to disconnect from MQTT endpoint: {}", e);
+        }
+    }
+    if let Some(transport) = mqtt_transport {
+        if let Err(e) = transport.disconnect(remaining_time).await {
+            warn!("failed to disconnect from MQTT broker: {}", e);
         }
     }
+    // dropping the publisher and the queryable closes the underlying sessions (if any)
+    drop(vehicle_status_queryable);
+    drop(publisher);
+    info!("FMS forwarder has been stopped");
     Ok(())
 }
@@ -20,18 +20,24 @@
 //! An abstraction of a vehicle's (current) status based on
 //! [Eclipse kuksa.val Databroker](https://github.com/eclipse/kuksa.val).
 //!
-use std::{collections::HashMap, error::Error, fmt::Display, time::Duration};
-
-use clap::Args;
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Display,
+    time::{Duration, SystemTime},
+};
+
+use clap::{Args, ValueEnum};
 use http::Uri;
 use kuksa_rust_sdk::kuksa::{common::ClientTraitV2, val::v2::KuksaClientV2};
 use kuksa_rust_sdk::v2_proto::value::TypedValue;
-use kuksa_rust_sdk::v2_proto::IncompatibleValueTypeError;
+use kuksa_rust_sdk::v2_proto::{Datapoint, IncompatibleValueTypeError};
 use log::{debug, error, info, warn};
-use protobuf::MessageField;
+use protobuf::{well_known_types::timestamp::Timestamp, MessageField};
 use tokio::sync::mpsc::Sender;
 
 use fms_proto::fms::{TellTaleInfo, Trigger, VehicleStatus};
+use fms_proto::TRIGGER_TIMER;
 
 const SNAPSHOT_VSS_PATHS: &[&str] = &[
     vss::VSS_VEHICLE_CHASSIS_PARKINGBRAKE_ISENGAGED,
@@ -90,10 +96,10 @@ const TRIGGER_DRIVER_LOGOUT: &str = "DRIVER_LOGOUT";
 const TRIGGER_ENGINE_ON: &str = "ENGINE_ON";
 const TRIGGER_ENGINE_OFF: &str = "ENGINE_OFF";
 const TRIGGER_TELL_TALE: &str = "TELL_TALE";
-const TRIGGER_TIMER: &str = "TIMER";
 
 const PARAM_DATABROKER_URI: &str = "databroker-uri";
 const PARAM_TIMER_INTERVAL: &str = "timer-interval";
+const PARAM_TIME_SOURCE: &str = "time-source";
 
 mod kuksa;
 mod vss;
@@ -107,6 +113,27 @@ pub struct KuksaDatabrokerClientConfig {
     /// The time period to wait after polling FMS snapshot data from the kuksa.val Databroker, e.g 5m10s or 1h15m.
     #[arg(long = PARAM_TIMER_INTERVAL, value_name = "DURATION_SPEC", env = "TIMER_INTERVAL", default_value = "5s", value_parser = |s: &str| duration_str::parse(s) )]
     timer_interval: Duration,
+
+    /// The source of the timestamps to use for the vehicle status' creation time and the triggers' event time.
+    #[arg(long = PARAM_TIME_SOURCE, value_name = "SOURCE", env = "TIME_SOURCE", default_value = "system", value_enum)]
+    time_source: TimeSource,
+}
+
+/// The source of the timestamps that are used for the time at which a vehicle status has been
+/// created and the time at which the event that triggered the reporting of the status has occurred.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum TimeSource {
+    /// The forwarder's system clock.
+    #[value(name = "system")]
+    System,
+    /// The time reported by the GNSS receiver in Vehicle.CurrentLocation.Timestamp.
+    /// The triggers' event times are corrected by the offset between the system clock and the GNSS time.
+    #[value(name = "gnss")]
+    Gnss,
+    /// The timestamps that the Databroker has recorded for the data points.
+    /// The creation time is the most recent timestamp of all of the snapshot's data points.
+    #[value(name = "databroker")]
+    Databroker,
 }
 
 /// Indicates a problem while invoking a Databroker operation.
@@ -141,6 +168,66 @@ enum FmsTrigger {
     TellTale(TellTaleInfo),
 }
 
+/// A trigger along with the information about when the event that caused it has occurred.
+struct TriggerEvent {
+    fms_trigger: FmsTrigger,
+    /// The (system) time at which the forwarder has been notified about the event.
+    received: Timestamp,
+    /// The Databroker's timestamp of the data point that has caused the event.
+    datapoint_timestamp: Option<Timestamp>,
+}
+
+impl TriggerEvent {
+    fn new(fms_trigger: FmsTrigger, datapoint_timestamp: Option<Timestamp>) -> Self {
+        TriggerEvent {
+            fms_trigger,
+            received: Timestamp::now(),
+            datapoint_timestamp,
+        }
+    }
+
+    /// Determines the point in time at which the event has occurred.
+    ///
+    /// The `snapshot_time` is the creation time of the vehicle status that has been
+    /// read from the Databroker at (system) time `now` in response to the event.
+    fn event_time(
+        &self,
+        time_source: TimeSource,
+        snapshot_time: Option<&Timestamp>,
+        now: &Timestamp,
+    ) -> Timestamp {
+        match (time_source, snapshot_time) {
+            (TimeSource::Databroker, _) => self
+                .datapoint_timestamp
+                .clone()
+                .unwrap_or_else(|| self.received.clone()),
+            (TimeSource::Gnss, Some(gnss_time)) => shift(&self.received, now, gnss_time),
+            _ => self.received.clone(),
+        }
+    }
+}
+
+/// Shifts an instant by the offset between two other instants.
+fn shift(instant: &Timestamp, from: &Timestamp, to: &Timestamp) -> Timestamp {
+    let instant: SystemTime = instant.clone().into();
+    let from: SystemTime = from.clone().into();
+    let to: SystemTime = to.clone().into();
+    let shifted = match to.duration_since(from) {
+        Ok(offset) => instant.checked_add(offset),
+        Err(e) => instant.checked_sub(e.duration()),
+    };
+    Timestamp::from(shifted.unwrap_or(instant))
+}
+
+fn to_timestamp(datapoint: &Datapoint) -> Option<Timestamp> {
+    datapoint.timestamp.as_ref().map(|timestamp| {
+        let mut result = Timestamp::new();
+        result.seconds = timestamp.seconds;
+        result.nanos = timestamp.nanos;
+        result
+    })
+}
+
 impl FmsTrigger {
     fn new_trigger(type_: &str) -> Trigger {
         let mut trigger = Trigger::new();
@@ -271,6 +358,7 @@ impl TryFrom<(String, &TypedValue)> for FmsTrigger {
 
 struct KuksaValDatabroker {
     client: Box<KuksaClientV2>,
+    time_source: TimeSource,
 }
 
 impl KuksaValDatabroker {
@@ -290,6 +378,7 @@ impl KuksaValDatabroker {
                 let client = KuksaClientV2::new(uri);
                 KuksaValDatabroker {
                     client: Box::new(client),
+                    time_source: config.time_source,
                 }
             })
     }
@@ -321,8 +410,12 @@ impl KuksaValDatabroker {
             }
             Ok(get_response) => {
                 let mut vss_data = HashMap::new();
+                let mut datapoint_timestamps = Vec::new();
                 let mut idx = 0usize;
                 get_response.iter().for_each(|data_entry| {
+                    if let Some(timestamp) = to_timestamp(data_entry) {
+                        datapoint_timestamps.push(timestamp);
+                    }
                     if let (name, Some(value)) = (
                         SNAPSHOT_VSS_PATHS[idx],
                         data_entry
@@ -335,14 +428,23 @@ impl KuksaValDatabroker {
                     }
                     idx += 1;
                 });
-                kuksa::new_vehicle_status(vss_data)
+                kuksa::new_vehicle_status(vss_data).map(|mut vehicle_status| {
+                    if let Some(snapshot_time) = kuksa::snapshot_time(
+                        self.time_source,
+                        &vehicle_status,
+                        &datapoint_timestamps,
+                    ) {
+                        vehicle_status.created = MessageField::some(snapshot_time);
+                    }
+                    vehicle_status
+                })
             }
         }
     }
 
     pub async fn register_triggers(
         &mut self,
-        sender: Sender<FmsTrigger>,
+        sender: Sender<TriggerEvent>,
     ) -> Result<(), DatabrokerError> {
         let paths = TRIGGER_VSS_PATHS.iter().map(|v| v.to_string()).collect();
 
@@ -358,7 +460,10 @@ impl KuksaValDatabroker {
                                     .and_then(|v| v.typed_value.as_ref())
                                 {
                                     if let Ok(trigger) = FmsTrigger::try_from((path, value)) {
-                                        let _ = sender.send(trigger).await;
+                                        let datapoint_timestamp = to_timestamp(&datapoint);
+                                        let _ = sender
+                                            .send(TriggerEvent::new(trigger, datapoint_timestamp))
+                                            .await;
                                     }
                                 } else {
                                     debug!(
@@ -388,15 +493,16 @@ pub async fn init(
     status_publisher: Sender<VehicleStatus>,
 ) -> Result<(), DatabrokerError> {
     let timer_interval = config.timer_interval.to_owned();
+    let time_source = config.time_source;
 
     let mut databroker = KuksaValDatabroker::new(config).await?;
-    let (tx, mut rx) = tokio::sync::mpsc::channel::<FmsTrigger>(50);
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<TriggerEvent>(50);
     databroker.register_triggers(tx.clone()).await?;
 
     tokio::task::spawn(async move {
         let mut current_status = VehicleStatus::new();
 
-        while let Some(fms_trigger) = rx.recv().await {
+        while let Some(trigger_event) = rx.recv().await {
             match databroker.get_vehicle_status().await {
                 Err(e) => {
                     warn!(
@@ -407,8 +513,13 @@ pub async fn init(
                 Ok(mut new_vehicle_status) => {
                     let last_known_status = current_status.clone();
                     current_status = new_vehicle_status.clone();
-                    let mut trigger = fms_trigger.as_trigger();
-                    match fms_trigger {
+                    let mut trigger = trigger_event.fms_trigger.as_trigger();
+                    trigger.event_time = MessageField::some(trigger_event.event_time(
+                        time_source,
+                        new_vehicle_status.created.as_ref(),
+                        &Timestamp::now(),
+                    ));
+                    match trigger_event.fms_trigger {
                         FmsTrigger::Driver1Login => {
                             info!("driver one has logged in");
                             trigger.driver = new_vehicle_status.driver1_id.clone();
@@ -471,12 +582,10 @@ pub async fn init(
                         }
                     }
                     new_vehicle_status.trigger = MessageField::some(trigger);
-                    match status_publisher.send(new_vehicle_status).await {
-                        Ok(_) => {}
-                        Err(e) => {
-                            warn!("failed to send new vehicle status via channel: {}", e);
-                        }
-                    };
+                    if status_publisher.send(new_vehicle_status).await.is_err() {
+                        info!("vehicle status channel has been closed, stopping processing of triggers");
+                        break;
+                    }
                 }
             }
         }
@@ -486,8 +595,100 @@ pub async fn init(
     tokio::task::spawn(async move {
         loop {
             tokio::time::sleep(timer_interval).await;
-            let _ = timer_sender.send(FmsTrigger::Timer).await;
+            if timer_sender
+                .send(TriggerEvent::new(FmsTrigger::Timer, None))
+                .await
+                .is_err()
+            {
+                break;
+            }
         }
     });
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timestamp(seconds: i64, nanos: i32) -> Timestamp {
+        let mut timestamp = Timestamp::new();
+        timestamp.seconds = seconds;
+        timestamp.nanos = nanos;
+        timestamp
+    }
+
+    fn trigger_event(received: Timestamp, datapoint_timestamp: Option<Timestamp>) -> TriggerEvent {
+        TriggerEvent {
+            fms_trigger: FmsTrigger::EngineOn,
+            received,
+            datapoint_timestamp,
+        }
+    }
+
+    #[test]
+    fn test_event_time_uses_system_clock() {
+        let event = trigger_event(timestamp(1000, 0), Some(timestamp(900, 0)));
+        let gnss_time = timestamp(2000, 0);
+        assert_eq!(
+            event.event_time(TimeSource::System, Some(&gnss_time), &timestamp(1010, 0)),
+            timestamp(1000, 0)
+        );
+    }
+
+    #[test]
+    fn test_event_time_uses_datapoint_timestamp() {
+        let now = timestamp(1010, 0);
+        let event = trigger_event(timestamp(1000, 0), Some(timestamp(900, 500)));
+        assert_eq!(
+            event.event_time(TimeSource::Databroker, None, &now),
+            timestamp(900, 500)
+        );
+        // the time of reception is used if the Databroker has not recorded a timestamp
+        let event = trigger_event(timestamp(1000, 0), None);
+        assert_eq!(
+            event.event_time(TimeSource::Databroker, None, &now),
+            timestamp(1000, 0)
+        );
+    }
+
+    #[test]
+    fn test_event_time_is_corrected_by_gnss_offset() {
+        // the system clock is 100.25s behind the GNSS time
+        let event = trigger_event(timestamp(1000, 0), None);
+        let gnss_time = timestamp(1110, 250_000_000);
+        assert_eq!(
+            event.event_time(TimeSource::Gnss, Some(&gnss_time), &timestamp(1010, 0)),
+            timestamp(1100, 250_000_000)
+        );
+    }
+
+    #[test]
+    fn test_event_time_falls_back_to_system_clock_without_gnss_time() {
+        let event = trigger_event(timestamp(1000, 0), Some(timestamp(900, 0)));
+        assert_eq!(
+            event.event_time(TimeSource::Gnss, None, &timestamp(1010, 0)),
+            timestamp(1000, 0)
+        );
+    }
+
+    #[test]
+    fn test_shift_supports_negative_and_positive_offsets() {
+        let instant = timestamp(1000, 500_000_000);
+        // positive offset
+        assert_eq!(
+            shift(&instant, &timestamp(2000, 0), &timestamp(2030, 600_000_000)),
+            timestamp(1031, 100_000_000)
+        );
+        // negative offset
+        assert_eq!(
+            shift(&instant, &timestamp(2030, 600_000_000), &timestamp(2000, 0)),
+            timestamp(969, 900_000_000)
+        );
+        // no offset
+        assert_eq!(
+            shift(&instant, &timestamp(2000, 0), &timestamp(2000, 0)),
+            instant
+        );
+    }
+}
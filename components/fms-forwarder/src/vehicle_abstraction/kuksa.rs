@@ -26,7 +26,37 @@ use std::collections::HashMap;
 use crate::vehicle_abstraction::vss;
 use fms_proto::fms::VehicleStatus;
 
-use super::DatabrokerError;
+use super::{DatabrokerError, TimeSource};
+
+/// Determines the point in time at which a vehicle status snapshot has been taken.
+///
+/// Returns `None` if the system clock is to be used or if the time source
+/// does not provide a timestamp for the snapshot.
+pub fn snapshot_time(
+    time_source: TimeSource,
+    vehicle_status: &VehicleStatus,
+    datapoint_timestamps: &[Timestamp],
+) -> Option<Timestamp> {
+    match time_source {
+        TimeSource::System => None,
+        TimeSource::Gnss => {
+            let gnss_time = vehicle_status
+                .snapshot_data
+                .as_ref()
+                .and_then(|snapshot_data| snapshot_data.gnss_position.as_ref())
+                .and_then(|gnss_position| gnss_position.instant.as_ref())
+                .cloned();
+            if gnss_time.is_none() {
+                debug!("vehicle status contains no GNSS time, using system clock instead");
+            }
+            gnss_time
+        }
+        TimeSource::Databroker => datapoint_timestamps
+            .iter()
+            .max_by_key(|timestamp| (timestamp.seconds, timestamp.nanos))
+            .cloned(),
+    }
+}
 
 pub fn new_vehicle_status(
     data: HashMap<String, TypedValue>,
@@ -221,3 +251,60 @@ pub fn new_vehicle_status(
     }
     Ok(vehicle_status)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timestamp(seconds: i64, nanos: i32) -> Timestamp {
+        let mut timestamp = Timestamp::new();
+        timestamp.seconds = seconds;
+        timestamp.nanos = nanos;
+        timestamp
+    }
+
+    #[test]
+    fn test_snapshot_time_uses_newest_databroker_timestamp() {
+        let vehicle_status = VehicleStatus::new();
+        let datapoint_timestamps = [
+            timestamp(1000, 900),
+            timestamp(1001, 100),
+            timestamp(1001, 50),
+            timestamp(999, 999_999_999),
+        ];
+        assert_eq!(
+            snapshot_time(
+                TimeSource::Databroker,
+                &vehicle_status,
+                &datapoint_timestamps
+            ),
+            Some(timestamp(1001, 100))
+        );
+        assert_eq!(
+            snapshot_time(TimeSource::Databroker, &vehicle_status, &[]),
+            None
+        );
+        assert_eq!(
+            snapshot_time(TimeSource::System, &vehicle_status, &datapoint_timestamps),
+            None
+        );
+    }
+
+    #[test]
+    fn test_snapshot_time_uses_gnss_time() {
+        let mut vehicle_status = VehicleStatus::new();
+        // the system clock is used if the vehicle status contains no GNSS time
+        assert_eq!(snapshot_time(TimeSource::Gnss, &vehicle_status, &[]), None);
+
+        vehicle_status
+            .snapshot_data
+            .mut_or_insert_default()
+            .gnss_position
+            .mut_or_insert_default()
+            .instant = MessageField::some(timestamp(1234, 0));
+        assert_eq!(
+            snapshot_time(TimeSource::Gnss, &vehicle_status, &[timestamp(2000, 0)]),
+            Some(timestamp(1234, 0))
+        );
+    }
+}
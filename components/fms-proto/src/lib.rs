@@ -18,3 +18,6 @@
 // SPDX-License-Identifier: Apache-2.0
 
 include!(concat!(env!("OUT_DIR"), "/fms/mod.rs"));
+
+/// The type of trigger of a vehicle status that has been created periodically.
+pub const TRIGGER_TIMER: &str = "TIMER";
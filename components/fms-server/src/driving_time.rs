@@ -0,0 +1,574 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Derives drivers' working state timelines and driving time totals from the
+//! tachograph working states contained in (a series of) vehicle statuses.
+//!
+//! The driving time rules being checked are a simplified version of the rules defined
+//! by Regulation (EC) No 561/2006. In particular, days and weeks are considered to be
+//! UTC calendar days and weeks (starting on Monday) instead of being delimited by the
+//! driver's daily and weekly rest periods.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, Utc};
+
+use crate::models::status::DriverWorkingStateProperty;
+use crate::models::working_state::{
+    DailyDrivingTimeObject, DriverWorkingStatesObject, DrivingTimeRuleProperty,
+    DrivingTimeViolationObject, WeeklyDrivingTimeObject, WorkingStateIntervalObject,
+};
+
+const HOUR: i64 = 3600;
+const MINUTE: i64 = 60;
+
+const MAX_CONTINUOUS_DRIVING: i64 = 4 * HOUR + 30 * MINUTE;
+const MAX_DAILY_DRIVING: i64 = 9 * HOUR;
+const MAX_EXTENDED_DAILY_DRIVING: i64 = 10 * HOUR;
+const MAX_EXTENDED_DAYS_PER_WEEK: usize = 2;
+const MAX_WEEKLY_DRIVING: i64 = 56 * HOUR;
+const MAX_FORTNIGHTLY_DRIVING: i64 = 90 * HOUR;
+const MIN_BREAK: i64 = 45 * MINUTE;
+const MIN_SPLIT_BREAK_FIRST_PART: i64 = 15 * MINUTE;
+const MIN_SPLIT_BREAK_SECOND_PART: i64 = 30 * MINUTE;
+
+/// The identity and working state of a driver as reported in a vehicle status.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DriverSample {
+    pub driver_id: String,
+    pub working_state: Option<DriverWorkingStateProperty>,
+}
+
+/// The tachograph related data contained in a single vehicle status.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkingStateSample {
+    pub vin: String,
+    pub created_date_time: DateTime<Utc>,
+    /// The drivers in the first and second tachograph slot.
+    pub drivers: [Option<DriverSample>; 2],
+}
+
+struct OpenInterval {
+    vin: String,
+    working_state: DriverWorkingStateProperty,
+    start: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct TimelineBuilder {
+    open_intervals: HashMap<String, OpenInterval>,
+    slot_occupants: HashMap<(String, usize), String>,
+    intervals: BTreeMap<String, Vec<WorkingStateIntervalObject>>,
+}
+
+impl TimelineBuilder {
+    fn close(&mut self, driver_id: &str, at: DateTime<Utc>) {
+        if let Some(open) = self.open_intervals.remove(driver_id) {
+            if open.start < at {
+                self.intervals
+                    .entry(driver_id.to_string())
+                    .or_default()
+                    .push(WorkingStateIntervalObject {
+                        vin: open.vin,
+                        working_state: open.working_state,
+                        start_date_time: open.start,
+                        stop_date_time: at,
+                        duration_seconds: (at - open.start).num_seconds(),
+                    });
+            }
+        }
+    }
+
+    fn process(&mut self, sample: &WorkingStateSample) {
+        let at = sample.created_date_time;
+        let is_present = |driver_id: &str| {
+            sample
+                .drivers
+                .iter()
+                .flatten()
+                .any(|d| d.driver_id == driver_id)
+        };
+        // update all slots before opening any intervals, so that drivers
+        // who merely swap slots keep their open interval
+        for (slot, driver) in sample.drivers.iter().enumerate() {
+            let slot_key = (sample.vin.clone(), slot);
+            let new_occupant = driver.as_ref().map(|d| &d.driver_id);
+            if self.slot_occupants.get(&slot_key) != new_occupant {
+                // the card in the slot has been removed or replaced
+                if let Some(previous_occupant) = self.slot_occupants.remove(&slot_key) {
+                    if !is_present(&previous_occupant) {
+                        self.close(&previous_occupant, at);
+                    }
+                }
+                if let Some(driver_id) = new_occupant {
+                    self.slot_occupants.insert(slot_key, driver_id.to_owned());
+                }
+            }
+        }
+
+        for driver in sample.drivers.iter() {
+            let Some((driver_id, working_state)) = driver
+                .as_ref()
+                .and_then(|d| d.working_state.map(|state| (&d.driver_id, state)))
+            else {
+                continue;
+            };
+            if self
+                .open_intervals
+                .get(driver_id)
+                .is_some_and(|open| open.working_state == working_state && open.vin == sample.vin)
+            {
+                continue;
+            }
+            self.close(driver_id, at);
+            self.open_intervals.insert(
+                driver_id.to_owned(),
+                OpenInterval {
+                    vin: sample.vin.clone(),
+                    working_state,
+                    start: at,
+                },
+            );
+        }
+    }
+
+    fn finish(mut self, stop: DateTime<Utc>) -> BTreeMap<String, Vec<WorkingStateIntervalObject>> {
+        let driver_ids: Vec<String> = self.open_intervals.keys().cloned().collect();
+        for driver_id in driver_ids {
+            self.close(&driver_id, stop);
+        }
+        self.intervals
+    }
+}
+
+fn start_of_day(date: NaiveDate) -> DateTime<Utc> {
+    date.and_hms_opt(0, 0, 0)
+        .expect("midnight is a valid time")
+        .and_utc()
+}
+
+fn start_of_week(date: NaiveDate) -> NaiveDate {
+    date - Days::new(u64::from(date.weekday().num_days_from_monday()))
+}
+
+fn daily_driving_times(intervals: &[WorkingStateIntervalObject]) -> BTreeMap<NaiveDate, i64> {
+    let mut totals = BTreeMap::new();
+    for interval in intervals
+        .iter()
+        .filter(|i| i.working_state == DriverWorkingStateProperty::Drive)
+    {
+        // split intervals that span midnight
+        let mut start = interval.start_date_time;
+        while start < interval.stop_date_time {
+            let day = start.date_naive();
+            let end_of_day = start_of_day(day + Days::new(1));
+            let stop = end_of_day.min(interval.stop_date_time);
+            *totals.entry(day).or_insert(0) += (stop - start).num_seconds();
+            start = stop;
+        }
+    }
+    totals
+}
+
+fn weekly_driving_times(daily: &BTreeMap<NaiveDate, i64>) -> BTreeMap<NaiveDate, i64> {
+    let mut totals = BTreeMap::new();
+    for (day, seconds) in daily {
+        *totals.entry(start_of_week(*day)).or_insert(0) += seconds;
+    }
+    totals
+}
+
+fn continuous_driving_violations(
+    intervals: &[WorkingStateIntervalObject],
+) -> Vec<DrivingTimeViolationObject> {
+    let mut violations = Vec::new();
+    let mut driving_seconds = 0;
+    let mut period: Option<(DateTime<Utc>, DateTime<Utc>)> = None;
+    let mut had_first_part_of_split_break = false;
+
+    let mut end_of_driving_period =
+        |driving_seconds: &mut i64, period: &mut Option<(DateTime<Utc>, DateTime<Utc>)>| {
+            if let Some((start, stop)) = period.take() {
+                if *driving_seconds > MAX_CONTINUOUS_DRIVING {
+                    violations.push(DrivingTimeViolationObject {
+                        rule: DrivingTimeRuleProperty::ContinuousDriving,
+                        start_date_time: start,
+                        stop_date_time: stop,
+                        driving_seconds: *driving_seconds,
+                        limit_seconds: MAX_CONTINUOUS_DRIVING,
+                    });
+                }
+            }
+            *driving_seconds = 0;
+        };
+
+    let mut previous_stop: Option<DateTime<Utc>> = None;
+    for interval in intervals {
+        // periods without any recorded working state (e.g. because the driver card
+        // has been removed) as well as periods of rest count as a break
+        let break_seconds = match interval.working_state {
+            DriverWorkingStateProperty::Rest => interval.duration_seconds,
+            _ => previous_stop
+                .map(|stop| (interval.start_date_time - stop).num_seconds())
+                .unwrap_or_default(),
+        };
+        if break_seconds >= MIN_BREAK
+            || (had_first_part_of_split_break && break_seconds >= MIN_SPLIT_BREAK_SECOND_PART)
+        {
+            end_of_driving_period(&mut driving_seconds, &mut period);
+            had_first_part_of_split_break = false;
+        } else if break_seconds >= MIN_SPLIT_BREAK_FIRST_PART {
+            had_first_part_of_split_break = true;
+        }
+
+        if interval.working_state == DriverWorkingStateProperty::Drive {
+            driving_seconds += interval.duration_seconds;
+            period = Some(match period {
+                Some((start, _)) => (start, interval.stop_date_time),
+                None => (interval.start_date_time, interval.stop_date_time),
+            });
+        }
+        previous_stop = Some(interval.stop_date_time);
+    }
+    end_of_driving_period(&mut driving_seconds, &mut period);
+    violations
+}
+
+fn driving_time_violations(
+    intervals: &[WorkingStateIntervalObject],
+    daily: &BTreeMap<NaiveDate, i64>,
+    weekly: &BTreeMap<NaiveDate, i64>,
+) -> Vec<DrivingTimeViolationObject> {
+    let mut violations = continuous_driving_violations(intervals);
+
+    let mut extended_days_per_week: HashMap<NaiveDate, usize> = HashMap::new();
+    for (day, seconds) in daily {
+        let start_date_time = start_of_day(*day);
+        let stop_date_time = start_of_day(*day + Days::new(1));
+        if *seconds > MAX_EXTENDED_DAILY_DRIVING {
+            violations.push(DrivingTimeViolationObject {
+                rule: DrivingTimeRuleProperty::DailyDriving,
+                start_date_time,
+                stop_date_time,
+                driving_seconds: *seconds,
+                limit_seconds: MAX_EXTENDED_DAILY_DRIVING,
+            });
+        }
+        if *seconds > MAX_DAILY_DRIVING {
+            let extended_days = extended_days_per_week
+                .entry(start_of_week(*day))
+                .or_insert(0);
+            *extended_days += 1;
+            if *extended_days > MAX_EXTENDED_DAYS_PER_WEEK {
+                violations.push(DrivingTimeViolationObject {
+                    rule: DrivingTimeRuleProperty::ExtendedDailyDriving,
+                    start_date_time,
+                    stop_date_time,
+                    driving_seconds: *seconds,
+                    limit_seconds: MAX_DAILY_DRIVING,
+                });
+            }
+        }
+    }
+
+    for (week_start, seconds) in weekly {
+        let next_week_start = *week_start + Days::new(7);
+        if *seconds > MAX_WEEKLY_DRIVING {
+            violations.push(DrivingTimeViolationObject {
+                rule: DrivingTimeRuleProperty::WeeklyDriving,
+                start_date_time: start_of_day(*week_start),
+                stop_date_time: start_of_day(next_week_start),
+                driving_seconds: *seconds,
+                limit_seconds: MAX_WEEKLY_DRIVING,
+            });
+        }
+        if let Some(next_week_seconds) = weekly.get(&next_week_start) {
+            let fortnight_seconds = seconds + next_week_seconds;
+            if fortnight_seconds > MAX_FORTNIGHTLY_DRIVING {
+                violations.push(DrivingTimeViolationObject {
+                    rule: DrivingTimeRuleProperty::FortnightlyDriving,
+                    start_date_time: start_of_day(*week_start),
+                    stop_date_time: start_of_day(next_week_start + Days::new(7)),
+                    driving_seconds: fortnight_seconds,
+                    limit_seconds: MAX_FORTNIGHTLY_DRIVING,
+                });
+            }
+        }
+    }
+
+    violations.sort_by_key(|v| v.start_date_time);
+    violations
+}
+
+/// Determines the working state intervals of all drivers contained in a set of samples.
+///
+/// An interval starts with the first sample that reports a driver to be in a particular
+/// working state and ends with the first sample reporting a different working state for
+/// the driver or no longer containing the driver's card in the tachograph slot.
+/// Intervals that are still open after the last sample are closed at `stop`.
+pub fn driver_working_states(
+    mut samples: Vec<WorkingStateSample>,
+    stop: DateTime<Utc>,
+) -> Vec<DriverWorkingStatesObject> {
+    samples.sort_by_key(|sample| sample.created_date_time);
+    let mut builder = TimelineBuilder::default();
+    samples.iter().for_each(|sample| builder.process(sample));
+
+    builder
+        .finish(stop)
+        .into_iter()
+        .map(|(driver_id, working_states)| {
+            let daily = daily_driving_times(&working_states);
+            let weekly = weekly_driving_times(&daily);
+            let violations = driving_time_violations(&working_states, &daily, &weekly);
+            DriverWorkingStatesObject {
+                driver_id,
+                working_states,
+                daily_driving_times: daily
+                    .into_iter()
+                    .map(|(date, driving_seconds)| DailyDrivingTimeObject {
+                        date,
+                        driving_seconds,
+                    })
+                    .collect(),
+                weekly_driving_times: weekly
+                    .into_iter()
+                    .map(|(week_start, driving_seconds)| WeeklyDrivingTimeObject {
+                        week_start,
+                        driving_seconds,
+                    })
+                    .collect(),
+                violations,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    const VIN: &str = "WDD169005-1J-236684";
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        // 2024-01-01 is a Monday
+        Utc.with_ymd_and_hms(2024, 1, day, hour, minute, 0).unwrap()
+    }
+
+    fn sample(
+        created_date_time: DateTime<Utc>,
+        driver1: Option<(&str, DriverWorkingStateProperty)>,
+    ) -> WorkingStateSample {
+        WorkingStateSample {
+            vin: VIN.to_string(),
+            created_date_time,
+            drivers: [
+                driver1.map(|(id, state)| DriverSample {
+                    driver_id: id.to_string(),
+                    working_state: Some(state),
+                }),
+                None,
+            ],
+        }
+    }
+
+    #[test]
+    fn consecutive_samples_with_same_state_are_merged() {
+        let samples = vec![
+            sample(at(1, 8, 0), Some(("D1", DriverWorkingStateProperty::Work))),
+            sample(
+                at(1, 8, 30),
+                Some(("D1", DriverWorkingStateProperty::Drive)),
+            ),
+            sample(at(1, 9, 0), Some(("D1", DriverWorkingStateProperty::Drive))),
+            sample(at(1, 10, 0), Some(("D1", DriverWorkingStateProperty::Rest))),
+            sample(at(1, 11, 0), None),
+        ];
+        let result = driver_working_states(samples, at(1, 12, 0));
+        assert_eq!(result.len(), 1);
+        let driver = &result[0];
+        assert_eq!(driver.driver_id, "D1");
+        let states: Vec<_> = driver
+            .working_states
+            .iter()
+            .map(|i| (i.working_state, i.duration_seconds))
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                (DriverWorkingStateProperty::Work, 30 * MINUTE),
+                (DriverWorkingStateProperty::Drive, 90 * MINUTE),
+                (DriverWorkingStateProperty::Rest, HOUR),
+            ]
+        );
+        assert_eq!(driver.daily_driving_times[0].driving_seconds, 90 * MINUTE);
+        assert!(driver.violations.is_empty());
+    }
+
+    #[test]
+    fn driving_across_midnight_is_split_into_days() {
+        let samples = vec![
+            sample(
+                at(1, 23, 0),
+                Some(("D1", DriverWorkingStateProperty::Drive)),
+            ),
+            sample(at(2, 1, 0), Some(("D1", DriverWorkingStateProperty::Rest))),
+        ];
+        let result = driver_working_states(samples, at(2, 2, 0));
+        let daily = &result[0].daily_driving_times;
+        assert_eq!(daily.len(), 2);
+        assert_eq!(daily[0].driving_seconds, HOUR);
+        assert_eq!(daily[1].driving_seconds, HOUR);
+        assert_eq!(result[0].weekly_driving_times[0].driving_seconds, 2 * HOUR);
+    }
+
+    #[test]
+    fn continuous_driving_without_break_is_flagged() {
+        let samples = vec![
+            sample(at(1, 6, 0), Some(("D1", DriverWorkingStateProperty::Drive))),
+            // a 20 minute break is not sufficient on its own
+            sample(at(1, 9, 0), Some(("D1", DriverWorkingStateProperty::Rest))),
+            sample(
+                at(1, 9, 20),
+                Some(("D1", DriverWorkingStateProperty::Drive)),
+            ),
+            sample(at(1, 11, 0), Some(("D1", DriverWorkingStateProperty::Rest))),
+        ];
+        let result = driver_working_states(samples, at(1, 12, 0));
+        let violations = &result[0].violations;
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].rule,
+            DrivingTimeRuleProperty::ContinuousDriving
+        );
+        assert_eq!(violations[0].driving_seconds, 4 * HOUR + 40 * MINUTE);
+    }
+
+    #[test]
+    fn split_break_resets_continuous_driving() {
+        let samples = vec![
+            sample(at(1, 6, 0), Some(("D1", DriverWorkingStateProperty::Drive))),
+            sample(at(1, 8, 0), Some(("D1", DriverWorkingStateProperty::Rest))),
+            sample(
+                at(1, 8, 15),
+                Some(("D1", DriverWorkingStateProperty::Drive)),
+            ),
+            sample(
+                at(1, 10, 15),
+                Some(("D1", DriverWorkingStateProperty::Rest)),
+            ),
+            sample(
+                at(1, 10, 45),
+                Some(("D1", DriverWorkingStateProperty::Drive)),
+            ),
+            sample(
+                at(1, 12, 45),
+                Some(("D1", DriverWorkingStateProperty::Rest)),
+            ),
+        ];
+        let result = driver_working_states(samples, at(1, 13, 30));
+        assert!(result[0].violations.is_empty());
+    }
+
+    #[test]
+    fn daily_and_weekly_limits_are_flagged() {
+        let mut samples = Vec::new();
+        // drive 11 hours (with sufficient breaks) on each day from Monday to Saturday
+        for day in 1..=6 {
+            for (start, stop) in [(4, 8), (9, 13), (14, 17)] {
+                samples.push(sample(
+                    at(day, start, 0),
+                    Some(("D1", DriverWorkingStateProperty::Drive)),
+                ));
+                samples.push(sample(
+                    at(day, stop, 0),
+                    Some(("D1", DriverWorkingStateProperty::Rest)),
+                ));
+            }
+        }
+        let result = driver_working_states(samples, at(7, 0, 0));
+        let violations = &result[0].violations;
+        let count = |rule| violations.iter().filter(|v| v.rule == rule).count();
+        assert_eq!(count(DrivingTimeRuleProperty::ContinuousDriving), 0);
+        assert_eq!(count(DrivingTimeRuleProperty::DailyDriving), 6);
+        assert_eq!(count(DrivingTimeRuleProperty::ExtendedDailyDriving), 4);
+        assert_eq!(count(DrivingTimeRuleProperty::WeeklyDriving), 1);
+        assert_eq!(result[0].weekly_driving_times[0].driving_seconds, 66 * HOUR);
+    }
+
+    #[test]
+    fn removing_driver_card_ends_interval() {
+        let samples = vec![
+            sample(at(1, 8, 0), Some(("D1", DriverWorkingStateProperty::Drive))),
+            sample(at(1, 9, 0), None),
+            sample(
+                at(1, 10, 0),
+                Some(("D2", DriverWorkingStateProperty::Drive)),
+            ),
+        ];
+        let result = driver_working_states(samples, at(1, 11, 0));
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].driver_id, "D1");
+        assert_eq!(result[0].working_states[0].stop_date_time, at(1, 9, 0));
+        assert_eq!(result[1].driver_id, "D2");
+        assert_eq!(result[1].working_states[0].duration_seconds, HOUR);
+    }
+
+    #[test]
+    fn swapping_slots_keeps_intervals() {
+        let driver = |id: &str, state| {
+            Some(DriverSample {
+                driver_id: id.to_string(),
+                working_state: Some(state),
+            })
+        };
+        let samples = vec![
+            WorkingStateSample {
+                vin: VIN.to_string(),
+                created_date_time: at(1, 8, 0),
+                drivers: [
+                    driver("D1", DriverWorkingStateProperty::Drive),
+                    driver("D2", DriverWorkingStateProperty::Work),
+                ],
+            },
+            // the co-drivers swap their cards
+            WorkingStateSample {
+                vin: VIN.to_string(),
+                created_date_time: at(1, 9, 0),
+                drivers: [
+                    driver("D2", DriverWorkingStateProperty::Work),
+                    driver("D1", DriverWorkingStateProperty::Drive),
+                ],
+            },
+        ];
+        let result = driver_working_states(samples, at(1, 10, 0));
+        assert_eq!(result.len(), 2);
+        for (driver, working_state) in result.iter().zip([
+            DriverWorkingStateProperty::Drive,
+            DriverWorkingStateProperty::Work,
+        ]) {
+            assert_eq!(driver.working_states.len(), 1);
+            assert_eq!(driver.working_states[0].working_state, working_state);
+            assert_eq!(driver.working_states[0].start_date_time, at(1, 8, 0));
+            assert_eq!(driver.working_states[0].duration_seconds, 2 * HOUR);
+        }
+    }
+}
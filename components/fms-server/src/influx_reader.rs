@@ -17,17 +17,20 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use const_format::formatcp;
 use influx_client::connection::{InfluxConnection, InfluxConnectionConfig};
 use influxrs::InfluxError;
 use log::error;
 
+use crate::driving_time::{DriverSample, WorkingStateSample};
 use crate::models::position::{GnssPositionObject, VehiclePositionObject};
 use crate::models::status::{DriverWorkingStateProperty, SnapshotDataObject, VehicleStatusObject};
 use crate::models::vehicle::VehicleObject;
 use crate::models::TriggerObject;
-use crate::query_parser::QueryParameters;
+use crate::query_parser::{QueryParameters, WorkingStateQueryParameters};
 
 const FILTER_FIELDS_POSITION: &str = formatcp!(
     r#"filter(fn: (r) => contains(set: ["{}","{}","{}","{}","{}","{}","{}","{}", "{}"], value: r._field))"#,
@@ -41,10 +44,37 @@ const FILTER_FIELDS_POSITION: &str = formatcp!(
     influx_client::FIELD_TACHOGRAPH_SPEED,
     influx_client::FIELD_WHEEL_BASED_SPEED,
 );
+const FILTER_FIELDS_WORKING_STATE: &str = formatcp!(
+    r#"filter(fn: (r) => contains(set: ["{}","{}","{}","{}","{}"], value: r._field))"#,
+    influx_client::FIELD_CREATED_DATE_TIME,
+    influx_client::FIELD_DRIVER1_ID,
+    influx_client::FIELD_DRIVER1_WORKING_STATE,
+    influx_client::FIELD_DRIVER2_ID,
+    influx_client::FIELD_DRIVER2_WORKING_STATE,
+);
 const FILTER_MEASUREMENT_SNAPSHOT: &str = formatcp!(
     r#"filter(fn: (r) => r._measurement == "{}")"#,
     influx_client::MEASUREMENT_SNAPSHOT,
 );
+const FILTER_MEASUREMENTS_VEHICLE_STATUS: &str = formatcp!(
+    r#"filter(fn: (r) => r._measurement == "{}" or r._measurement == "{}")"#,
+    influx_client::MEASUREMENT_HEADER,
+    influx_client::MEASUREMENT_SNAPSHOT,
+);
+const FILTER_CONNECTED: &str = formatcp!(
+    r#"filter(fn: (r) => r._measurement == "{}" and r._field == "{}")"#,
+    influx_client::MEASUREMENT_CONNECTIVITY,
+    influx_client::FIELD_CONNECTED,
+);
+const FILTER_LAST_SEEN: &str = formatcp!(
+    r#"filter(fn: (r) => (r._measurement == "{}" or r._measurement == "{}") and r._field == "{}")"#,
+    influx_client::MEASUREMENT_HEADER,
+    influx_client::MEASUREMENT_CONNECTIVITY,
+    influx_client::FIELD_CREATED_DATE_TIME,
+);
+// the period of time to consider when determining the vehicles' connectivity,
+// which is the same as the default period used by schema.tagValues()
+const RANGE_CONNECTIVITY: &str = "range(start: -30d)";
 const FILTER_TAG_ANY_VIN: &str = formatcp!(
     r#"filter(fn: (r) => r["{}"] =~ /.*/)"#,
     influx_client::TAG_VIN
@@ -91,6 +121,18 @@ fn unpack_driver_working_state(value: Option<&String>) -> Option<DriverWorkingSt
     None
 }
 
+fn unpack_driver_sample(
+    driver_id: Option<&String>,
+    working_state: Option<&String>,
+) -> Option<DriverSample> {
+    driver_id
+        .filter(|id| !id.is_empty())
+        .map(|driver_id| DriverSample {
+            driver_id: driver_id.to_string(),
+            working_state: unpack_driver_working_state(working_state),
+        })
+}
+
 pub struct InfluxReader {
     influx_con: InfluxConnection,
 }
@@ -110,15 +152,58 @@ impl InfluxReader {
             influx_client::TAG_VIN,
         ));
 
-        self.influx_con.client.query(read_query).await.map(|vins| {
-            vins.into_iter()
-                .filter_map(|entry| {
-                    entry
-                        .get("_value")
-                        .map(|vin| VehicleObject::new(vin.to_string()))
+        let vins = self.influx_con.client.query(read_query).await?;
+        let connected = self
+            .get_latest_value_per_vin(FILTER_CONNECTED, "last()")
+            .await?;
+        let last_seen = self
+            .get_latest_value_per_vin(FILTER_LAST_SEEN, "max()")
+            .await?;
+        Ok(vins
+            .into_iter()
+            .filter_map(|entry| {
+                entry.get("_value").map(|vin| {
+                    let mut vehicle = VehicleObject::new(vin.to_string());
+                    vehicle.connected = unpack_value_bool(connected.get(vin));
+                    vehicle.last_seen_date_time = unpack_time(last_seen.get(vin));
+                    vehicle
                 })
-                .collect()
-        })
+            })
+            .collect())
+    }
+
+    /// Gets the value of a field per VIN, as determined by a selector function like `last()`.
+    async fn get_latest_value_per_vin(
+        &self,
+        filter: &str,
+        selector: &str,
+    ) -> Result<HashMap<String, String>, InfluxError> {
+        let read_query =
+            influxrs::Query::new(format!(r#"from(bucket: "{}")"#, self.influx_con.bucket))
+                .then(RANGE_CONNECTIVITY)
+                .then(filter)
+                .then(format!(
+                    r#"group(columns: ["{}"], mode:"by")"#,
+                    influx_client::TAG_VIN
+                ))
+                .then(r#"sort(columns: ["_time"])"#)
+                .then(selector);
+
+        self.influx_con
+            .client
+            .query(read_query)
+            .await
+            .map(|entries| {
+                entries
+                    .into_iter()
+                    .filter_map(|mut entry| {
+                        match (entry.remove(influx_client::TAG_VIN), entry.remove("_value")) {
+                            (Some(vin), Some(value)) => Some((vin, value)),
+                            _ => None,
+                        }
+                    })
+                    .collect()
+            })
     }
 
     pub async fn get_vehicleposition(
@@ -252,6 +337,7 @@ impl InfluxReader {
         let mut read_query =
             influxrs::Query::new(format!(r#"from(bucket: "{}")"#, self.influx_con.bucket))
                 .then(time_filter)
+                .then(FILTER_MEASUREMENTS_VEHICLE_STATUS)
                 .then(vin_filter)
                 .then(trigger_filter)
                 .then(r#"aggregateWindow(every: 500ms, fn: last, createEmpty: false)"#);
@@ -392,4 +478,70 @@ impl InfluxReader {
                     .collect()
             })
     }
+
+    pub async fn get_working_state_samples(
+        &self,
+        parameters: &WorkingStateQueryParameters,
+    ) -> Result<Vec<WorkingStateSample>, InfluxError> {
+        let time_filter = format!(
+            "range(start: {}, stop: {})",
+            parameters.start_time, parameters.stop_time
+        );
+        let vin_filter = match &parameters.vin {
+            Some(v) => format!(
+                r#"filter(fn: (r) => r["{}"] == "{}")"#,
+                influx_client::TAG_VIN,
+                v
+            ),
+            None => FILTER_TAG_ANY_VIN.to_string(),
+        };
+
+        // the driver IDs and working states are spread over the header and snapshot
+        // measurements, so we need to join them on their (common) point in time
+        let read_query =
+            influxrs::Query::new(format!(r#"from(bucket: "{}")"#, self.influx_con.bucket))
+                .then(time_filter)
+                .then(FILTER_MEASUREMENTS_VEHICLE_STATUS)
+                .then(vin_filter)
+                .then(FILTER_FIELDS_WORKING_STATE)
+                .then(r#"aggregateWindow(every: 500ms, fn: last, createEmpty: false)"#)
+                .then(r#"group(columns: ["vin"], mode:"by")"#)
+                .then(r#"pivot(rowKey: ["_time"], columnKey: ["_field"], valueColumn: "_value")"#);
+
+        self.influx_con
+            .client
+            .query(read_query)
+            .await
+            .map_err(|e| {
+                error!("Error during the query for driver working states: {}", e);
+                e
+            })
+            .map(|measurements| {
+                measurements
+                    .into_iter()
+                    .filter_map(|entry| {
+                        match (
+                            entry.get(influx_client::TAG_VIN),
+                            unpack_time(entry.get(influx_client::FIELD_CREATED_DATE_TIME)),
+                        ) {
+                            (Some(vin), Some(created_date_time)) => Some(WorkingStateSample {
+                                vin: vin.to_string(),
+                                created_date_time,
+                                drivers: [
+                                    unpack_driver_sample(
+                                        entry.get(influx_client::FIELD_DRIVER1_ID),
+                                        entry.get(influx_client::FIELD_DRIVER1_WORKING_STATE),
+                                    ),
+                                    unpack_driver_sample(
+                                        entry.get(influx_client::FIELD_DRIVER2_ID),
+                                        entry.get(influx_client::FIELD_DRIVER2_WORKING_STATE),
+                                    ),
+                                ],
+                            }),
+                            _ => None,
+                        }
+                    })
+                    .collect()
+            })
+    }
 }
@@ -29,10 +29,11 @@ use std::collections::HashMap;
 use std::process;
 use std::sync::Arc;
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 
 use influx_reader::InfluxReader;
 
+mod driving_time;
 mod influx_reader;
 mod models;
 mod query_parser;
@@ -43,7 +44,10 @@ use models::position::{
 use models::status::{
     VehicleStatusResponseObject, VehicleStatusResponseObjectVehicleStatusResponse,
 };
-use query_parser::parse_query_parameters;
+use models::working_state::{
+    DriverWorkingStateResponseObject, DriverWorkingStateResponseObjectDriverWorkingStateResponse,
+};
+use query_parser::{parse_query_parameters, parse_working_state_query_parameters};
 
 pub fn app(influx_connection_params: &InfluxConnectionConfig) -> Router {
     let influx_reader = InfluxReader::new(influx_connection_params).map_or_else(
@@ -59,11 +63,12 @@ pub fn app(influx_connection_params: &InfluxConnectionConfig) -> Router {
         .route("/rfms/vehiclepositions", get(get_vehicleposition))
         .route("/rfms/vehicles", get(get_vehicles))
         .route("/rfms/vehiclestatuses", get(get_vehiclesstatuses))
+        .route("/fms/driverworkingstates", get(get_driver_working_states))
         .with_state(influx_reader)
 }
 
 async fn root() -> &'static str {
-    "Welcome to the rFMS server. The following endpoints are implemented: '/rfms/vehicleposition', '/rfms/vehicles', and '/rfms/vehiclestatuses'"
+    "Welcome to the rFMS server. The following endpoints are implemented: '/rfms/vehicleposition', '/rfms/vehicles', '/rfms/vehiclestatuses', and '/fms/driverworkingstates'"
 }
 
 async fn get_vehicleposition(
@@ -146,3 +151,35 @@ async fn get_vehiclesstatuses(
             StatusCode::INTERNAL_SERVER_ERROR
         })
 }
+
+async fn get_driver_working_states(
+    State(influx_server): State<Arc<InfluxReader>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let query_parameters = parse_working_state_query_parameters(&params)?;
+    let request_server_date_time = Utc::now();
+    let stop = DateTime::from_timestamp(query_parameters.stop_time, 0)
+        .map_or(request_server_date_time, |stop| {
+            stop.min(request_server_date_time)
+        });
+
+    influx_server
+        .get_working_state_samples(&query_parameters)
+        .await
+        .map(|samples| {
+            let mut drivers = driving_time::driver_working_states(samples, stop);
+            if let Some(driver_id) = &query_parameters.driver_id {
+                drivers.retain(|driver| &driver.driver_id == driver_id);
+            }
+            let result_object = json!(DriverWorkingStateResponseObject {
+                driver_working_state_response:
+                    DriverWorkingStateResponseObjectDriverWorkingStateResponse { drivers },
+                request_server_date_time,
+            });
+            Json(result_object)
+        })
+        .map_err(|e| {
+            error!("error retrieving driver working states: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
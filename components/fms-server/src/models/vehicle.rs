@@ -138,6 +138,16 @@ pub struct VehicleObject {
     #[serde(rename = "authorizedPaths")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub authorized_paths: Option<Vec<String>>,
+
+    /// Indicates whether the vehicle is currently connected to the back end. This is not part of the rFMS standard.
+    #[serde(rename = "connected")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connected: Option<bool>,
+
+    /// The most recent point in time at which the vehicle has reported its status or its connection to the back end has changed. This is not part of the rFMS standard.
+    #[serde(rename = "lastSeenDateTime")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_seen_date_time: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 /// Indicates when the vehicle was produced.
@@ -181,6 +191,8 @@ impl VehicleObject {
             door_configuration: None,
             has_ramp_or_lift: None,
             authorized_paths: None,
+            connected: None,
+            last_seen_date_time: None,
         }
     }
 }
@@ -0,0 +1,145 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Response objects of the (non rFMS) driver working state endpoint.
+
+use crate::models::status::DriverWorkingStateProperty;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DriverWorkingStateResponseObject {
+    #[serde(rename = "driverWorkingStateResponse")]
+    pub driver_working_state_response: DriverWorkingStateResponseObjectDriverWorkingStateResponse,
+
+    /// Time in UTC at which the request has been received by the server.
+    #[serde(rename = "requestServerDateTime")]
+    pub request_server_date_time: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DriverWorkingStateResponseObjectDriverWorkingStateResponse {
+    #[serde(rename = "drivers")]
+    pub drivers: Vec<DriverWorkingStatesObject>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DriverWorkingStatesObject {
+    /// The unique identification of the driver in a Member State as read from the tachograph card.
+    #[serde(rename = "driverId")]
+    pub driver_id: String,
+
+    /// The periods of time that the driver has spent in a particular working state, ordered by start time.
+    #[serde(rename = "workingStates")]
+    pub working_states: Vec<WorkingStateIntervalObject>,
+
+    /// The accumulated driving time per (UTC) calendar day.
+    #[serde(rename = "dailyDrivingTimes")]
+    pub daily_driving_times: Vec<DailyDrivingTimeObject>,
+
+    /// The accumulated driving time per (UTC) calendar week, starting on Monday 00:00.
+    #[serde(rename = "weeklyDrivingTimes")]
+    pub weekly_driving_times: Vec<WeeklyDrivingTimeObject>,
+
+    /// The driving time rules that have been violated within the requested time range.
+    #[serde(rename = "violations")]
+    pub violations: Vec<DrivingTimeViolationObject>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct WorkingStateIntervalObject {
+    /// The vehicle identification number of the vehicle in which the working state has been recorded.
+    #[serde(rename = "vin")]
+    pub vin: String,
+
+    #[serde(rename = "workingState")]
+    pub working_state: DriverWorkingStateProperty,
+
+    /// The instant at which the driver has entered the working state.
+    #[serde(rename = "startDateTime")]
+    pub start_date_time: chrono::DateTime<chrono::Utc>,
+
+    /// The instant at which the driver has left the working state.
+    #[serde(rename = "stopDateTime")]
+    pub stop_date_time: chrono::DateTime<chrono::Utc>,
+
+    /// The length of the interval in seconds.
+    #[serde(rename = "durationSeconds")]
+    pub duration_seconds: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DailyDrivingTimeObject {
+    #[serde(rename = "date")]
+    pub date: chrono::NaiveDate,
+
+    #[serde(rename = "drivingSeconds")]
+    pub driving_seconds: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct WeeklyDrivingTimeObject {
+    /// The Monday that the week starts on.
+    #[serde(rename = "weekStart")]
+    pub week_start: chrono::NaiveDate,
+
+    #[serde(rename = "drivingSeconds")]
+    pub driving_seconds: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DrivingTimeViolationObject {
+    #[serde(rename = "rule")]
+    pub rule: DrivingTimeRuleProperty,
+
+    /// The start of the period in which the rule has been violated.
+    #[serde(rename = "startDateTime")]
+    pub start_date_time: chrono::DateTime<chrono::Utc>,
+
+    /// The end of the period in which the rule has been violated.
+    #[serde(rename = "stopDateTime")]
+    pub stop_date_time: chrono::DateTime<chrono::Utc>,
+
+    /// The driving time accumulated within the period.
+    #[serde(rename = "drivingSeconds")]
+    pub driving_seconds: i64,
+
+    /// The maximum driving time allowed within the period.
+    #[serde(rename = "limitSeconds")]
+    pub limit_seconds: i64,
+}
+
+/// The driving time rules of Regulation (EC) No 561/2006 that are being checked.
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum DrivingTimeRuleProperty {
+    /// More than 4.5 hours of driving without a break of at least 45 minutes.
+    #[serde(rename = "CONTINUOUS_DRIVING")]
+    ContinuousDriving,
+    /// More than 10 hours of driving on a single day.
+    #[serde(rename = "DAILY_DRIVING")]
+    DailyDriving,
+    /// More than two days with more than 9 hours of driving within a week.
+    #[serde(rename = "EXTENDED_DAILY_DRIVING")]
+    ExtendedDailyDriving,
+    /// More than 56 hours of driving within a week.
+    #[serde(rename = "WEEKLY_DRIVING")]
+    WeeklyDriving,
+    /// More than 90 hours of driving within two consecutive weeks.
+    #[serde(rename = "FORTNIGHTLY_DRIVING")]
+    FortnightlyDriving,
+}
@@ -30,6 +30,7 @@ const START_TIME_QUERY: &str = "starttime";
 const STOP_TIME_QUERY: &str = "stoptime";
 const VIN_QUERY: &str = "vin";
 const TRIGGER_FILTER_QUERY: &str = "triggerFilter";
+const DRIVER_ID_QUERY: &str = "driverId";
 
 #[derive(Debug)]
 pub struct QueryParameters {
@@ -40,6 +41,14 @@ pub struct QueryParameters {
     pub latest_only: Option<bool>,
 }
 
+#[derive(Debug)]
+pub struct WorkingStateQueryParameters {
+    pub start_time: i64,
+    pub stop_time: i64,
+    pub vin: Option<String>,
+    pub driver_id: Option<String>,
+}
+
 pub fn parse_query_parameters(
     params: &HashMap<String, String>,
 ) -> Result<QueryParameters, StatusCode> {
@@ -72,6 +81,27 @@ pub fn parse_query_parameters(
     Ok(parameters)
 }
 
+pub fn parse_working_state_query_parameters(
+    params: &HashMap<String, String>,
+) -> Result<WorkingStateQueryParameters, StatusCode> {
+    // a time range is mandatory because working states can only be
+    // determined from a series of vehicle statuses
+    let Some(start_time) = parse_time(params, START_TIME_QUERY)? else {
+        return Err(StatusCode::BAD_REQUEST);
+    };
+    let stop_time = parse_time(params, STOP_TIME_QUERY)?.unwrap_or(Utc::now().timestamp());
+    if stop_time <= start_time {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    Ok(WorkingStateQueryParameters {
+        start_time,
+        stop_time,
+        vin: params.get(VIN_QUERY).cloned(),
+        driver_id: params.get(DRIVER_ID_QUERY).cloned(),
+    })
+}
+
 fn parse_latest_only(params: &HashMap<String, String>) -> Result<Option<bool>, StatusCode> {
     let latest_parameter = params.get(LATEST_ONLY_QUERY);
     if let Some(latest_string) = latest_parameter {
@@ -102,7 +132,7 @@ fn parse_time(params: &HashMap<String, String>, key: &str) -> Result<Option<i64>
 
 #[cfg(test)]
 mod tests {
-    use crate::query_parser::parse_query_parameters;
+    use crate::query_parser::{parse_query_parameters, parse_working_state_query_parameters};
     use axum::http::StatusCode;
     use std::collections::HashMap;
 
@@ -225,4 +255,53 @@ mod tests {
         assert_eq!(query_result.start_time, 1671117120);
         assert_eq!(query_result.stop_time, 1671117180);
     }
+
+    #[test]
+    fn working_states_without_starttime_returns_400() {
+        let parameters: HashMap<String, String> =
+            vec![(String::from("latestOnly"), String::from("true"))]
+                .into_iter()
+                .collect();
+        let result = parse_working_state_query_parameters(&parameters);
+        assert!(result.is_err(), "Expected Err but got Ok");
+        assert_eq!(result.unwrap_err(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn working_states_with_stoptime_before_starttime_returns_400() {
+        let parameters: HashMap<String, String> = vec![
+            (
+                String::from("starttime"),
+                String::from("2022-12-15T15:13:00Z"),
+            ),
+            (
+                String::from("stoptime"),
+                String::from("2022-12-15T15:12:00Z"),
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let result = parse_working_state_query_parameters(&parameters);
+        assert!(result.is_err(), "Expected Err but got Ok");
+        assert_eq!(result.unwrap_err(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn working_states_with_starttime_works() {
+        let parameters: HashMap<String, String> = vec![
+            (
+                String::from("starttime"),
+                String::from("2022-12-15T15:12:00Z"),
+            ),
+            (String::from("driverId"), String::from("DF000000000001")),
+        ]
+        .into_iter()
+        .collect();
+        let result = parse_working_state_query_parameters(&parameters);
+        assert!(result.is_ok(), "Expected Ok but got Err");
+        let query_result = result.unwrap();
+        assert_eq!(query_result.start_time, 1671117120);
+        assert_eq!(query_result.driver_id, Some("DF000000000001".to_string()));
+        assert!(query_result.vin.is_none());
+    }
 }
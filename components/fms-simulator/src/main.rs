@@ -0,0 +1,345 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use clap::{Parser, Subcommand};
+use fms_proto::fms::VehicleStatus;
+use fms_zenoh::ZenohTransportConfig;
+use hono_wire_format::vehicle_status_priority;
+use log::{debug, info, warn};
+use rand::{rngs::StdRng, SeedableRng};
+use up_rust::{
+    communication::{CallOptions, Publisher, SimplePublisher, UPayload},
+    LocalUriProvider, StaticUriProvider, UTransport, UUri,
+};
+use up_transport_hono_mqtt::{HonoMqttTransport, HonoMqttTransportConfig};
+use up_transport_zenoh::UPTransportZenoh;
+
+use crate::{
+    route::{Position, Route},
+    vehicle::SimulatedVehicle,
+};
+
+mod route;
+mod vehicle;
+
+const PARAM_AREA_RADIUS: &str = "area-radius";
+const PARAM_CENTER_LATITUDE: &str = "center-latitude";
+const PARAM_CENTER_LONGITUDE: &str = "center-longitude";
+const PARAM_DURATION: &str = "duration";
+const PARAM_ROUTE_RADIUS: &str = "route-radius";
+const PARAM_SEED: &str = "seed";
+const PARAM_TIME_FACTOR: &str = "time-factor";
+const PARAM_TIMER_INTERVAL: &str = "timer-interval";
+const PARAM_VEHICLES: &str = "vehicles";
+const PARAM_VIN_AS_AUTHORITY: &str = "vin-as-authority";
+const PARAM_VIN_PREFIX: &str = "vin-prefix";
+
+const VIN_LENGTH: usize = 17;
+const STATS_INTERVAL: Duration = Duration::from_secs(10);
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Simulates a fleet of vehicles and publishes their status to a back end system using uProtocol.
+///
+/// The simulator can be used instead of the FMS Forwarder for demonstrating the blueprint
+/// or for load testing the back end services without having to run a Kuksa Databroker and
+/// CSV provider per vehicle.
+#[derive(Parser)]
+#[command(version, about, long_about = None, arg_required_else_help = true)]
+struct FmsSimulatorCommand {
+    /// The topic to publish vehicle status events to.
+    #[arg(long = "topic", value_name = "URI", env = "TOPIC", default_value = "up://fms-simulator/D100/1/D100", value_parser = up_rust::UUri::from_str )]
+    vehicle_status_topic: UUri,
+
+    /// The number of vehicles to simulate.
+    #[arg(long = PARAM_VEHICLES, value_name = "NUMBER", env = "VEHICLES", default_value = "10", value_parser = clap::value_parser!(u32).range(1..) )]
+    vehicles: u32,
+
+    /// The prefix to use for the vehicles' identification numbers.
+    /// The VINs are created by appending the (zero padded) index of the vehicle to the prefix.
+    #[arg(long = PARAM_VIN_PREFIX, value_name = "PREFIX", env = "VIN_PREFIX", default_value = "SIMFMS", value_parser = parse_vin_prefix )]
+    vin_prefix: String,
+
+    /// Indicates whether the vehicles should use their VIN as the authority of the topic
+    /// that they publish their status to, e.g. for using the Hono transport in gateway mode.
+    #[arg(long = PARAM_VIN_AS_AUTHORITY, env = "VIN_AS_AUTHORITY")]
+    vin_as_authority: bool,
+
+    /// The interval at which the vehicles' status should be published in addition to
+    /// the status published for events like a driver logging in or the engine being started.
+    #[arg(long = PARAM_TIMER_INTERVAL, value_name = "DURATION_SPEC", env = "TIMER_INTERVAL", default_value = "5s", value_parser = |s: &str| duration_str::parse(s) )]
+    timer_interval: Duration,
+
+    /// The factor by which the simulated time passes faster than real time.
+    /// This can be used to quickly run through a driver's shift, e.g. a factor of 60
+    /// simulates an hour of driving per minute.
+    #[arg(long = PARAM_TIME_FACTOR, value_name = "FACTOR", env = "TIME_FACTOR", default_value = "1.0", value_parser = parse_positive_f64 )]
+    time_factor: f64,
+
+    /// The latitude of the center of the area in which the vehicles' depots are located.
+    #[arg(long = PARAM_CENTER_LATITUDE, value_name = "DEGREES", env = "CENTER_LATITUDE", default_value = "48.7758", allow_negative_numbers = true, value_parser = parse_latitude )]
+    center_latitude: f64,
+
+    /// The longitude of the center of the area in which the vehicles' depots are located.
+    #[arg(long = PARAM_CENTER_LONGITUDE, value_name = "DEGREES", env = "CENTER_LONGITUDE", default_value = "9.1829", allow_negative_numbers = true, value_parser = parse_longitude )]
+    center_longitude: f64,
+
+    /// The radius (in meters) of the area around the center in which the vehicles' depots are located.
+    #[arg(long = PARAM_AREA_RADIUS, value_name = "METERS", env = "AREA_RADIUS", default_value = "20000", value_parser = parse_positive_f64 )]
+    area_radius: f64,
+
+    /// The maximum distance (in meters) of a route's waypoints from the vehicle's depot.
+    #[arg(long = PARAM_ROUTE_RADIUS, value_name = "METERS", env = "ROUTE_RADIUS", default_value = "50000", value_parser = parse_positive_f64 )]
+    route_radius: f64,
+
+    /// The seed to use for the random number generator.
+    /// Using the same seed results in the same vehicles, routes and events being simulated.
+    #[arg(long = PARAM_SEED, value_name = "NUMBER", env = "SEED", default_value = "0")]
+    seed: u64,
+
+    /// The (real) time after which the simulator should stop.
+    /// The simulator runs until it gets terminated if not set.
+    #[arg(long = PARAM_DURATION, value_name = "DURATION_SPEC", env = "DURATION", value_parser = |s: &str| duration_str::parse(s) )]
+    duration: Option<Duration>,
+
+    #[command(subcommand)]
+    transport: TransportType,
+}
+
+#[derive(Subcommand)]
+#[command(subcommand_required = true)]
+enum TransportType {
+    /// Publishes vehicle status via Eclipse uProtocol using Eclipse Hono based transport.
+    #[command(name = "hono")]
+    Hono(Box<HonoMqttTransportConfig>),
+
+    /// Publishes vehicle status via Eclipse uProtocol using Eclipse Zenoh based transport.
+    #[command(name = "zenoh")]
+    Zenoh(Box<ZenohTransportConfig>),
+}
+
+fn parse_vin_prefix(s: &str) -> Result<String, String> {
+    if s.len() >= VIN_LENGTH {
+        return Err(format!(
+            "prefix must be shorter than {} characters",
+            VIN_LENGTH
+        ));
+    }
+    if !s.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err("prefix must consist of alphanumeric characters only".to_string());
+    }
+    Ok(s.to_uppercase())
+}
+
+fn parse_positive_f64(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(v) if v.is_finite() && v > 0.0 => Ok(v),
+        _ => Err("value must be a positive number".to_string()),
+    }
+}
+
+fn parse_latitude(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(v) if (-90.0..=90.0).contains(&v) => Ok(v),
+        _ => Err("latitude must be a number in the range [-90, 90]".to_string()),
+    }
+}
+
+fn parse_longitude(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(v) if (-180.0..=180.0).contains(&v) => Ok(v),
+        _ => Err("longitude must be a number in the range [-180, 180]".to_string()),
+    }
+}
+
+fn vin(prefix: &str, index: u32) -> String {
+    format!(
+        "{}{:0>width$}",
+        prefix,
+        index,
+        width = VIN_LENGTH - prefix.len()
+    )
+}
+
+#[derive(Default)]
+struct Statistics {
+    published: AtomicU64,
+    failed: AtomicU64,
+}
+
+async fn publish_vehicle_status(
+    publisher: &SimplePublisher,
+    resource_id: u16,
+    vehicle_status: VehicleStatus,
+    statistics: &Statistics,
+) {
+    let priority = vehicle_status_priority(&vehicle_status);
+    let result = match UPayload::try_from_protobuf(vehicle_status) {
+        Ok(payload) => publisher
+            .publish(
+                resource_id,
+                CallOptions::for_publish(None, None, Some(priority)),
+                Some(payload),
+            )
+            .await
+            .map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    match result {
+        Ok(()) => {
+            statistics.published.fetch_add(1, Ordering::Relaxed);
+        }
+        Err(e) => {
+            statistics.failed.fetch_add(1, Ordering::Relaxed);
+            warn!("failed to publish vehicle status event: {}", e);
+        }
+    }
+}
+
+async fn run_vehicle(
+    mut vehicle: SimulatedVehicle,
+    start_delay: Duration,
+    timer_interval: Duration,
+    time_factor: f64,
+    publisher: Arc<SimplePublisher>,
+    resource_id: u16,
+    statistics: Arc<Statistics>,
+) {
+    tokio::time::sleep(start_delay).await;
+    debug!("starting simulation of vehicle [VIN: {}]", vehicle.vin());
+    let mut tick = tokio::time::interval(TICK_INTERVAL);
+    let mut timer = tokio::time::interval(timer_interval);
+    loop {
+        tokio::select! {
+            _ = tick.tick() => {
+                let simulated_seconds = TICK_INTERVAL.as_secs_f64() * time_factor;
+                for trigger in vehicle.advance(simulated_seconds) {
+                    let vehicle_status = vehicle.vehicle_status(trigger);
+                    publish_vehicle_status(&publisher, resource_id, vehicle_status, &statistics).await;
+                }
+            }
+            _ = timer.tick() => {
+                let vehicle_status = vehicle.vehicle_status(SimulatedVehicle::timer_trigger());
+                publish_vehicle_status(&publisher, resource_id, vehicle_status, &statistics).await;
+            }
+        }
+    }
+}
+
+async fn log_statistics(statistics: Arc<Statistics>) {
+    let mut interval = tokio::time::interval(STATS_INTERVAL);
+    interval.tick().await;
+    let (mut last_published, mut last_failed) = (0, 0);
+    loop {
+        interval.tick().await;
+        let published = statistics.published.load(Ordering::Relaxed);
+        let failed = statistics.failed.load(Ordering::Relaxed);
+        info!(
+            "published {} vehicle status events ({:.1}/s), {} failed",
+            published,
+            (published - last_published) as f64 / STATS_INTERVAL.as_secs_f64(),
+            failed - last_failed,
+        );
+        last_published = published;
+        last_failed = failed;
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::init();
+    let command = FmsSimulatorCommand::parse();
+    let uri_provider = StaticUriProvider::try_from(&command.vehicle_status_topic).map(Arc::new)?;
+
+    let transport: Arc<dyn UTransport> = match command.transport {
+        TransportType::Hono(config) => HonoMqttTransport::new(&config).await.map(Arc::new)?,
+        TransportType::Zenoh(config) => {
+            let zenoh_config = (*config).try_into()?;
+            UPTransportZenoh::new(zenoh_config, uri_provider.get_source_uri())
+                .await
+                .map(Arc::new)?
+        }
+    };
+
+    let origin_resource_id = u16::try_from(command.vehicle_status_topic.resource_id)?;
+    let publisher = Arc::new(SimplePublisher::new(transport.clone(), uri_provider));
+    let statistics = Arc::new(Statistics::default());
+    info!("starting FMS simulator with {} vehicles", command.vehicles);
+
+    let center = Position {
+        latitude: command.center_latitude,
+        longitude: command.center_longitude,
+    };
+    let mut rng = StdRng::seed_from_u64(command.seed);
+    for index in 0..command.vehicles {
+        let route = Route::random(&mut rng, center, command.area_radius, command.route_radius);
+        let vin = vin(&command.vin_prefix, index);
+        let vehicle_publisher = if command.vin_as_authority {
+            let vehicle_uri_provider = StaticUriProvider::new(
+                vin.as_str(),
+                command.vehicle_status_topic.ue_id,
+                command.vehicle_status_topic.uentity_major_version(),
+            );
+            Arc::new(SimplePublisher::new(
+                transport.clone(),
+                Arc::new(vehicle_uri_provider),
+            ))
+        } else {
+            publisher.clone()
+        };
+        let vehicle = SimulatedVehicle::new(
+            vin,
+            format!("DSIM{:0>10}", index),
+            route,
+            command.seed.wrapping_add(u64::from(index)),
+        );
+        // spread the vehicles' publishing over the timer interval
+        let start_delay = command.timer_interval.mul_f64(f64::from(index)) / command.vehicles;
+        tokio::spawn(run_vehicle(
+            vehicle,
+            start_delay,
+            command.timer_interval,
+            command.time_factor,
+            vehicle_publisher,
+            origin_resource_id,
+            statistics.clone(),
+        ));
+    }
+    tokio::spawn(log_statistics(statistics));
+
+    match command.duration {
+        Some(duration) => {
+            tokio::time::sleep(duration).await;
+            info!("simulation duration has elapsed, stopping FMS simulator");
+        }
+        None => {
+            tokio::signal::ctrl_c().await?;
+            info!("stopping FMS simulator");
+        }
+    }
+    Ok(())
+}
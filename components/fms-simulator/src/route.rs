@@ -0,0 +1,172 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Closed routes that a simulated vehicle drives along.
+
+use rand::Rng;
+
+const EARTH_RADIUS_M: f64 = 6_371_000.0;
+
+/// A position on the earth's surface (WGS84 based).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Position {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Position {
+    /// Gets the great-circle distance to another position in meters.
+    pub fn distance_to(&self, other: &Position) -> f64 {
+        let lat1 = self.latitude.to_radians();
+        let lat2 = other.latitude.to_radians();
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.longitude - self.longitude).to_radians();
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_M * a.sqrt().asin()
+    }
+
+    /// Gets the initial bearing (0-359 degrees) for travelling to another position.
+    pub fn bearing_to(&self, other: &Position) -> f64 {
+        let lat1 = self.latitude.to_radians();
+        let lat2 = other.latitude.to_radians();
+        let d_lon = (other.longitude - self.longitude).to_radians();
+        let y = d_lon.sin() * lat2.cos();
+        let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * d_lon.cos();
+        y.atan2(x).to_degrees().rem_euclid(360.0)
+    }
+
+    /// Gets the position reached by travelling a distance (in meters) along a bearing.
+    pub fn destination(&self, bearing: f64, distance: f64) -> Position {
+        let lat1 = self.latitude.to_radians();
+        let lon1 = self.longitude.to_radians();
+        let bearing = bearing.to_radians();
+        let angular_distance = distance / EARTH_RADIUS_M;
+        let lat2 = (lat1.sin() * angular_distance.cos()
+            + lat1.cos() * angular_distance.sin() * bearing.cos())
+        .asin();
+        let lon2 = lon1
+            + (bearing.sin() * angular_distance.sin() * lat1.cos())
+                .atan2(angular_distance.cos() - lat1.sin() * lat2.sin());
+        Position {
+            latitude: lat2.to_degrees(),
+            longitude: lon2.to_degrees(),
+        }
+    }
+
+    /// Gets the position at a fraction (0.0 - 1.0) of the way to another position.
+    ///
+    /// Uses linear interpolation, which is good enough for the short legs of a route.
+    pub fn interpolate(&self, other: &Position, fraction: f64) -> Position {
+        Position {
+            latitude: self.latitude + (other.latitude - self.latitude) * fraction,
+            longitude: self.longitude + (other.longitude - self.longitude) * fraction,
+        }
+    }
+}
+
+/// A closed route that starts and ends at a depot.
+#[derive(Clone, Debug)]
+pub struct Route {
+    waypoints: Vec<Position>,
+}
+
+impl Route {
+    /// Creates a random route with waypoints scattered around a depot.
+    ///
+    /// The depot is placed randomly within `area_radius` meters of `center` while the
+    /// waypoints are placed within `route_radius` meters of the depot.
+    pub fn random<R: Rng>(
+        rng: &mut R,
+        center: Position,
+        area_radius: f64,
+        route_radius: f64,
+    ) -> Self {
+        let depot = center.destination(
+            rng.gen_range(0.0..360.0),
+            area_radius * rng.gen::<f64>().sqrt(),
+        );
+        let number_of_stops = rng.gen_range(4..=10);
+        // visit the waypoints in the order of their bearing from the depot
+        // in order to prevent the route from crossing itself all the time
+        let mut bearings: Vec<f64> = (0..number_of_stops)
+            .map(|_| rng.gen_range(0.0..360.0))
+            .collect();
+        bearings.sort_by(f64::total_cmp);
+        let mut waypoints = vec![depot];
+        waypoints.extend(
+            bearings
+                .into_iter()
+                .map(|bearing| depot.destination(bearing, rng.gen_range(0.2..1.0) * route_radius)),
+        );
+        Route { waypoints }
+    }
+
+    /// Gets the number of legs of the route.
+    pub fn legs(&self) -> usize {
+        self.waypoints.len()
+    }
+
+    /// Gets the start and end position of a leg.
+    pub fn leg(&self, index: usize) -> (Position, Position) {
+        let start = self.waypoints[index % self.waypoints.len()];
+        let end = self.waypoints[(index + 1) % self.waypoints.len()];
+        (start, end)
+    }
+
+    /// Gets the length of a leg in meters.
+    pub fn leg_length(&self, index: usize) -> f64 {
+        let (start, end) = self.leg(index);
+        start.distance_to(&end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn test_destination_is_at_expected_distance_and_bearing() {
+        let start = Position {
+            latitude: 48.7758,
+            longitude: 9.1829,
+        };
+        let destination = start.destination(90.0, 10_000.0);
+        assert!((start.distance_to(&destination) - 10_000.0).abs() < 1.0);
+        assert!((start.bearing_to(&destination) - 90.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_random_route_stays_within_radius() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let center = Position {
+            latitude: 48.7758,
+            longitude: 9.1829,
+        };
+        let route = Route::random(&mut rng, center, 50_000.0, 20_000.0);
+        assert!(route.legs() >= 5);
+        let (depot, _) = route.leg(0);
+        assert!(center.distance_to(&depot) <= 50_001.0);
+        for leg in 0..route.legs() {
+            let (start, _) = route.leg(leg);
+            assert!(depot.distance_to(&start) <= 20_001.0);
+        }
+    }
+}
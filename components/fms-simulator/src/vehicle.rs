@@ -0,0 +1,566 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! A simulated truck that drives along a route during a driver's shift.
+//!
+//! The simulation follows a simple daily routine: the driver logs in, does some pre-trip
+//! work, drives the route (stopping at some of the waypoints for deliveries), takes breaks
+//! as required by the EU driving time rules and finally logs out again at the end of the shift.
+
+use fms_proto::fms::{TellTaleInfo, Trigger, VehicleStatus};
+use fms_proto::TRIGGER_TIMER;
+use protobuf::{well_known_types::timestamp::Timestamp, MessageField};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::route::{Position, Route};
+
+const TRIGGER_DRIVER1_WORKING_STATE_CHANGED: &str = "DRIVER_1_WORKING_STATE_CHANGED";
+const TRIGGER_PARKING_BRAKE_SWITCH_CHANGE: &str = "PARKING_BRAKE_SWITCH_CHANGE";
+const TRIGGER_DRIVER_LOGIN: &str = "DRIVER_LOGIN";
+const TRIGGER_DRIVER_LOGOUT: &str = "DRIVER_LOGOUT";
+const TRIGGER_ENGINE_ON: &str = "ENGINE_ON";
+const TRIGGER_ENGINE_OFF: &str = "ENGINE_OFF";
+const TRIGGER_TELL_TALE: &str = "TELL_TALE";
+
+const TELL_TALE_NAME_ECT: &str = "ENGINE_COOLANT_TEMPERATURE";
+const TELL_TALE_NAME_FUEL_LEVEL: &str = "FUEL_LEVEL";
+const TELL_TALE_STATUS_OFF: &str = "OFF";
+const TELL_TALE_STATUS_RED: &str = "RED";
+const TELL_TALE_STATUS_YELLOW: &str = "YELLOW";
+
+const WORKING_STATE_DRIVE: &str = "DRIVE";
+const WORKING_STATE_REST: &str = "REST";
+const WORKING_STATE_WORK: &str = "WORK";
+
+/// Diesel, according to J1939's SPN 5837.
+const FUEL_TYPE_DIESEL: &str = "04";
+const TANK_CAPACITY_ML: f64 = 400_000.0;
+const FUEL_CONSUMPTION_ML_PER_M: f64 = 0.3;
+const FUEL_CONSUMPTION_IDLE_ML_PER_S: f64 = 0.7;
+const FUEL_LEVEL_WARNING_THRESHOLD: f64 = 15.0;
+const FUEL_LEVEL_REFUEL_THRESHOLD: f64 = 30.0;
+const ADBLUE_PER_FUEL_RATIO: f64 = 0.05;
+
+const ACCELERATION_KMH_PER_S: f64 = 3.0;
+const DECELERATION_KMH_PER_S: f64 = 5.0;
+const IDLE_ENGINE_SPEED: f64 = 600.0;
+const ECT_WARNING_PROBABILITY_PER_S: f64 = 0.02 / 3600.0;
+const MAX_STEP_S: f64 = 5.0;
+
+const HOUR: f64 = 3600.0;
+const MINUTE: f64 = 60.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Activity {
+    OffDuty,
+    PreTrip,
+    Driving,
+    Delivery,
+    Break,
+    PostTrip,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum StopReason {
+    Delivery,
+    Break,
+    EndOfShift,
+}
+
+/// A vehicle whose status is being simulated.
+pub struct SimulatedVehicle {
+    rng: StdRng,
+    vin: String,
+    driver_id: String,
+    route: Route,
+    leg: usize,
+    leg_progress: f64,
+    deliver_at_end_of_leg: bool,
+    cruise_speed: f64,
+    speed: f64,
+    heading: f64,
+    position: Position,
+    altitude: i32,
+    ambient_air_temperature: f64,
+    gross_combination_vehicle_weight: u32,
+    activity: Activity,
+    activity_remaining: f64,
+    pending_stop: Option<StopReason>,
+    driving_since_break: f64,
+    driving_in_shift: f64,
+    max_driving_since_break: f64,
+    max_driving_in_shift: f64,
+    card_present: bool,
+    working_state: &'static str,
+    engine_running: bool,
+    parking_brake_engaged: bool,
+    odometer: f64,
+    engine_hours: f64,
+    fuel_used: f64,
+    fuel_in_tank: f64,
+    fuel_level_tell_tale: &'static str,
+    ect_tell_tale: &'static str,
+    ect_warning_remaining: f64,
+}
+
+impl SimulatedVehicle {
+    /// Creates a new vehicle.
+    ///
+    /// The vehicle's properties are derived from the given seed so that
+    /// subsequent simulation runs produce the same vehicles.
+    pub fn new(vin: String, driver_id: String, route: Route, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let (depot, next_waypoint) = route.leg(0);
+        let fuel_in_tank = rng.gen_range(0.4..1.0) * TANK_CAPACITY_ML;
+        // start the vehicles at random points of their daily routine in order
+        // to prevent all of them from emitting the same events at the same time
+        let activity_remaining = rng.gen_range(0.0..HOUR);
+        SimulatedVehicle {
+            vin,
+            driver_id,
+            leg: 0,
+            leg_progress: 0.0,
+            deliver_at_end_of_leg: false,
+            cruise_speed: 0.0,
+            speed: 0.0,
+            heading: depot.bearing_to(&next_waypoint),
+            position: depot,
+            altitude: rng.gen_range(100..600),
+            ambient_air_temperature: rng.gen_range(-5.0..30.0),
+            gross_combination_vehicle_weight: rng.gen_range(12_000..40_000),
+            activity: Activity::OffDuty,
+            activity_remaining,
+            pending_stop: None,
+            driving_since_break: 0.0,
+            driving_in_shift: 0.0,
+            max_driving_since_break: rng.gen_range(3.0 * HOUR..4.5 * HOUR),
+            max_driving_in_shift: rng.gen_range(6.0 * HOUR..9.0 * HOUR),
+            card_present: false,
+            working_state: WORKING_STATE_REST,
+            engine_running: false,
+            parking_brake_engaged: true,
+            odometer: rng.gen_range(10_000_000.0..900_000_000.0),
+            engine_hours: rng.gen_range(500.0..20_000.0),
+            fuel_used: rng.gen_range(5_000_000_000.0..300_000_000_000.0),
+            fuel_in_tank,
+            fuel_level_tell_tale: TELL_TALE_STATUS_OFF,
+            ect_tell_tale: TELL_TALE_STATUS_OFF,
+            ect_warning_remaining: 0.0,
+            route,
+            rng,
+        }
+    }
+
+    /// Gets the vehicle's identification number.
+    pub fn vin(&self) -> &str {
+        &self.vin
+    }
+
+    fn new_trigger(type_: &str) -> Trigger {
+        let mut trigger = Trigger::new();
+        trigger.context = "RFMS".to_string();
+        trigger.type_ = type_.to_string();
+        trigger
+    }
+
+    fn new_driver_trigger(&self, type_: &str) -> Trigger {
+        let mut trigger = Self::new_trigger(type_);
+        trigger
+            .driver
+            .mut_or_insert_default()
+            .tacho_driver_identification
+            .mut_or_insert_default()
+            .driver_identification = self.driver_id.clone();
+        trigger
+    }
+
+    fn new_tell_tale_trigger(name: &str, status: &str) -> Trigger {
+        let mut trigger = Self::new_trigger(TRIGGER_TELL_TALE);
+        let mut tell_tale_info = TellTaleInfo::new();
+        tell_tale_info.tell_tale = name.to_string();
+        tell_tale_info.status = status.to_string();
+        trigger.tell_tale_info = MessageField::some(tell_tale_info);
+        trigger
+    }
+
+    /// Creates the trigger for the periodic reporting of the vehicle's status.
+    pub fn timer_trigger() -> Trigger {
+        Self::new_trigger(TRIGGER_TIMER)
+    }
+
+    fn set_working_state(&mut self, working_state: &'static str, triggers: &mut Vec<Trigger>) {
+        if self.working_state != working_state {
+            self.working_state = working_state;
+            if self.card_present {
+                triggers.push(self.new_driver_trigger(TRIGGER_DRIVER1_WORKING_STATE_CHANGED));
+            }
+        }
+    }
+
+    fn set_engine_running(&mut self, running: bool, triggers: &mut Vec<Trigger>) {
+        if self.engine_running != running {
+            self.engine_running = running;
+            triggers.push(Self::new_trigger(if running {
+                TRIGGER_ENGINE_ON
+            } else {
+                TRIGGER_ENGINE_OFF
+            }));
+        }
+    }
+
+    fn set_parking_brake_engaged(&mut self, engaged: bool, triggers: &mut Vec<Trigger>) {
+        if self.parking_brake_engaged != engaged {
+            self.parking_brake_engaged = engaged;
+            triggers.push(Self::new_trigger(TRIGGER_PARKING_BRAKE_SWITCH_CHANGE));
+        }
+    }
+
+    fn set_card_present(&mut self, present: bool, triggers: &mut Vec<Trigger>) {
+        if self.card_present != present {
+            if present {
+                self.card_present = true;
+                triggers.push(self.new_driver_trigger(TRIGGER_DRIVER_LOGIN));
+            } else {
+                triggers.push(self.new_driver_trigger(TRIGGER_DRIVER_LOGOUT));
+                self.card_present = false;
+            }
+        }
+    }
+
+    fn fuel_level(&self) -> f64 {
+        100.0 * self.fuel_in_tank / TANK_CAPACITY_ML
+    }
+
+    fn start_driving(&mut self, triggers: &mut Vec<Trigger>) {
+        self.set_engine_running(true, triggers);
+        self.set_parking_brake_engaged(false, triggers);
+        self.set_working_state(WORKING_STATE_DRIVE, triggers);
+        self.cruise_speed = self.rng.gen_range(50.0..85.0);
+        self.activity = Activity::Driving;
+    }
+
+    fn park(
+        &mut self,
+        activity: Activity,
+        working_state: &'static str,
+        duration: f64,
+        triggers: &mut Vec<Trigger>,
+    ) {
+        self.set_parking_brake_engaged(true, triggers);
+        self.set_engine_running(false, triggers);
+        self.set_working_state(working_state, triggers);
+        self.activity = activity;
+        self.activity_remaining = duration;
+    }
+
+    fn end_activity(&mut self, triggers: &mut Vec<Trigger>) {
+        match self.activity {
+            Activity::OffDuty => {
+                self.set_card_present(true, triggers);
+                self.set_working_state(WORKING_STATE_WORK, triggers);
+                self.activity = Activity::PreTrip;
+                self.activity_remaining = self.rng.gen_range(5.0 * MINUTE..15.0 * MINUTE);
+            }
+            Activity::PreTrip | Activity::Delivery => {
+                self.start_driving(triggers);
+            }
+            Activity::Break => {
+                self.driving_since_break = 0.0;
+                self.start_driving(triggers);
+            }
+            Activity::PostTrip => {
+                if self.fuel_level() < FUEL_LEVEL_REFUEL_THRESHOLD {
+                    self.fuel_in_tank = TANK_CAPACITY_ML;
+                }
+                self.set_working_state(WORKING_STATE_REST, triggers);
+                self.set_card_present(false, triggers);
+                self.driving_since_break = 0.0;
+                self.driving_in_shift = 0.0;
+                self.activity = Activity::OffDuty;
+                self.activity_remaining = self.rng.gen_range(9.0 * HOUR..12.0 * HOUR);
+            }
+            Activity::Driving => {}
+        }
+    }
+
+    fn stop(&mut self, reason: StopReason, triggers: &mut Vec<Trigger>) {
+        match reason {
+            StopReason::Delivery => {
+                let duration = self.rng.gen_range(5.0 * MINUTE..20.0 * MINUTE);
+                self.park(Activity::Delivery, WORKING_STATE_WORK, duration, triggers);
+            }
+            StopReason::Break => {
+                let duration = self.rng.gen_range(45.0 * MINUTE..60.0 * MINUTE);
+                self.park(Activity::Break, WORKING_STATE_REST, duration, triggers);
+            }
+            StopReason::EndOfShift => {
+                let duration = self.rng.gen_range(5.0 * MINUTE..15.0 * MINUTE);
+                self.park(Activity::PostTrip, WORKING_STATE_WORK, duration, triggers);
+            }
+        }
+    }
+
+    fn drive(&mut self, seconds: f64, triggers: &mut Vec<Trigger>) {
+        let target_speed = if self.pending_stop.is_some() {
+            0.0
+        } else {
+            self.cruise_speed
+        };
+        let previous_speed = self.speed;
+        self.speed = if target_speed > self.speed {
+            (self.speed + ACCELERATION_KMH_PER_S * seconds).min(target_speed)
+        } else {
+            (self.speed - DECELERATION_KMH_PER_S * seconds).max(target_speed)
+        };
+        let distance = (previous_speed + self.speed) / 2.0 / 3.6 * seconds;
+        self.odometer += distance;
+        self.consume_fuel(distance * FUEL_CONSUMPTION_ML_PER_M);
+        self.driving_since_break += seconds;
+        self.driving_in_shift += seconds;
+
+        self.leg_progress += distance;
+        while self.leg_progress >= self.route.leg_length(self.leg) {
+            self.leg_progress -= self.route.leg_length(self.leg);
+            self.leg = (self.leg + 1) % self.route.legs();
+            if self.deliver_at_end_of_leg && self.pending_stop.is_none() {
+                self.pending_stop = Some(StopReason::Delivery);
+            }
+            self.deliver_at_end_of_leg = self.rng.gen_bool(0.5);
+            self.cruise_speed = self.rng.gen_range(50.0..85.0);
+        }
+        let (start, end) = self.route.leg(self.leg);
+        let leg_length = self.route.leg_length(self.leg);
+        if leg_length > 0.0 {
+            self.position = start.interpolate(&end, self.leg_progress / leg_length);
+            self.heading = start.bearing_to(&end);
+        }
+
+        if self.pending_stop.is_none() {
+            if self.driving_in_shift >= self.max_driving_in_shift {
+                self.pending_stop = Some(StopReason::EndOfShift);
+            } else if self.driving_since_break >= self.max_driving_since_break {
+                self.pending_stop = Some(StopReason::Break);
+            }
+        }
+
+        if self.speed <= 0.0 {
+            if let Some(reason) = self.pending_stop.take() {
+                self.stop(reason, triggers);
+            }
+        }
+
+        if self.ect_tell_tale == TELL_TALE_STATUS_OFF
+            && self
+                .rng
+                .gen_bool((ECT_WARNING_PROBABILITY_PER_S * seconds).min(1.0))
+        {
+            self.ect_tell_tale = TELL_TALE_STATUS_RED;
+            self.ect_warning_remaining = self.rng.gen_range(2.0 * MINUTE..10.0 * MINUTE);
+            triggers.push(Self::new_tell_tale_trigger(
+                TELL_TALE_NAME_ECT,
+                TELL_TALE_STATUS_RED,
+            ));
+        }
+    }
+
+    fn consume_fuel(&mut self, amount: f64) {
+        self.fuel_used += amount;
+        self.fuel_in_tank = (self.fuel_in_tank - amount).max(0.0);
+    }
+
+    fn update_tell_tales(&mut self, seconds: f64, triggers: &mut Vec<Trigger>) {
+        let fuel_level_status = if self.fuel_level() < FUEL_LEVEL_WARNING_THRESHOLD {
+            TELL_TALE_STATUS_YELLOW
+        } else {
+            TELL_TALE_STATUS_OFF
+        };
+        if self.fuel_level_tell_tale != fuel_level_status {
+            self.fuel_level_tell_tale = fuel_level_status;
+            triggers.push(Self::new_tell_tale_trigger(
+                TELL_TALE_NAME_FUEL_LEVEL,
+                fuel_level_status,
+            ));
+        }
+
+        if self.ect_tell_tale != TELL_TALE_STATUS_OFF {
+            self.ect_warning_remaining -= seconds;
+            if self.ect_warning_remaining <= 0.0 {
+                self.ect_tell_tale = TELL_TALE_STATUS_OFF;
+                triggers.push(Self::new_tell_tale_trigger(
+                    TELL_TALE_NAME_ECT,
+                    TELL_TALE_STATUS_OFF,
+                ));
+            }
+        }
+    }
+
+    fn step(&mut self, seconds: f64, triggers: &mut Vec<Trigger>) {
+        if self.engine_running {
+            self.engine_hours += seconds / HOUR;
+            self.consume_fuel(FUEL_CONSUMPTION_IDLE_ML_PER_S * seconds);
+        }
+        if self.activity == Activity::Driving {
+            self.drive(seconds, triggers);
+        } else {
+            self.activity_remaining -= seconds;
+            if self.activity_remaining <= 0.0 {
+                self.end_activity(triggers);
+            }
+        }
+        self.update_tell_tales(seconds, triggers);
+    }
+
+    /// Advances the simulation by the given number of (simulated) seconds.
+    ///
+    /// Returns the triggers for the events that have occurred in the meantime.
+    pub fn advance(&mut self, seconds: f64) -> Vec<Trigger> {
+        let mut triggers = Vec::new();
+        let mut remaining = seconds;
+        while remaining > 0.0 {
+            let step = remaining.min(MAX_STEP_S);
+            self.step(step, &mut triggers);
+            remaining -= step;
+        }
+        triggers
+    }
+
+    /// Creates a snapshot of the vehicle's current status.
+    pub fn vehicle_status(&self, mut trigger: Trigger) -> VehicleStatus {
+        let now = Timestamp::now();
+        if trigger.event_time.is_none() {
+            trigger.event_time = MessageField::some(now.clone());
+        }
+        let mut vehicle_status = VehicleStatus::new();
+        vehicle_status.vin = self.vin.clone();
+        vehicle_status.trigger = MessageField::some(trigger);
+        vehicle_status.created = MessageField::some(now.clone());
+        vehicle_status.hr_total_vehicle_distance = Some(self.odometer as u64);
+        vehicle_status.total_engine_hours = Some(self.engine_hours);
+        vehicle_status.gross_combination_vehicle_weight =
+            Some(self.gross_combination_vehicle_weight);
+        vehicle_status.engine_total_fuel_used = Some(self.fuel_used as u64);
+        if self.card_present {
+            let driver_id = vehicle_status
+                .driver1_id
+                .mut_or_insert_default()
+                .tacho_driver_identification
+                .mut_or_insert_default();
+            driver_id.driver_identification = self.driver_id.clone();
+            driver_id.card_issuing_memberState = "D".to_string();
+        }
+
+        let snapshot_data = vehicle_status.snapshot_data.mut_or_insert_default();
+        let gnss_position = snapshot_data.gnss_position.mut_or_insert_default();
+        gnss_position.latitude = self.position.latitude;
+        gnss_position.longitude = self.position.longitude;
+        gnss_position.heading = Some(self.heading as u32);
+        gnss_position.altitude = Some(self.altitude);
+        gnss_position.speed = Some(self.speed);
+        gnss_position.instant = MessageField::some(now);
+        snapshot_data.wheel_based_speed = Some(self.speed);
+        snapshot_data.tachograph_speed = Some(self.speed);
+        snapshot_data.engine_speed = Some(if self.engine_running {
+            IDLE_ENGINE_SPEED + self.speed * 12.0
+        } else {
+            0.0
+        });
+        snapshot_data.fuel_type = Some(FUEL_TYPE_DIESEL.to_string());
+        snapshot_data.fuel_level1 = Some(self.fuel_level());
+        snapshot_data.catalyst_fuel_level =
+            Some((self.fuel_level() / ADBLUE_PER_FUEL_RATIO / 20.0).min(100.0));
+        snapshot_data.driver1_working_state = Some(self.working_state.to_string());
+        snapshot_data.ambient_air_temperature = Some(self.ambient_air_temperature);
+        snapshot_data.parking_brake_engaged = Some(self.parking_brake_engaged);
+        snapshot_data
+            .estimated_distance_to_empty
+            .mut_or_insert_default()
+            .fuel = Some((self.fuel_in_tank / FUEL_CONSUMPTION_ML_PER_M) as u64);
+        vehicle_status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_vehicle() -> SimulatedVehicle {
+        let mut rng = StdRng::seed_from_u64(1);
+        let center = Position {
+            latitude: 48.7758,
+            longitude: 9.1829,
+        };
+        let route = Route::random(&mut rng, center, 10_000.0, 20_000.0);
+        SimulatedVehicle::new(
+            "SIMFMS00000000001".to_string(),
+            "DSIM0000000001".to_string(),
+            route,
+            1,
+        )
+    }
+
+    fn trigger_types(triggers: &[Trigger]) -> Vec<&str> {
+        triggers.iter().map(|t| t.type_.as_str()).collect()
+    }
+
+    #[test]
+    fn test_shift_starts_with_login_and_engine_on() {
+        let mut vehicle = new_vehicle();
+        let triggers = vehicle.advance(HOUR + 20.0 * MINUTE);
+        let types = trigger_types(&triggers);
+        let login = types.iter().position(|t| *t == TRIGGER_DRIVER_LOGIN);
+        let engine_on = types.iter().position(|t| *t == TRIGGER_ENGINE_ON);
+        assert!(login.is_some());
+        assert!(engine_on.is_some());
+        assert!(login < engine_on);
+        assert!(vehicle.card_present);
+        assert_eq!(vehicle.working_state, WORKING_STATE_DRIVE);
+    }
+
+    #[test]
+    fn test_driving_consumes_fuel_and_moves_vehicle() {
+        let mut vehicle = new_vehicle();
+        vehicle.advance(HOUR + 20.0 * MINUTE);
+        let odometer = vehicle.odometer;
+        let fuel_used = vehicle.fuel_used;
+        let position = vehicle.position;
+        vehicle.advance(30.0 * MINUTE);
+        assert!(vehicle.odometer > odometer);
+        assert!(vehicle.fuel_used > fuel_used);
+        assert_ne!(vehicle.position, position);
+    }
+
+    #[test]
+    fn test_driver_takes_breaks_and_logs_out_at_end_of_shift() {
+        let mut vehicle = new_vehicle();
+        let mut types = Vec::new();
+        let mut took_break = false;
+        for _ in 0..(24 * 60) {
+            let triggers = vehicle.advance(MINUTE);
+            types.extend(triggers.into_iter().map(|t| t.type_));
+            took_break |= vehicle.activity == Activity::Break;
+            // the driver must never drive longer than 4.5 hours without a break
+            assert!(vehicle.driving_since_break <= 4.5 * HOUR);
+        }
+        assert!(took_break);
+        assert!(types.iter().any(|t| t == TRIGGER_DRIVER_LOGOUT));
+        let status = vehicle.vehicle_status(SimulatedVehicle::timer_trigger());
+        assert_eq!(status.vin, "SIMFMS00000000001");
+        assert_eq!(status.trigger.type_, TRIGGER_TIMER);
+    }
+}
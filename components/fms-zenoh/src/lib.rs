@@ -17,26 +17,240 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
-use std::path::PathBuf;
+//! Command line options for configuring Eclipse Zenoh based uProtocol transports.
+//!
+//! The configuration can be read from a file and/or be set by means of individual options,
+//! which take precedence over the corresponding values from the file.
 
-use clap::Args;
+use std::path::{Path, PathBuf};
+
+use clap::{Args, ValueEnum};
 use zenoh::config::Config;
 
+pub mod vehicle_status;
+
+const PARAM_CONFIG: &str = "config";
+const PARAM_CONNECT: &str = "connect";
+const PARAM_LISTEN: &str = "listen";
+const PARAM_MODE: &str = "mode";
+const PARAM_MULTICAST_SCOUTING: &str = "multicast-scouting";
+const PARAM_PASSWORD: &str = "password";
+const PARAM_TLS_CONNECT_CERTIFICATE: &str = "tls-connect-certificate";
+const PARAM_TLS_CONNECT_PRIVATE_KEY: &str = "tls-connect-private-key";
+const PARAM_TLS_ENABLE_MTLS: &str = "tls-enable-mtls";
+const PARAM_TLS_LISTEN_CERTIFICATE: &str = "tls-listen-certificate";
+const PARAM_TLS_LISTEN_PRIVATE_KEY: &str = "tls-listen-private-key";
+const PARAM_TLS_ROOT_CA_CERTIFICATE: &str = "tls-root-ca-certificate";
+const PARAM_USERNAME: &str = "username";
+
+/// The modes that a Zenoh session can run in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Mode {
+    Peer,
+    Client,
+    Router,
+}
+
+impl Mode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Mode::Peer => "peer",
+            Mode::Client => "client",
+            Mode::Router => "router",
+        }
+    }
+}
+
 /// Parameters for configuring the connection to an Eclipse Zenoh router.
 #[derive(Args)]
 pub struct ZenohTransportConfig {
-    /// A file to read the Zenoh configuration from.
-    #[arg(long = "config", short)]
+    /// A file to read the Zenoh configuration from. The other options take precedence
+    /// over the corresponding values from the file.
+    #[arg(long = PARAM_CONFIG, short, value_name = "PATH", env = "ZENOH_CONFIG")]
     config_file: Option<PathBuf>,
+
+    /// The mode to run the Zenoh session in.
+    #[arg(long = PARAM_MODE, value_name = "MODE", env = "ZENOH_MODE", value_enum)]
+    mode: Option<Mode>,
+
+    /// The endpoints to connect to, separated by commas, e.g. tcp/zenoh-router:7447.
+    /// May also be used multiple times.
+    #[arg(long = PARAM_CONNECT, value_name = "ENDPOINT", env = "ZENOH_CONNECT", value_delimiter = ',')]
+    connect: Vec<String>,
+
+    /// The endpoints to listen on, separated by commas, e.g. tcp/0.0.0.0:7447.
+    /// May also be used multiple times.
+    #[arg(long = PARAM_LISTEN, value_name = "ENDPOINT", env = "ZENOH_LISTEN", value_delimiter = ',')]
+    listen: Vec<String>,
+
+    /// Indicates whether other Zenoh nodes should be discovered by means of UDP multicast.
+    #[arg(long = PARAM_MULTICAST_SCOUTING, value_name = "FLAG", env = "ZENOH_MULTICAST_SCOUTING")]
+    multicast_scouting: Option<bool>,
+
+    /// The path to a PEM file containing the certificates of the CAs to trust when establishing TLS connections.
+    #[arg(long = PARAM_TLS_ROOT_CA_CERTIFICATE, value_name = "PATH", env = "ZENOH_TLS_ROOT_CA_CERTIFICATE")]
+    tls_root_ca_certificate: Option<PathBuf>,
+
+    /// The path to a PEM file containing the X.509 certificate to use for authenticating to peers
+    /// when connecting to a TLS endpoint using mutual TLS.
+    #[arg(id = PARAM_TLS_CONNECT_CERTIFICATE, long = PARAM_TLS_CONNECT_CERTIFICATE, value_name = "PATH", env = "ZENOH_TLS_CONNECT_CERTIFICATE", requires = PARAM_TLS_CONNECT_PRIVATE_KEY)]
+    tls_connect_certificate: Option<PathBuf>,
+
+    /// The path to a PEM file containing the private key of the certificate set via --tls-connect-certificate.
+    #[arg(id = PARAM_TLS_CONNECT_PRIVATE_KEY, long = PARAM_TLS_CONNECT_PRIVATE_KEY, value_name = "PATH", env = "ZENOH_TLS_CONNECT_PRIVATE_KEY", requires = PARAM_TLS_CONNECT_CERTIFICATE)]
+    tls_connect_private_key: Option<PathBuf>,
+
+    /// The path to a PEM file containing the X.509 certificate to present to peers connecting to a TLS endpoint.
+    #[arg(id = PARAM_TLS_LISTEN_CERTIFICATE, long = PARAM_TLS_LISTEN_CERTIFICATE, value_name = "PATH", env = "ZENOH_TLS_LISTEN_CERTIFICATE", requires = PARAM_TLS_LISTEN_PRIVATE_KEY)]
+    tls_listen_certificate: Option<PathBuf>,
+
+    /// The path to a PEM file containing the private key of the certificate set via --tls-listen-certificate.
+    #[arg(id = PARAM_TLS_LISTEN_PRIVATE_KEY, long = PARAM_TLS_LISTEN_PRIVATE_KEY, value_name = "PATH", env = "ZENOH_TLS_LISTEN_PRIVATE_KEY", requires = PARAM_TLS_LISTEN_CERTIFICATE)]
+    tls_listen_private_key: Option<PathBuf>,
+
+    /// Indicates whether peers need to authenticate by means of a client certificate (mutual TLS).
+    #[arg(long = PARAM_TLS_ENABLE_MTLS, value_name = "FLAG", env = "ZENOH_TLS_ENABLE_MTLS")]
+    tls_enable_mtls: Option<bool>,
+
+    /// The username to use for authenticating to peers.
+    #[arg(id = PARAM_USERNAME, long = PARAM_USERNAME, value_name = "USERNAME", env = "ZENOH_USERNAME", requires = PARAM_PASSWORD)]
+    username: Option<String>,
+
+    /// The password to use for authenticating to peers.
+    #[arg(id = PARAM_PASSWORD, long = PARAM_PASSWORD, value_name = "PWD", env = "ZENOH_PASSWORD", requires = PARAM_USERNAME)]
+    password: Option<String>,
+}
+
+/// Encodes a string as a JSON5 string literal.
+fn json5_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn json5_string_array(values: &[String]) -> String {
+    let items: Vec<String> = values.iter().map(|value| json5_string(value)).collect();
+    format!("[{}]", items.join(","))
+}
+
+fn json5_path(path: &Path) -> String {
+    json5_string(&path.to_string_lossy())
 }
 
 impl TryFrom<ZenohTransportConfig> for Config {
     type Error = Box<dyn std::error::Error>;
     fn try_from(value: ZenohTransportConfig) -> Result<Self, Self::Error> {
-        if let Some(path) = &value.config_file {
-            Config::from_file(path).map_err(|e| e as Box<dyn std::error::Error>)
+        let mut config = if let Some(path) = &value.config_file {
+            Config::from_file(path).map_err(|e| e as Box<dyn std::error::Error>)?
         } else {
-            Ok(Config::default())
+            Config::default()
+        };
+
+        let mut settings: Vec<(&str, String)> = Vec::new();
+        if let Some(mode) = value.mode {
+            settings.push(("mode", json5_string(mode.as_str())));
+        }
+        if !value.connect.is_empty() {
+            settings.push(("connect/endpoints", json5_string_array(&value.connect)));
+        }
+        if !value.listen.is_empty() {
+            settings.push(("listen/endpoints", json5_string_array(&value.listen)));
+        }
+        if let Some(enabled) = value.multicast_scouting {
+            settings.push(("scouting/multicast/enabled", enabled.to_string()));
+        }
+        for (key, path) in [
+            (
+                "transport/link/tls/root_ca_certificate",
+                &value.tls_root_ca_certificate,
+            ),
+            (
+                "transport/link/tls/connect_certificate",
+                &value.tls_connect_certificate,
+            ),
+            (
+                "transport/link/tls/connect_private_key",
+                &value.tls_connect_private_key,
+            ),
+            (
+                "transport/link/tls/listen_certificate",
+                &value.tls_listen_certificate,
+            ),
+            (
+                "transport/link/tls/listen_private_key",
+                &value.tls_listen_private_key,
+            ),
+        ] {
+            if let Some(path) = path {
+                settings.push((key, json5_path(path)));
+            }
+        }
+        if let Some(enabled) = value.tls_enable_mtls {
+            settings.push(("transport/link/tls/enable_mtls", enabled.to_string()));
         }
+        if let (Some(username), Some(password)) = (&value.username, &value.password) {
+            settings.push(("transport/auth/usrpwd/user", json5_string(username)));
+            settings.push(("transport/auth/usrpwd/password", json5_string(password)));
+        }
+
+        for (key, json5_value) in settings {
+            config
+                .insert_json5(key, &json5_value)
+                .map_err(|e| format!("invalid Zenoh configuration value for [{key}]: {e}"))?;
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{Command, FromArgMatches};
+
+    use super::*;
+
+    #[test]
+    fn test_options_are_applied_to_config() {
+        let command = ZenohTransportConfig::augment_args(Command::new("zenoh"));
+        let matches = command.get_matches_from(vec![
+            "zenoh",
+            "--mode",
+            "client",
+            "--connect",
+            "tcp/router-1:7447,tcp/router-2:7447",
+            "--multicast-scouting",
+            "false",
+            "--tls-root-ca-certificate",
+            "/etc/ca.pem",
+            "--username",
+            "fms",
+            "--password",
+            "secret",
+        ]);
+        let zenoh_config = ZenohTransportConfig::from_arg_matches(&matches).unwrap();
+        let config = Config::try_from(zenoh_config).unwrap();
+        assert_eq!(config.get_json("mode").unwrap(), r#""client""#);
+        assert_eq!(
+            config.get_json("connect/endpoints").unwrap(),
+            r#"["tcp/router-1:7447","tcp/router-2:7447"]"#
+        );
+        assert_eq!(
+            config.get_json("scouting/multicast/enabled").unwrap(),
+            "false"
+        );
+        assert_eq!(
+            config
+                .get_json("transport/link/tls/root_ca_certificate")
+                .unwrap(),
+            r#""/etc/ca.pem""#
+        );
+        assert_eq!(
+            config.get_json("transport/auth/usrpwd/user").unwrap(),
+            r#""fms""#
+        );
+    }
+
+    #[test]
+    fn test_username_requires_password() {
+        let command = ZenohTransportConfig::augment_args(Command::new("zenoh"));
+        let matches = command.try_get_matches_from(vec!["zenoh", "--username", "fms"]);
+        assert!(matches.is_err_and(|e| e.kind() == clap::error::ErrorKind::MissingRequiredArgument));
     }
 }
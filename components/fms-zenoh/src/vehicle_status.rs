@@ -0,0 +1,264 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! A Zenoh queryable that answers `get` requests for the most recent status of vehicles.
+//!
+//! The status of a vehicle can be retrieved using key `<prefix>/<VIN>`, e.g. `fms/vehicle-status/WBA1234567890`.
+//! Selectors containing wildcards, e.g. `fms/vehicle-status/*`, yield the status of all matching vehicles.
+//! The status is replied in its protobuf encoding.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use clap::Args;
+use fms_proto::fms::VehicleStatus;
+use log::{debug, info, warn};
+use protobuf::Message;
+use zenoh::bytes::Encoding;
+use zenoh::config::{Config, ZenohId};
+use zenoh::key_expr::KeyExpr;
+use zenoh::query::{Query, Queryable};
+use zenoh::{Session, Wait};
+
+const PARAM_STATUS_QUERYABLE: &str = "status-queryable";
+
+/// Parameters for providing the most recent status of vehicles by means of a Zenoh queryable.
+#[derive(Args)]
+pub struct VehicleStatusQueryableConfig {
+    /// The key expression prefix to declare a queryable for the most recent status of vehicles under,
+    /// e.g. fms/vehicle-status. The status of a vehicle can then be retrieved using key <prefix>/<VIN>.
+    /// No queryable is declared if not set.
+    #[arg(long = PARAM_STATUS_QUERYABLE, value_name = "KEY_PREFIX", env = "ZENOH_STATUS_QUERYABLE", value_parser = parse_key_prefix)]
+    pub key_prefix: Option<String>,
+}
+
+fn parse_key_prefix(s: &str) -> Result<String, String> {
+    let key_prefix = s.trim_end_matches('/');
+    if key_prefix.contains(['*', '$']) {
+        return Err("must not contain wildcards".to_string());
+    }
+    KeyExpr::try_from(format!("{key_prefix}/*"))
+        .map(|_| key_prefix.to_string())
+        .map_err(|e| e.to_string())
+}
+
+struct StatusEntry {
+    created: (i64, i32),
+    payload: Vec<u8>,
+}
+
+/// The most recent status of each vehicle, as reported to the local process.
+#[derive(Default)]
+pub struct LatestVehicleStatus {
+    entries: RwLock<HashMap<String, StatusEntry>>,
+}
+
+fn created(vehicle_status: &VehicleStatus) -> (i64, i32) {
+    vehicle_status
+        .created
+        .as_ref()
+        .map_or((0, 0), |created| (created.seconds, created.nanos))
+}
+
+impl LatestVehicleStatus {
+    /// Records the status of a vehicle.
+    ///
+    /// The status is ignored if a status of the vehicle that has been created later on
+    /// has already been recorded, e.g. because the statuses have been received out of order.
+    ///
+    /// Returns `true` if the status has been recorded.
+    pub fn update(&self, vehicle_status: &VehicleStatus) -> bool {
+        let created = created(vehicle_status);
+        let mut entries = self.entries.write().expect("lock is not poisoned");
+        if entries
+            .get(&vehicle_status.vin)
+            .is_some_and(|entry| entry.created > created)
+        {
+            return false;
+        }
+        match vehicle_status.write_to_bytes() {
+            Ok(payload) => {
+                entries.insert(
+                    vehicle_status.vin.to_owned(),
+                    StatusEntry { created, payload },
+                );
+                true
+            }
+            Err(e) => {
+                warn!("failed to serialize vehicle status: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Gets the number of vehicles that a status has been recorded for.
+    pub fn len(&self) -> usize {
+        self.entries.read().expect("lock is not poisoned").len()
+    }
+
+    /// Checks if no status has been recorded for any vehicle yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the (encoded) status of all vehicles whose key intersects with a selector's key expression.
+    fn matching(
+        &self,
+        key_prefix: &str,
+        selector: &KeyExpr<'_>,
+    ) -> Vec<(KeyExpr<'static>, Vec<u8>)> {
+        let entries = self.entries.read().expect("lock is not poisoned");
+        entries
+            .iter()
+            .filter_map(|(vin, entry)| {
+                // VINs containing characters that are not allowed in key expressions are skipped
+                let key = KeyExpr::try_from(format!("{key_prefix}/{vin}")).ok()?;
+                selector
+                    .intersects(&key)
+                    .then(|| (key, entry.payload.clone()))
+            })
+            .collect()
+    }
+}
+
+/// A Zenoh queryable providing the most recent status of vehicles.
+///
+/// The queryable uses a dedicated Zenoh session, which is closed when the queryable is dropped.
+/// The queryable cannot be declared on the uProtocol transport's session, because `UPTransportZenoh`
+/// does not expose its session. Opening the transport's session on a shared Zenoh runtime instead
+/// would require Zenoh's internal API (the transport's `zenoh-unstable` feature), which may change
+/// with any Zenoh release. Note that the dedicated session establishes its own links to the Zenoh
+/// network and takes part in scouting, i.e. declaring the queryable doubles the number of links
+/// that the process maintains.
+pub struct VehicleStatusQueryable {
+    _queryable: Queryable<()>,
+    _session: Session,
+}
+
+impl VehicleStatusQueryable {
+    /// Opens a Zenoh session and declares the queryable.
+    ///
+    /// The session is configured like the uProtocol transport's session, except that
+    /// it uses its own Zenoh ID and does not listen on any endpoints, which are already
+    /// in use by the transport.
+    pub async fn declare(
+        config: &Config,
+        key_prefix: &str,
+        latest_vehicle_status: Arc<LatestVehicleStatus>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let config = session_config(config)?;
+        let session = zenoh::open(config)
+            .await
+            .map_err(|e| e as Box<dyn std::error::Error>)?;
+        let key_expr = format!("{key_prefix}/*");
+        let prefix = key_prefix.to_string();
+        let queryable = session
+            .declare_queryable(&key_expr)
+            .callback(move |query| reply(&query, &prefix, &latest_vehicle_status))
+            .await
+            .map_err(|e| e as Box<dyn std::error::Error>)?;
+        info!("declared queryable for most recent vehicle status [key expression: {key_expr}]");
+        Ok(VehicleStatusQueryable {
+            _queryable: queryable,
+            _session: session,
+        })
+    }
+}
+
+/// Derives the configuration of the queryable's session from the transport's configuration.
+fn session_config(config: &Config) -> Result<Config, Box<dyn std::error::Error>> {
+    let mut config = config.clone();
+    // two sessions with the same ID cannot be connected to the same Zenoh network
+    config
+        .insert_json5("id", &format!("\"{}\"", ZenohId::default()))
+        .map_err(|e| e as Box<dyn std::error::Error>)?;
+    config
+        .insert_json5("listen/endpoints", "[]")
+        .map_err(|e| e as Box<dyn std::error::Error>)?;
+    Ok(config)
+}
+
+fn reply(query: &Query, key_prefix: &str, latest_vehicle_status: &LatestVehicleStatus) {
+    let matching = latest_vehicle_status.matching(key_prefix, query.key_expr());
+    debug!(
+        "replying to query [selector: {}] with status of {} vehicle(s)",
+        query.selector(),
+        matching.len()
+    );
+    for (key, payload) in matching {
+        if let Err(e) = query
+            .reply(key, payload)
+            .encoding(Encoding::APPLICATION_PROTOBUF.with_schema(VehicleStatus::NAME))
+            .wait()
+        {
+            warn!("failed to reply to query: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use protobuf::well_known_types::timestamp::Timestamp;
+
+    use super::*;
+
+    fn vehicle_status(vin: &str, seconds: i64) -> VehicleStatus {
+        let mut vehicle_status = VehicleStatus::new();
+        vehicle_status.vin = vin.to_string();
+        vehicle_status.created = Some(Timestamp {
+            seconds,
+            ..Default::default()
+        })
+        .into();
+        vehicle_status
+    }
+
+    #[test]
+    fn test_latest_vehicle_status_is_matched_by_selector() {
+        let latest = LatestVehicleStatus::default();
+        assert!(latest.update(&vehicle_status("VIN1", 20)));
+        assert!(!latest.update(&vehicle_status("VIN1", 10)));
+        assert!(latest.update(&vehicle_status("VIN2", 10)));
+        assert_eq!(latest.len(), 2);
+
+        let selector = KeyExpr::try_from("fms/vehicle-status/VIN1").unwrap();
+        let matching = latest.matching("fms/vehicle-status", &selector);
+        assert_eq!(matching.len(), 1);
+        let status = VehicleStatus::parse_from_bytes(&matching[0].1).unwrap();
+        assert_eq!(status.created.seconds, 20);
+
+        let selector = KeyExpr::try_from("fms/vehicle-status/*").unwrap();
+        assert_eq!(latest.matching("fms/vehicle-status", &selector).len(), 2);
+        assert!(parse_key_prefix("fms/*").is_err());
+    }
+
+    #[test]
+    fn test_session_config_uses_new_id() {
+        let mut config = Config::default();
+        config
+            .insert_json5("listen/endpoints", r#"["tcp/0.0.0.0:7447"]"#)
+            .unwrap();
+        let session_config = session_config(&config).unwrap();
+        assert_ne!(
+            session_config.get_json("id").unwrap(),
+            config.get_json("id").unwrap()
+        );
+        assert_eq!(session_config.get_json("listen/endpoints").unwrap(), "[]");
+    }
+}
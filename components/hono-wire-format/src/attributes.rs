@@ -0,0 +1,195 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Encoding of uProtocol message attributes as a list of name/value pairs, e.g. MQTT 5 user
+//! properties or Kafka headers, that is shared by the MQTT 5 and Kafka based transports.
+//!
+//! As defined by the uProtocol MQTT 5 transport specification, each attribute is conveyed using its
+//! protobuf field number as the name and its (string encoded) value. An additional `uP` pair contains
+//! the version of the uProtocol message format.
+
+use std::str::FromStr;
+
+use protobuf::{Enum, EnumOrUnknown};
+use up_rust::{UAttributes, UCode, UMessageType, UPayloadFormat, UPriority, UUri, UUID};
+
+/// The name of the pair that contains the version of the uProtocol message format.
+pub const NAME_UPROTOCOL_VERSION: &str = "uP";
+/// The version of the uProtocol message format.
+pub const UPROTOCOL_VERSION: &str = "1";
+
+pub const NAME_ID: &str = "1";
+pub const NAME_TYPE: &str = "2";
+pub const NAME_SOURCE: &str = "3";
+pub const NAME_SINK: &str = "4";
+pub const NAME_PRIORITY: &str = "5";
+pub const NAME_TTL: &str = "6";
+pub const NAME_PERMISSION_LEVEL: &str = "7";
+pub const NAME_COMMSTATUS: &str = "8";
+pub const NAME_REQUEST_ID: &str = "9";
+pub const NAME_TOKEN: &str = "10";
+pub const NAME_TRACEPARENT: &str = "11";
+pub const NAME_PAYLOAD_FORMAT: &str = "12";
+
+/// Gets the attributes of a uProtocol message as a list of name/value pairs.
+///
+/// The list starts with the version of the uProtocol message format and
+/// contains all attributes that have been set.
+pub fn to_pairs(attributes: &UAttributes) -> Vec<(&'static str, String)> {
+    let mut pairs = Vec::new();
+    pairs.push((NAME_UPROTOCOL_VERSION, UPROTOCOL_VERSION.to_string()));
+    if let Some(id) = attributes.id.as_ref() {
+        pairs.push((NAME_ID, id.to_hyphenated_string()));
+    }
+    pairs.push((NAME_TYPE, attributes.type_.value().to_string()));
+    if let Some(source) = attributes.source.as_ref() {
+        pairs.push((NAME_SOURCE, source.to_uri(false)));
+    }
+    if let Some(sink) = attributes.sink.as_ref() {
+        pairs.push((NAME_SINK, sink.to_uri(false)));
+    }
+    pairs.push((NAME_PRIORITY, attributes.priority.value().to_string()));
+    if let Some(ttl) = attributes.ttl {
+        pairs.push((NAME_TTL, ttl.to_string()));
+    }
+    if let Some(permission_level) = attributes.permission_level {
+        pairs.push((NAME_PERMISSION_LEVEL, permission_level.to_string()));
+    }
+    if let Some(commstatus) = attributes.commstatus {
+        pairs.push((NAME_COMMSTATUS, commstatus.value().to_string()));
+    }
+    if let Some(request_id) = attributes.reqid.as_ref() {
+        pairs.push((NAME_REQUEST_ID, request_id.to_hyphenated_string()));
+    }
+    if let Some(token) = attributes.token.as_ref() {
+        pairs.push((NAME_TOKEN, token.to_owned()));
+    }
+    if let Some(traceparent) = attributes.traceparent.as_ref() {
+        pairs.push((NAME_TRACEPARENT, traceparent.to_owned()));
+    }
+    pairs.push((
+        NAME_PAYLOAD_FORMAT,
+        attributes.payload_format.value().to_string(),
+    ));
+    pairs
+}
+
+fn parse_number<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse::<T>()
+        .map_err(|_e| format!("invalid value for attribute [{name}]: {value}"))
+}
+
+fn parse_enum<E: Enum>(name: &str, value: &str) -> Result<EnumOrUnknown<E>, String> {
+    parse_number::<i32>(name, value).map(EnumOrUnknown::from_i32)
+}
+
+fn parse_uri(name: &str, value: &str) -> Result<UUri, String> {
+    UUri::from_str(value).map_err(|e| format!("invalid URI in attribute [{name}]: {e}"))
+}
+
+fn parse_uuid(name: &str, value: &str) -> Result<UUID, String> {
+    UUID::from_str(value).map_err(|e| format!("invalid UUID in attribute [{name}]: {e}"))
+}
+
+/// Creates uProtocol message attributes from a list of name/value pairs.
+///
+/// Pairs with unknown names are ignored.
+///
+/// # Errors
+///
+/// Returns an error if the pairs do not indicate a uProtocol message
+/// or if any of the attribute values is invalid.
+pub fn from_pairs<I, N, V>(pairs: I) -> Result<UAttributes, String>
+where
+    I: IntoIterator<Item = (N, V)>,
+    N: AsRef<str>,
+    V: AsRef<str>,
+{
+    let mut attributes = UAttributes::new();
+    let mut uprotocol_version = None;
+    for (name, value) in pairs {
+        let (name, value) = (name.as_ref(), value.as_ref());
+        match name {
+            NAME_UPROTOCOL_VERSION => uprotocol_version = Some(value.to_string()),
+            NAME_ID => attributes.id = Some(parse_uuid(name, value)?).into(),
+            NAME_TYPE => attributes.type_ = parse_enum::<UMessageType>(name, value)?,
+            NAME_SOURCE => attributes.source = Some(parse_uri(name, value)?).into(),
+            NAME_SINK => attributes.sink = Some(parse_uri(name, value)?).into(),
+            NAME_PRIORITY => attributes.priority = parse_enum::<UPriority>(name, value)?,
+            NAME_TTL => attributes.ttl = Some(parse_number(name, value)?),
+            NAME_PERMISSION_LEVEL => attributes.permission_level = Some(parse_number(name, value)?),
+            NAME_COMMSTATUS => attributes.commstatus = Some(parse_enum::<UCode>(name, value)?),
+            NAME_REQUEST_ID => attributes.reqid = Some(parse_uuid(name, value)?).into(),
+            NAME_TOKEN => attributes.token = Some(value.to_string()),
+            NAME_TRACEPARENT => attributes.traceparent = Some(value.to_string()),
+            NAME_PAYLOAD_FORMAT => {
+                attributes.payload_format = parse_enum::<UPayloadFormat>(name, value)?
+            }
+            _ => {}
+        }
+    }
+    match uprotocol_version.as_deref() {
+        Some(UPROTOCOL_VERSION) => Ok(attributes),
+        Some(version) => Err(format!("unsupported uProtocol version: {version}")),
+        None => Err("message does not contain uProtocol attributes".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use up_rust::UMessageBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_attributes_survive_roundtrip() {
+        let topic = UUri::from_str("up://WBA12345678901234/D100/1/D100").unwrap();
+        let message = UMessageBuilder::publish(topic)
+            .with_priority(UPriority::UPRIORITY_CS2)
+            .with_ttl(1500)
+            .with_traceparent("00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01")
+            .build()
+            .unwrap();
+        let attributes = message.attributes.get_or_default();
+
+        let pairs = to_pairs(attributes);
+        assert_eq!(pairs[0], (NAME_UPROTOCOL_VERSION, "1".to_string()));
+        assert!(pairs.contains(&(NAME_TTL, "1500".to_string())));
+        assert_eq!(&from_pairs(pairs).unwrap(), attributes);
+    }
+
+    #[test]
+    fn test_from_pairs_ignores_unknown_names() {
+        let attributes = from_pairs([("uP", "1"), ("5", "4"), ("content-type", "text/plain")]);
+        assert_eq!(
+            attributes.unwrap().priority.enum_value(),
+            Ok(UPriority::UPRIORITY_CS3)
+        );
+    }
+
+    #[test]
+    fn test_from_pairs_rejects_invalid_pairs() {
+        // no uProtocol version
+        assert!(from_pairs([(NAME_TYPE, "1")]).is_err());
+        assert!(from_pairs([("uP", "2"), (NAME_TYPE, "1")]).is_err());
+        assert!(from_pairs([("uP", "1"), (NAME_TTL, "soon")]).is_err());
+        assert!(from_pairs([("uP", "1"), (NAME_SOURCE, "up://")]).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Support for Hono's command & control message exchange pattern that is shared by
+//! all of the Hono based transports.
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use up_rust::{UAttributes, UCode};
+
+/// The time after which a command request is considered to have timed out,
+/// if the request itself does not define a time-to-live.
+const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Maps the communication status of a uProtocol response message to an HTTP status code,
+/// as expected by Hono.
+pub fn response_status(attributes: &UAttributes) -> u16 {
+    let code = attributes
+        .commstatus
+        .and_then(|status| status.enum_value().ok())
+        .unwrap_or(UCode::OK);
+    match code {
+        UCode::OK => 200,
+        UCode::INVALID_ARGUMENT | UCode::FAILED_PRECONDITION | UCode::OUT_OF_RANGE => 400,
+        UCode::UNAUTHENTICATED => 401,
+        UCode::PERMISSION_DENIED => 403,
+        UCode::NOT_FOUND => 404,
+        UCode::ALREADY_EXISTS | UCode::ABORTED => 409,
+        UCode::RESOURCE_EXHAUSTED => 429,
+        UCode::UNIMPLEMENTED => 501,
+        UCode::UNAVAILABLE => 503,
+        UCode::DEADLINE_EXCEEDED => 504,
+        _ => 500,
+    }
+}
+
+/// Keeps track of command requests that have not been responded to yet.
+///
+/// The entries are keyed by an identifier that the response can be correlated with,
+/// e.g. the ID of the uProtocol request message, and hold the information required
+/// for sending or creating the response, e.g. Hono's request ID.
+pub struct PendingRequests<T> {
+    requests: Mutex<HashMap<String, (T, Instant)>>,
+}
+
+impl<T> Default for PendingRequests<T> {
+    fn default() -> Self {
+        PendingRequests {
+            requests: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<T: Clone> PendingRequests<T> {
+    /// Adds a request that a response is expected for.
+    ///
+    /// The request expires after the given time-to-live (milliseconds) or after a
+    /// default timeout if no (positive) time-to-live is given.
+    ///
+    /// Also removes all requests that have timed out in the meantime.
+    pub fn add(&self, id: String, value: T, ttl: Option<u32>) {
+        let timeout = ttl
+            .filter(|ttl| *ttl > 0)
+            .map_or(DEFAULT_COMMAND_TIMEOUT, |ttl| {
+                Duration::from_millis(u64::from(ttl))
+            });
+        let now = Instant::now();
+        if let Ok(mut requests) = self.requests.lock() {
+            requests.retain(|_id, (_value, expires)| *expires > now);
+            requests.insert(id, (value, now + timeout));
+        }
+    }
+
+    /// Gets the value of a pending request.
+    ///
+    /// Returns `None` if no (unexpired) request exists for the given identifier.
+    pub fn get(&self, id: &str) -> Option<T> {
+        self.requests
+            .lock()
+            .ok()
+            .and_then(|requests| requests.get(id).cloned())
+            .filter(|(_value, expires)| *expires > Instant::now())
+            .map(|(value, _expires)| value)
+    }
+
+    /// Removes a pending request.
+    ///
+    /// Returns the value of the request or `None` if no (unexpired) request
+    /// exists for the given identifier.
+    pub fn remove(&self, id: &str) -> Option<T> {
+        self.requests
+            .lock()
+            .ok()
+            .and_then(|mut requests| requests.remove(id))
+            .filter(|(_value, expires)| *expires > Instant::now())
+            .map(|(value, _expires)| value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_status_maps_commstatus() {
+        let mut attributes = UAttributes::new();
+        assert_eq!(response_status(&attributes), 200);
+        attributes.commstatus = Some(UCode::NOT_FOUND.into());
+        assert_eq!(response_status(&attributes), 404);
+        attributes.commstatus = Some(UCode::INTERNAL.into());
+        assert_eq!(response_status(&attributes), 500);
+    }
+
+    #[test]
+    fn test_pending_requests_can_be_removed_once() {
+        let pending_requests = PendingRequests::default();
+        pending_requests.add("msg-id".to_string(), "hono-id".to_string(), None);
+        assert_eq!(pending_requests.get("msg-id"), Some("hono-id".to_string()));
+        assert_eq!(
+            pending_requests.remove("msg-id"),
+            Some("hono-id".to_string())
+        );
+        assert_eq!(pending_requests.remove("msg-id"), None);
+    }
+
+    #[test]
+    fn test_pending_requests_expire() {
+        let pending_requests = PendingRequests::default();
+        pending_requests.add("msg-id".to_string(), "hono-id".to_string(), Some(1));
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(pending_requests.get("msg-id"), None);
+        assert_eq!(pending_requests.remove("msg-id"), None);
+    }
+}
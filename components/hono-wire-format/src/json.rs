@@ -0,0 +1,220 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Mapping of CloudEvents to/from the [CloudEvents JSON Event Format](https://github.com/cloudevents/spec/blob/v1.0.2/cloudevents/formats/json-format.md).
+//!
+//! Extension attributes are mapped to JSON booleans, numbers or strings. Because the JSON format
+//! does not retain the attributes' CloudEvents types, URI-reference typed attributes are identified
+//! by name when parsing an event. Timestamp typed attributes are not supported because they are not
+//! used by uProtocol's CloudEvents mapping.
+//!
+//! Protobuf data is mapped to `data_base64`, with the type URL of the data being conveyed in the
+//! `dataschema` attribute. When parsing an event, `data_base64` is mapped back to protobuf data if the
+//! event's uProtocol payload format indicates a protobuf payload.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use protobuf::{well_known_types::any::Any, Enum};
+use serde_json::{Map, Number, Value};
+use up_rust::{CloudEvent, UPayloadFormat};
+
+const ATTRIBUTE_DATA: &str = "data";
+const ATTRIBUTE_DATA_BASE64: &str = "data_base64";
+const ATTRIBUTE_DATA_SCHEMA: &str = "dataschema";
+const ATTRIBUTE_ID: &str = "id";
+const ATTRIBUTE_PAYLOAD_FORMAT: &str = "pformat";
+const ATTRIBUTE_SOURCE: &str = "source";
+const ATTRIBUTE_SPEC_VERSION: &str = "specversion";
+const ATTRIBUTE_TYPE: &str = "type";
+
+// the extension attributes of type URI-reference used by uProtocol's CloudEvents mapping
+const URI_REF_ATTRIBUTES: [&str; 1] = ["sink"];
+
+/// Serializes a CloudEvent to JSON.
+pub(crate) fn to_json(event: &CloudEvent) -> Result<Vec<u8>, String> {
+    let mut object = Map::new();
+    object.insert(
+        ATTRIBUTE_SPEC_VERSION.to_string(),
+        Value::from(event.spec_version.as_str()),
+    );
+    object.insert(ATTRIBUTE_ID.to_string(), Value::from(event.id.as_str()));
+    object.insert(
+        ATTRIBUTE_SOURCE.to_string(),
+        Value::from(event.source.as_str()),
+    );
+    object.insert(
+        ATTRIBUTE_TYPE.to_string(),
+        Value::from(event.type_.as_str()),
+    );
+    for (name, value) in &event.attributes {
+        let json_value = if value.has_ce_boolean() {
+            Value::from(value.ce_boolean())
+        } else if value.has_ce_integer() {
+            Value::from(value.ce_integer())
+        } else if value.has_ce_string() {
+            Value::from(value.ce_string())
+        } else if value.has_ce_uri() {
+            Value::from(value.ce_uri())
+        } else if value.has_ce_uri_ref() {
+            Value::from(value.ce_uri_ref())
+        } else if value.has_ce_bytes() {
+            Value::from(STANDARD.encode(value.ce_bytes()))
+        } else {
+            return Err(format!("unsupported type of attribute [{name}]"));
+        };
+        object.insert(name.to_owned(), json_value);
+    }
+    if event.has_text_data() {
+        object.insert(ATTRIBUTE_DATA.to_string(), Value::from(event.text_data()));
+    } else if event.has_binary_data() {
+        object.insert(
+            ATTRIBUTE_DATA_BASE64.to_string(),
+            Value::from(STANDARD.encode(event.binary_data())),
+        );
+    } else if event.has_proto_data() {
+        let data = event.proto_data();
+        object.insert(
+            ATTRIBUTE_DATA_BASE64.to_string(),
+            Value::from(STANDARD.encode(&data.value)),
+        );
+        if !data.type_url.is_empty() {
+            object.insert(
+                ATTRIBUTE_DATA_SCHEMA.to_string(),
+                Value::from(data.type_url.as_str()),
+            );
+        }
+    }
+    serde_json::to_vec(&Value::Object(object)).map_err(|e| e.to_string())
+}
+
+fn get_string(object: &Map<String, Value>, name: &str) -> Result<String, String> {
+    object
+        .get(name)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| format!("event has no [{name}] attribute"))
+}
+
+/// Checks if the uProtocol payload format of an event indicates a protobuf payload.
+fn has_protobuf_payload(object: &Map<String, Value>) -> bool {
+    object
+        .get(ATTRIBUTE_PAYLOAD_FORMAT)
+        .and_then(Value::as_i64)
+        .is_some_and(|format| {
+            format == i64::from(UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF.value())
+                || format
+                    == i64::from(UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY.value())
+        })
+}
+
+fn get_integer(name: &str, number: &Number) -> Result<i32, String> {
+    number
+        .as_i64()
+        .and_then(|v| i32::try_from(v).ok())
+        .ok_or_else(|| format!("value of attribute [{name}] is not a 32 bit integer"))
+}
+
+/// Deserializes a CloudEvent from JSON.
+pub(crate) fn from_json(payload: &[u8]) -> Result<CloudEvent, String> {
+    let Value::Object(mut object) =
+        serde_json::from_slice::<Value>(payload).map_err(|e| e.to_string())?
+    else {
+        return Err("event is not a JSON object".to_string());
+    };
+    let mut event = CloudEvent::new();
+    event.spec_version = get_string(&object, ATTRIBUTE_SPEC_VERSION)?;
+    event.id = get_string(&object, ATTRIBUTE_ID)?;
+    event.source = get_string(&object, ATTRIBUTE_SOURCE)?;
+    event.type_ = get_string(&object, ATTRIBUTE_TYPE)?;
+    if let Some(data) = object.remove(ATTRIBUTE_DATA_BASE64) {
+        let data = data
+            .as_str()
+            .ok_or_else(|| format!("[{ATTRIBUTE_DATA_BASE64}] is not a string"))
+            .and_then(|data| STANDARD.decode(data).map_err(|e| e.to_string()))?;
+        if has_protobuf_payload(&object) {
+            let type_url = match object.remove(ATTRIBUTE_DATA_SCHEMA) {
+                Some(Value::String(type_url)) => type_url,
+                Some(_) => return Err(format!("[{ATTRIBUTE_DATA_SCHEMA}] is not a string")),
+                None => String::new(),
+            };
+            event.set_proto_data(Any {
+                type_url,
+                value: data,
+                ..Default::default()
+            });
+        } else {
+            event.set_binary_data(data);
+        }
+    } else if let Some(data) = object.remove(ATTRIBUTE_DATA) {
+        match data {
+            Value::String(text) => event.set_text_data(text),
+            other => event.set_text_data(other.to_string()),
+        }
+    }
+    for (name, value) in object {
+        if [
+            ATTRIBUTE_SPEC_VERSION,
+            ATTRIBUTE_ID,
+            ATTRIBUTE_SOURCE,
+            ATTRIBUTE_TYPE,
+        ]
+        .contains(&name.as_str())
+        {
+            continue;
+        }
+        let attribute_value = event.attributes.entry(name.clone()).or_default();
+        match value {
+            Value::Bool(v) => attribute_value.set_ce_boolean(v),
+            Value::Number(v) => attribute_value.set_ce_integer(get_integer(&name, &v)?),
+            Value::String(v) if URI_REF_ATTRIBUTES.contains(&name.as_str()) => {
+                attribute_value.set_ce_uri_ref(v)
+            }
+            Value::String(v) => attribute_value.set_ce_string(v),
+            _ => return Err(format!("unsupported type of attribute [{name}]")),
+        }
+    }
+    Ok(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proto_data_retains_type_url() {
+        let mut event = CloudEvent::new();
+        event.spec_version = "1.0".to_string();
+        event.id = "1".to_string();
+        event.source = "//vehicle/D100/1/D100".to_string();
+        event.type_ = "pub.v1".to_string();
+        let payload_format = event
+            .attributes
+            .entry(ATTRIBUTE_PAYLOAD_FORMAT.to_string())
+            .or_default();
+        payload_format
+            .set_ce_integer(UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY.value());
+        event.set_proto_data(Any {
+            type_url: "type.googleapis.com/fms.VehicleStatus".to_string(),
+            value: vec![0x0a, 0x01, 0x41],
+            ..Default::default()
+        });
+
+        let json = to_json(&event).unwrap();
+        assert_eq!(from_json(&json).unwrap(), event);
+    }
+}
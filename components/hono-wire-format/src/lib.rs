@@ -0,0 +1,329 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Encoding and decoding of uProtocol messages that are exchanged via Eclipse Hono.
+//!
+//! Messages can either be sent as CloudEvents in protobuf or JSON format, or as
+//! raw protobuf, i.e. only the message's payload, e.g. for devices that are not
+//! uProtocol aware.
+
+use clap::ValueEnum;
+use fms_proto::{fms::VehicleStatus, TRIGGER_TIMER};
+use protobuf::{well_known_types::any::Any, Message};
+use up_rust::{
+    CloudEvent, UAttributes, UCode, UMessage, UMessageBuilder, UPayloadFormat, UPriority, UStatus,
+    UUri, CONTENT_TYPE_CLOUDEVENTS_PROTOBUF,
+};
+
+pub mod attributes;
+pub mod command;
+mod json;
+
+pub const CONTENT_TYPE_CLOUDEVENTS_JSON: &str = "application/cloudevents+json";
+pub const CONTENT_TYPE_PROTOBUF: &str = "application/protobuf";
+
+// other content types that are commonly used for protobuf
+const CONTENT_TYPES_PROTOBUF_ALIASES: [&str; 2] =
+    ["application/x-protobuf", "application/vnd.google.protobuf"];
+
+/// The format of the payload of messages exchanged via Hono.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum WireFormat {
+    /// The uProtocol message as a CloudEvent in protobuf format.
+    #[default]
+    #[value(name = "cloudevents-protobuf")]
+    CloudEventsProtobuf,
+    /// The uProtocol message as a CloudEvent in JSON format.
+    #[value(name = "cloudevents-json")]
+    CloudEventsJson,
+    /// The uProtocol message's protobuf payload only, without any of the message's attributes.
+    #[value(name = "protobuf")]
+    Protobuf,
+}
+
+impl WireFormat {
+    /// Gets the content type to send messages in this format with.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            WireFormat::CloudEventsProtobuf => CONTENT_TYPE_CLOUDEVENTS_PROTOBUF,
+            WireFormat::CloudEventsJson => CONTENT_TYPE_CLOUDEVENTS_JSON,
+            WireFormat::Protobuf => CONTENT_TYPE_PROTOBUF,
+        }
+    }
+
+    /// Determines the wire format from a content type.
+    ///
+    /// Any parameters of the content type, e.g. the charset, are ignored.
+    ///
+    /// Returns `None` if the content type does not correspond to any of the supported formats.
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let media_type = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase();
+        match media_type.as_str() {
+            CONTENT_TYPE_CLOUDEVENTS_PROTOBUF => Some(WireFormat::CloudEventsProtobuf),
+            CONTENT_TYPE_CLOUDEVENTS_JSON => Some(WireFormat::CloudEventsJson),
+            CONTENT_TYPE_PROTOBUF => Some(WireFormat::Protobuf),
+            other if CONTENT_TYPES_PROTOBUF_ALIASES.contains(&other) => Some(WireFormat::Protobuf),
+            _ => None,
+        }
+    }
+
+    /// Encodes a uProtocol message into this format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message cannot be represented in this format, e.g. if the
+    /// message does not contain a protobuf payload but is to be sent as raw protobuf.
+    pub fn encode(&self, message: UMessage) -> Result<Vec<u8>, UStatus> {
+        match self {
+            WireFormat::CloudEventsProtobuf => {
+                to_cloudevent(message)?.write_to_bytes().map_err(|_e| {
+                    UStatus::fail_with_code(UCode::INTERNAL, "failed to serialize CloudEvent")
+                })
+            }
+            WireFormat::CloudEventsJson => json::to_json(&to_cloudevent(message)?)
+                .map_err(|e| UStatus::fail_with_code(UCode::INTERNAL, e)),
+            WireFormat::Protobuf => raw_protobuf_payload(message),
+        }
+    }
+}
+
+fn to_cloudevent(message: UMessage) -> Result<CloudEvent, UStatus> {
+    CloudEvent::try_from(message)
+        .map_err(|e| UStatus::fail_with_code(UCode::INVALID_ARGUMENT, e.to_string()))
+}
+
+fn raw_protobuf_payload(message: UMessage) -> Result<Vec<u8>, UStatus> {
+    let payload_format = message
+        .attributes
+        .get_or_default()
+        .payload_format
+        .enum_value_or_default();
+    let payload = message.payload.unwrap_or_default();
+    match payload_format {
+        UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF => Ok(payload.to_vec()),
+        UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF_WRAPPED_IN_ANY => {
+            Any::parse_from_tokio_bytes(&payload)
+                .map(|any| any.value)
+                .map_err(|e| UStatus::fail_with_code(UCode::INVALID_ARGUMENT, e.to_string()))
+        }
+        _ => Err(UStatus::fail_with_code(
+            UCode::INVALID_ARGUMENT,
+            "message does not contain a protobuf payload",
+        )),
+    }
+}
+
+/// Decodes a uProtocol message from the payload of a message received via Hono.
+///
+/// The format of the payload is determined from the given content type. Messages without
+/// a content type are expected to contain a CloudEvent in protobuf format.
+///
+/// Because raw protobuf payloads do not contain any uProtocol attributes, they are
+/// expected to contain a `VehicleStatus` which is published to the given default topic.
+///
+/// # Errors
+///
+/// Returns an error if the content type is not supported, if the payload cannot be
+/// decoded or if a raw protobuf payload has been received but no default topic is given.
+pub fn decode(
+    content_type: Option<&str>,
+    payload: &[u8],
+    default_topic: Option<&UUri>,
+) -> Result<UMessage, String> {
+    let wire_format = match content_type {
+        Some(content_type) => WireFormat::from_content_type(content_type)
+            .ok_or_else(|| format!("unsupported content type [{content_type}]"))?,
+        None => WireFormat::CloudEventsProtobuf,
+    };
+    match wire_format {
+        WireFormat::CloudEventsProtobuf => CloudEvent::parse_from_bytes(payload)
+            .map_err(|e| format!("failed to deserialize CloudEvent: {e}"))
+            .and_then(from_cloudevent),
+        WireFormat::CloudEventsJson => json::from_json(payload)
+            .map_err(|e| format!("failed to deserialize CloudEvent: {e}"))
+            .and_then(from_cloudevent),
+        WireFormat::Protobuf => {
+            let topic = default_topic.ok_or_else(|| {
+                "no default topic configured for raw protobuf payload".to_string()
+            })?;
+            VehicleStatus::parse_from_bytes(payload)
+                .map_err(|e| format!("payload is not a VehicleStatus: {e}"))?;
+            UMessageBuilder::publish(topic.to_owned())
+                .build_with_payload(payload.to_vec(), UPayloadFormat::UPAYLOAD_FORMAT_PROTOBUF)
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+fn from_cloudevent(event: CloudEvent) -> Result<UMessage, String> {
+    UMessage::try_from(event).map_err(|e| format!("failed to extract payload from CloudEvent: {e}"))
+}
+
+/// Parses a uProtocol priority code (CS0 - CS6), ignoring case.
+pub fn parse_priority(code: &str) -> Result<UPriority, String> {
+    UPriority::try_from_priority_code(code.to_uppercase()).map_err(|e| e.to_string())
+}
+
+/// Gets the uProtocol priority to publish a vehicle status with.
+///
+/// A vehicle status that has been triggered by an event, e.g. a driver logging in,
+/// has a higher priority than the snapshots that are taken periodically.
+pub fn vehicle_status_priority(vehicle_status: &VehicleStatus) -> UPriority {
+    if vehicle_status.trigger.type_ == TRIGGER_TIMER {
+        UPriority::UPRIORITY_CS1
+    } else {
+        UPriority::UPRIORITY_CS2
+    }
+}
+
+/// Checks if a message should be sent to Hono's event endpoint.
+///
+/// This is the case if the message's priority is at least the given event priority.
+/// Messages without a priority are considered to have the default priority CS1.
+pub fn is_event(attributes: &UAttributes, event_priority: UPriority) -> bool {
+    let priority = attributes
+        .priority
+        .enum_value()
+        .ok()
+        .filter(|priority| *priority != UPriority::UPRIORITY_UNSPECIFIED)
+        .unwrap_or(UPriority::UPRIORITY_CS1);
+    priority as i32 >= event_priority as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn vehicle_status() -> VehicleStatus {
+        VehicleStatus {
+            vin: "VIN0001".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn vehicle_status_message_builder() -> UMessageBuilder {
+        let topic = UUri::from_str("//vehicle/D100/1/D100").unwrap();
+        let mut builder = UMessageBuilder::publish(topic);
+        builder
+            .with_priority(UPriority::UPRIORITY_CS2)
+            .with_ttl(10_000);
+        builder
+    }
+
+    fn vehicle_status_message() -> UMessage {
+        vehicle_status_message_builder()
+            .build_with_protobuf_payload(&vehicle_status())
+            .unwrap()
+    }
+
+    fn wrapped_vehicle_status_message() -> UMessage {
+        vehicle_status_message_builder()
+            .build_with_wrapped_protobuf_payload(&vehicle_status())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_from_content_type_ignores_parameters() {
+        assert_eq!(
+            WireFormat::from_content_type("application/cloudevents+json; charset=utf-8"),
+            Some(WireFormat::CloudEventsJson)
+        );
+        assert_eq!(
+            WireFormat::from_content_type("application/x-protobuf"),
+            Some(WireFormat::Protobuf)
+        );
+        assert_eq!(WireFormat::from_content_type("text/plain"), None);
+    }
+
+    #[test]
+    fn test_cloudevents_formats_retain_message() {
+        for wire_format in [WireFormat::CloudEventsProtobuf, WireFormat::CloudEventsJson] {
+            for message in [vehicle_status_message(), wrapped_vehicle_status_message()] {
+                let payload = wire_format.encode(message.clone()).unwrap();
+                let decoded = decode(Some(wire_format.content_type()), &payload, None).unwrap();
+                assert_eq!(decoded, message, "wire format {wire_format:?}");
+                let vehicle_status = decoded.extract_protobuf::<VehicleStatus>().unwrap();
+                assert_eq!(vehicle_status.vin, "VIN0001");
+            }
+        }
+    }
+
+    #[test]
+    fn test_raw_protobuf_requires_default_topic() {
+        let payload = WireFormat::Protobuf
+            .encode(vehicle_status_message())
+            .unwrap();
+        assert!(decode(Some(CONTENT_TYPE_PROTOBUF), &payload, None).is_err());
+
+        let default_topic = UUri::from_str("//hono/D100/1/D100").unwrap();
+        let decoded = decode(Some(CONTENT_TYPE_PROTOBUF), &payload, Some(&default_topic)).unwrap();
+        assert_eq!(
+            decoded.attributes.get_or_default().source.as_ref(),
+            Some(&default_topic)
+        );
+        let vehicle_status = decoded.extract_protobuf::<VehicleStatus>().unwrap();
+        assert_eq!(vehicle_status.vin, "VIN0001");
+    }
+
+    #[test]
+    fn test_vehicle_status_priority_depends_on_trigger() {
+        let mut vehicle_status = vehicle_status();
+        vehicle_status.trigger.mut_or_insert_default().type_ = TRIGGER_TIMER.to_string();
+        assert_eq!(
+            vehicle_status_priority(&vehicle_status),
+            UPriority::UPRIORITY_CS1
+        );
+        vehicle_status.trigger.mut_or_insert_default().type_ = "ENGINE_OFF".to_string();
+        assert_eq!(
+            vehicle_status_priority(&vehicle_status),
+            UPriority::UPRIORITY_CS2
+        );
+    }
+
+    #[test]
+    fn test_is_event_uses_priority() {
+        let topic = UUri::from_str("//vehicle/D100/1/D100").unwrap();
+        let timer_status = UMessageBuilder::publish(topic.clone()).build().unwrap();
+        let triggered_status = UMessageBuilder::publish(topic)
+            .with_priority(UPriority::UPRIORITY_CS2)
+            .build()
+            .unwrap();
+
+        let event_priority = parse_priority("cs2").unwrap();
+        assert!(!is_event(
+            timer_status.attributes.get_or_default(),
+            event_priority
+        ));
+        assert!(is_event(
+            triggered_status.attributes.get_or_default(),
+            event_priority
+        ));
+        assert!(is_event(
+            timer_status.attributes.get_or_default(),
+            UPriority::UPRIORITY_CS0
+        ));
+    }
+}
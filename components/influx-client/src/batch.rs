@@ -0,0 +1,619 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Buffers measurements and writes them to an InfluxDB server in batches.
+//!
+//! A batch is written once it is full or once the flush interval has elapsed. Writing a batch that fails
+//! is retried with exponential backoff. Batches that still cannot be written are spilled to a local file
+//! in line protocol, if configured, and are replayed once the InfluxDB server can be reached again.
+
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use clap::Args;
+use influxrs::InfluxError;
+use log::{debug, info, warn};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+use crate::connection::InfluxConnection;
+
+const PARAM_INFLUXDB_BATCHING: &str = "influxdb-batching";
+const PARAM_INFLUXDB_BATCH_SIZE: &str = "influxdb-batch-size";
+const PARAM_INFLUXDB_FLUSH_INTERVAL: &str = "influxdb-flush-interval";
+const PARAM_INFLUXDB_MAX_QUEUE_SIZE: &str = "influxdb-max-queue-size";
+const PARAM_INFLUXDB_MAX_RETRIES: &str = "influxdb-max-retries";
+const PARAM_INFLUXDB_RETRY_BACKOFF: &str = "influxdb-retry-backoff";
+const PARAM_INFLUXDB_SPILL_DIR: &str = "influxdb-spill-dir";
+
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Parameters for writing measurements to the InfluxDB server in batches.
+#[derive(Args, Clone, Debug)]
+pub struct BatchWriterConfig {
+    /// Buffers measurements in memory and writes them to the InfluxDB server in batches
+    /// instead of writing each vehicle status individually.
+    #[arg(long = PARAM_INFLUXDB_BATCHING, env = "INFLUXDB_BATCHING")]
+    pub enabled: bool,
+
+    /// The maximum number of measurements to write to the InfluxDB server in a single request.
+    #[arg(long = PARAM_INFLUXDB_BATCH_SIZE, value_name = "NUMBER", env = "INFLUXDB_BATCH_SIZE", default_value = "500", value_parser = clap::value_parser!(u32).range(1..))]
+    batch_size: u32,
+
+    /// The maximum time to buffer measurements before writing them to the InfluxDB server, e.g. 1s or 500ms.
+    #[arg(long = PARAM_INFLUXDB_FLUSH_INTERVAL, value_name = "DURATION_SPEC", env = "INFLUXDB_FLUSH_INTERVAL", default_value = "1s", value_parser = |s: &str| duration_str::parse(s) )]
+    flush_interval: Duration,
+
+    /// The maximum number of measurements to buffer in memory. Writing a vehicle status fails
+    /// if its measurements do not fit into the buffer anymore.
+    #[arg(long = PARAM_INFLUXDB_MAX_QUEUE_SIZE, value_name = "NUMBER", env = "INFLUXDB_MAX_QUEUE_SIZE", default_value = "50000")]
+    max_queue_size: usize,
+
+    /// The number of times to retry writing a batch of measurements that has failed.
+    #[arg(long = PARAM_INFLUXDB_MAX_RETRIES, value_name = "NUMBER", env = "INFLUXDB_MAX_RETRIES", default_value = "3")]
+    max_retries: u32,
+
+    /// The time to wait before retrying to write a batch of measurements for the first time, e.g. 500ms.
+    /// The time is doubled for each subsequent retry, up to 30s.
+    #[arg(long = PARAM_INFLUXDB_RETRY_BACKOFF, value_name = "DURATION_SPEC", env = "INFLUXDB_RETRY_BACKOFF", default_value = "500ms", value_parser = |s: &str| duration_str::parse(s) )]
+    retry_backoff: Duration,
+
+    /// The directory to spill batches of measurements to (in line protocol) that could not be written to the
+    /// InfluxDB server. The spilled measurements are replayed once the server can be reached again.
+    /// Batches that could not be written are kept in memory if not set.
+    #[arg(long = PARAM_INFLUXDB_SPILL_DIR, value_name = "DIR", env = "INFLUXDB_SPILL_DIR")]
+    spill_dir: Option<PathBuf>,
+}
+
+/// Information about the measurements that have not been written to the InfluxDB server yet.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BatchWriterMetrics {
+    /// The number of measurements buffered in memory, including the ones currently being written.
+    pub queue_depth: usize,
+    /// The number of measurements that have been spilled to disk and are waiting to be replayed.
+    pub spilled: usize,
+}
+
+/// A local file containing measurements in line protocol.
+struct SpillFile {
+    path: PathBuf,
+}
+
+impl SpillFile {
+    fn append(&self, lines: &[String]) -> std::io::Result<()> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let mut writer = BufWriter::new(file);
+        for line in lines {
+            writeln!(writer, "{line}")?;
+        }
+        writer.flush()
+    }
+
+    /// Reads the (non-empty) lines of the file one by one.
+    ///
+    /// A file that does not exist has no lines.
+    fn lines(&self) -> std::io::Result<impl Iterator<Item = std::io::Result<String>>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => Some(file),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+        Ok(file
+            .map(|file| BufReader::new(file).lines())
+            .into_iter()
+            .flatten()
+            .filter(|line| line.as_ref().map_or(true, |line| !line.is_empty())))
+    }
+
+    fn count(&self) -> std::io::Result<usize> {
+        self.lines()?
+            .try_fold(0, |count, line| line.map(|_| count + 1))
+    }
+
+    /// Replaces the content of the file with the given lines.
+    ///
+    /// Returns the number of lines that the file contains afterwards.
+    fn replace(
+        &self,
+        lines: impl IntoIterator<Item = std::io::Result<String>>,
+    ) -> std::io::Result<usize> {
+        let tmp_path = self.path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        let mut count = 0;
+        let written = lines.into_iter().try_for_each(|line| {
+            count += 1;
+            writeln!(writer, "{}", line?)
+        });
+        if let Err(e) = written.and_then(|_| writer.flush()) {
+            let _ = fs::remove_file(&tmp_path);
+            return Err(e);
+        }
+        drop(writer);
+        if count == 0 {
+            fs::remove_file(&tmp_path)?;
+            return match fs::remove_file(&self.path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+                _ => Ok(0),
+            };
+        }
+        fs::rename(&tmp_path, &self.path).map(|_| count)
+    }
+}
+
+/// Takes up to `max_lines` lines from the given lines.
+fn next_chunk(
+    lines: &mut impl Iterator<Item = std::io::Result<String>>,
+    max_lines: usize,
+) -> std::io::Result<Vec<String>> {
+    lines.take(max_lines).collect()
+}
+
+/// The measurements that have not been written to the InfluxDB server yet.
+#[derive(Default)]
+struct Queue {
+    lines: Mutex<VecDeque<String>>,
+    in_flight: AtomicUsize,
+    spilled: AtomicUsize,
+    batch_ready: Notify,
+}
+
+impl Queue {
+    fn push(
+        &self,
+        lines: Vec<String>,
+        batch_size: usize,
+        max_queue_size: usize,
+    ) -> Result<(), InfluxError> {
+        let Ok(mut queued) = self.lines.lock() else {
+            return Err(
+                std::io::Error::other("queue of measurements to write is unavailable").into(),
+            );
+        };
+        if queued.len() + self.in_flight.load(Ordering::Relaxed) + lines.len() > max_queue_size {
+            return Err(std::io::Error::other("queue of measurements to write is full").into());
+        }
+        queued.extend(lines);
+        if queued.len() >= batch_size {
+            self.batch_ready.notify_one();
+        }
+        Ok(())
+    }
+
+    /// Takes the next batch of measurements from the queue.
+    ///
+    /// The measurements are considered to be in flight until they have been completed or requeued.
+    fn take_batch(&self, batch_size: usize) -> Vec<String> {
+        self.lines.lock().map_or(Vec::new(), |mut queued| {
+            let count = batch_size.min(queued.len());
+            self.in_flight.fetch_add(count, Ordering::Relaxed);
+            queued.drain(..count).collect()
+        })
+    }
+
+    fn complete(&self, batch: &[String]) {
+        self.in_flight.fetch_sub(batch.len(), Ordering::Relaxed);
+    }
+
+    /// Puts a batch of measurements back at the head of the queue.
+    ///
+    /// The measurements are discarded if the queue is unavailable.
+    fn requeue(&self, batch: Vec<String>) {
+        self.in_flight.fetch_sub(batch.len(), Ordering::Relaxed);
+        match self.lines.lock() {
+            Ok(mut queued) => {
+                for line in batch.into_iter().rev() {
+                    queued.push_front(line);
+                }
+            }
+            Err(_) => warn!(
+                "discarding {} measurements, queue of measurements to write is unavailable",
+                batch.len()
+            ),
+        }
+    }
+
+    fn metrics(&self) -> BatchWriterMetrics {
+        let queued = self.lines.lock().map_or(0, |queued| queued.len());
+        BatchWriterMetrics {
+            queue_depth: queued + self.in_flight.load(Ordering::Relaxed),
+            spilled: self.spilled.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Checks if writing measurements that have failed with the given error may succeed when being retried.
+///
+/// Malformed measurements and measurements that are rejected by the server because of e.g. the bucket's
+/// retention policy are never going to be accepted.
+fn is_retryable(error: &InfluxError) -> bool {
+    match error {
+        InfluxError::NonSuccessResponse(status, _) => !matches!(status.as_u16(), 400 | 413 | 422),
+        _ => true,
+    }
+}
+
+/// Writes the measurements from the queue to the InfluxDB server.
+struct Flusher {
+    connection: Arc<InfluxConnection>,
+    queue: Arc<Queue>,
+    batch_size: usize,
+    max_retries: u32,
+    retry_backoff: Duration,
+    spill_file: Option<SpillFile>,
+}
+
+impl Flusher {
+    /// Writes a batch of measurements, retrying failed attempts with exponential backoff.
+    async fn write_batch(&self, batch: &[String], max_attempts: u32) -> Result<(), InfluxError> {
+        let lines = batch.join("\n");
+        let mut backoff = self.retry_backoff;
+        let mut attempt = 1;
+        loop {
+            match self.connection.write_lines(lines.clone()).await {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt < max_attempts && is_retryable(&e) => {
+                    debug!(
+                        "failed to write {} measurements to influx [attempt {attempt}/{max_attempts}], retrying in {backoff:?}: {e}",
+                        batch.len()
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_RETRY_BACKOFF);
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Writes all queued measurements to the InfluxDB server.
+    ///
+    /// When stopping, each batch is tried to be written only once and the batches that cannot be written
+    /// are spilled to disk (if configured) or are discarded.
+    async fn flush(&self, stopping: bool) {
+        let mut discarded = 0;
+        loop {
+            let batch = self.queue.take_batch(self.batch_size);
+            if batch.is_empty() {
+                break;
+            }
+            // do not wait for an unreachable server if there are measurements that have already been spilled
+            let max_attempts = if stopping || self.queue.spilled.load(Ordering::Relaxed) > 0 {
+                1
+            } else {
+                self.max_retries + 1
+            };
+            match self.write_batch(&batch, max_attempts).await {
+                Ok(()) => {
+                    debug!("wrote {} measurements to influx", batch.len());
+                    self.queue.complete(&batch);
+                }
+                Err(e) if !is_retryable(&e) => {
+                    warn!(
+                        "discarding {} measurements rejected by influx: {e}",
+                        batch.len()
+                    );
+                    self.queue.complete(&batch);
+                }
+                Err(e) => {
+                    warn!(
+                        "failed to write {} measurements to influx: {e}",
+                        batch.len()
+                    );
+                    if self.spill(&batch) {
+                        self.queue.complete(&batch);
+                    } else if stopping {
+                        warn!("discarding {} measurements", batch.len());
+                        discarded += batch.len();
+                        self.queue.complete(&batch);
+                    } else {
+                        // try again when the next flush is due
+                        self.queue.requeue(batch);
+                        break;
+                    }
+                }
+            }
+        }
+        if stopping {
+            if discarded > 0 {
+                warn!(
+                    "discarded {discarded} measurements that could neither be written to influx nor spilled while shutting down"
+                );
+            }
+        } else {
+            self.replay_spilled().await;
+        }
+    }
+
+    /// Appends a batch of measurements to the spill file.
+    ///
+    /// Returns `true` if the measurements have been spilled.
+    fn spill(&self, batch: &[String]) -> bool {
+        let Some(spill_file) = self.spill_file.as_ref() else {
+            return false;
+        };
+        match spill_file.append(batch) {
+            Ok(()) => {
+                let spilled =
+                    self.queue.spilled.fetch_add(batch.len(), Ordering::Relaxed) + batch.len();
+                info!(
+                    "spilled {} measurements to file {} [total: {spilled}]",
+                    batch.len(),
+                    spill_file.path.display()
+                );
+                true
+            }
+            Err(e) => {
+                warn!(
+                    "failed to spill measurements to file {}: {e}",
+                    spill_file.path.display()
+                );
+                false
+            }
+        }
+    }
+
+    /// Writes the measurements from the spill file to the InfluxDB server.
+    ///
+    /// The file is read in chunks of the batch size, so that a large file does not need to be
+    /// kept in memory. The measurements that could not be written are kept in the file.
+    async fn replay_spilled(&self) {
+        let Some(spill_file) = self.spill_file.as_ref() else {
+            return;
+        };
+        if self.queue.spilled.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        let mut lines = match spill_file.lines() {
+            Ok(lines) => lines,
+            Err(e) => {
+                warn!(
+                    "failed to read spilled measurements from file {}: {e}",
+                    spill_file.path.display()
+                );
+                return;
+            }
+        };
+        let mut processed = 0;
+        let mut unprocessed = Vec::new();
+        loop {
+            let chunk = match next_chunk(&mut lines, self.batch_size) {
+                Ok(chunk) if chunk.is_empty() => break,
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    warn!(
+                        "failed to read spilled measurements from file {}: {e}",
+                        spill_file.path.display()
+                    );
+                    return;
+                }
+            };
+            match self.write_batch(&chunk, 1).await {
+                Ok(()) => {}
+                Err(e) if !is_retryable(&e) => {
+                    warn!(
+                        "discarding {} spilled measurements rejected by influx: {e}",
+                        chunk.len()
+                    );
+                }
+                Err(e) => {
+                    debug!("failed to replay spilled measurements: {e}");
+                    unprocessed = chunk;
+                    break;
+                }
+            }
+            processed += chunk.len();
+        }
+        if processed == 0 {
+            return;
+        }
+        let remaining = match spill_file.replace(unprocessed.into_iter().map(Ok).chain(lines)) {
+            Ok(remaining) => remaining,
+            Err(e) => {
+                // the measurements will be replayed again, which is harmless because
+                // InfluxDB overwrites points having the same series and timestamp
+                warn!(
+                    "failed to update spill file {}: {e}",
+                    spill_file.path.display()
+                );
+                return;
+            }
+        };
+        self.queue.spilled.store(remaining, Ordering::Relaxed);
+        info!("replayed {processed} spilled measurements [remaining: {remaining}]");
+    }
+
+    async fn run(self, flush_interval: Duration, shutdown: Arc<Notify>) {
+        let mut interval = tokio::time::interval(flush_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            let stopping = tokio::select! {
+                _ = shutdown.notified() => true,
+                _ = interval.tick() => false,
+                _ = self.queue.batch_ready.notified() => false,
+            };
+            self.flush(stopping).await;
+            if stopping {
+                break;
+            }
+        }
+    }
+}
+
+/// Buffers measurements and writes them to an InfluxDB server in batches by means of a background task.
+pub(crate) struct BatchWriter {
+    queue: Arc<Queue>,
+    batch_size: usize,
+    max_queue_size: usize,
+    shutdown: Arc<Notify>,
+    flush_task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl BatchWriter {
+    /// Creates a new writer for the bucket of a connection.
+    ///
+    /// Measurements that have been spilled to disk by a previous instance are replayed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if not called from within a Tokio runtime.
+    pub(crate) fn new(
+        connection: Arc<InfluxConnection>,
+        config: &BatchWriterConfig,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let queue = Arc::new(Queue::default());
+        let spill_file = match config.spill_dir.as_ref() {
+            Some(dir) => {
+                fs::create_dir_all(dir)?;
+                let spill_file = SpillFile {
+                    path: dir.join(format!("{}.lp", connection.bucket)),
+                };
+                let spilled = spill_file.count()?;
+                if spilled > 0 {
+                    info!(
+                        "found {spilled} spilled measurements in file {}",
+                        spill_file.path.display()
+                    );
+                }
+                queue.spilled.store(spilled, Ordering::Relaxed);
+                Some(spill_file)
+            }
+            None => None,
+        };
+        let batch_size = config.batch_size as usize;
+        let shutdown = Arc::new(Notify::new());
+        let flusher = Flusher {
+            connection,
+            queue: queue.clone(),
+            batch_size,
+            max_retries: config.max_retries,
+            retry_backoff: config.retry_backoff,
+            spill_file,
+        };
+        let flush_task = tokio::spawn(flusher.run(config.flush_interval, shutdown.clone()));
+        Ok(BatchWriter {
+            queue,
+            batch_size,
+            max_queue_size: config.max_queue_size,
+            shutdown,
+            flush_task: Mutex::new(Some(flush_task)),
+        })
+    }
+
+    /// Adds measurements (in line protocol) to the queue of measurements to write.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the queue is full.
+    pub(crate) fn enqueue(&self, lines: Vec<String>) -> Result<(), InfluxError> {
+        self.queue
+            .push(lines, self.batch_size, self.max_queue_size)
+            .inspect_err(|e| warn!("failed to buffer measurements: {e}"))
+    }
+
+    pub(crate) fn metrics(&self) -> BatchWriterMetrics {
+        self.queue.metrics()
+    }
+
+    /// Writes the queued measurements and stops the background task.
+    ///
+    /// Measurements that cannot be written are spilled to disk, if configured.
+    pub(crate) async fn close(&self) {
+        let flush_task = self
+            .flush_task
+            .lock()
+            .ok()
+            .and_then(|mut flush_task| flush_task.take());
+        if let Some(flush_task) = flush_task {
+            self.shutdown.notify_one();
+            if let Err(e) = flush_task.await {
+                warn!("failed to flush measurements: {e}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(range: std::ops::Range<usize>) -> Vec<String> {
+        range
+            .map(|i| format!("snapshot,vin=VIN{i} speed=1 {i}"))
+            .collect()
+    }
+
+    #[test]
+    fn test_queue_keeps_order_of_requeued_batches() {
+        let queue = Queue::default();
+        queue.push(lines(0..5), 2, 6).unwrap();
+        assert!(queue.push(lines(5..7), 2, 6).is_err());
+
+        let batch = queue.take_batch(2);
+        assert_eq!(batch, lines(0..2));
+        assert_eq!(queue.metrics().queue_depth, 5);
+        queue.requeue(batch);
+        assert_eq!(queue.take_batch(10), lines(0..5));
+        assert_eq!(queue.metrics().queue_depth, 5);
+        queue.complete(&lines(0..5));
+        assert_eq!(queue.metrics(), BatchWriterMetrics::default());
+    }
+
+    #[test]
+    fn test_spill_file_can_be_replayed_partially() {
+        let dir = std::env::temp_dir().join(format!("influx-client-spill-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let spill_file = SpillFile {
+            path: dir.join("demo.lp"),
+        };
+        let read = |spill_file: &SpillFile| {
+            spill_file
+                .lines()
+                .unwrap()
+                .collect::<std::io::Result<Vec<_>>>()
+                .unwrap()
+        };
+        assert!(read(&spill_file).is_empty());
+        assert_eq!(spill_file.count().unwrap(), 0);
+
+        spill_file.append(&lines(0..3)).unwrap();
+        spill_file.append(&lines(3..5)).unwrap();
+        assert_eq!(read(&spill_file), lines(0..5));
+        assert_eq!(spill_file.count().unwrap(), 5);
+
+        // replace the file's content with the lines following the first chunk, while reading it
+        let mut spilled = spill_file.lines().unwrap();
+        assert_eq!(next_chunk(&mut spilled, 3).unwrap(), lines(0..3));
+        assert_eq!(spill_file.replace(spilled).unwrap(), 2);
+        assert_eq!(read(&spill_file), lines(3..5));
+        assert_eq!(spill_file.replace(std::iter::empty()).unwrap(), 0);
+        assert!(!spill_file.path.exists());
+        assert!(!spill_file.path.with_extension("tmp").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
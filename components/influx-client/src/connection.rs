@@ -18,9 +18,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::path::PathBuf;
+use std::time::Duration;
 
 use clap::Args;
-use influxrs::InfluxClient;
+use influxrs::{InfluxClient, InfluxError};
+use isahc::{config::Configurable, AsyncReadResponseExt, HttpClient};
 use log::{error, info};
 
 const PARAM_INFLUXDB_BUCKET: &str = "influxdb-bucket";
@@ -29,7 +31,10 @@ const PARAM_INFLUXDB_URI: &str = "influxdb-uri";
 const PARAM_INFLUXDB_TOKEN: &str = "influxdb-token";
 const PARAM_INFLUXDB_TOKEN_FILE: &str = "influxdb-token-file";
 
-#[derive(Args, Debug)]
+// the maximum time to wait for the InfluxDB server to respond to a write request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Args, Clone, Debug)]
 #[group(required = true, multiple = false)]
 struct Token {
     /// The API token to use for authenticating to the InfluxDB server.
@@ -41,7 +46,7 @@ struct Token {
     token_file: Option<PathBuf>,
 }
 
-#[derive(Args, Debug)]
+#[derive(Args, Clone, Debug)]
 pub struct InfluxConnectionConfig {
     /// The HTTP(S) URI of the InfluxDB server.
     #[arg(
@@ -80,6 +85,14 @@ pub struct InfluxConnectionConfig {
 }
 
 impl InfluxConnectionConfig {
+    /// Creates a copy of this configuration that uses another bucket.
+    pub fn with_bucket(&self, bucket: &str) -> Self {
+        InfluxConnectionConfig {
+            bucket: bucket.to_string(),
+            ..self.clone()
+        }
+    }
+
     fn token(&self) -> Result<String, Box<dyn std::error::Error>> {
         if let Some(token) = self.token.token.as_ref() {
             Ok(token.to_owned())
@@ -101,6 +114,9 @@ impl InfluxConnectionConfig {
 pub struct InfluxConnection {
     pub client: InfluxClient,
     pub bucket: String,
+    http_client: HttpClient,
+    write_uri: String,
+    token: String,
 }
 
 impl InfluxConnection {
@@ -131,17 +147,46 @@ impl InfluxConnection {
     pub fn new(
         connection_params: &InfluxConnectionConfig,
     ) -> Result<Self, Box<dyn std::error::Error>> {
+        let token = connection_params.token()?;
         let client = InfluxClient::builder(
             connection_params.uri.to_owned(),
-            connection_params.token()?,
+            token.to_owned(),
             connection_params.org.to_owned(),
         )
         .build()?;
         Ok(InfluxConnection {
             client,
             bucket: connection_params.bucket.to_owned(),
+            http_client: HttpClient::builder().timeout(REQUEST_TIMEOUT).build()?,
+            write_uri: format!(
+                "{}/api/v2/write?org={}&bucket={}&precision=ns",
+                connection_params.uri, connection_params.org, connection_params.bucket
+            ),
+            token,
         })
     }
+
+    /// Writes data points in InfluxDB line protocol to the connection's bucket.
+    ///
+    /// The data points are expected to be separated by newlines and
+    /// to have timestamps with nanosecond precision.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data points could not be written to the InfluxDB server.
+    pub async fn write_lines(&self, lines: String) -> Result<(), InfluxError> {
+        let request = isahc::Request::builder()
+            .uri(self.write_uri.as_str())
+            .method("POST")
+            .header("Authorization", format!("Token {}", self.token))
+            .body(lines)?;
+        let mut response = self.http_client.send_async(request).await?;
+        if !response.status().is_success() {
+            let body = response.text().await?;
+            return Err(InfluxError::NonSuccessResponse(response.status(), body));
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
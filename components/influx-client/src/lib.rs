@@ -25,7 +25,10 @@
 pub const FIELD_ALTITUDE: &str = "altitude";
 pub const FIELD_AMBIENT_AIR_TEMP: &str = "ambientAirTemperature";
 pub const FIELD_CATALYST_FUEL_LEVEL: &str = "catalystFuelLevel";
+pub const FIELD_CONNECTED: &str = "connected";
+pub const FIELD_CONNECTION_ADAPTER: &str = "adapter";
 pub const FIELD_CREATED_DATE_TIME: &str = "createdDateTime";
+pub const FIELD_DEVICE_ID: &str = "deviceId";
 pub const FIELD_DRIVER1_ID: &str = "driver1Id";
 pub const FIELD_DRIVER1_CARD_ISSUER: &str = "driver1IdCardIssuer";
 pub const FIELD_DRIVER1_WORKING_STATE: &str = "driver1WorkingState";
@@ -50,14 +53,18 @@ pub const FIELD_SPEED: &str = "speed";
 pub const FIELD_TACHOGRAPH_SPEED: &str = "tachographSpeed";
 pub const FIELD_TOTAL_ELECTRIC_MOTOR_HOURS: &str = "totalElectricMotorHours";
 pub const FIELD_TOTAL_ENGINE_HOURS: &str = "totalEngineHours";
+pub const FIELD_TRIGGER_EVENT_DATE_TIME: &str = "triggerEventDateTime";
 pub const FIELD_WHEEL_BASED_SPEED: &str = "wheelBasedSpeed";
 
+pub const MEASUREMENT_CONNECTIVITY: &str = "connectivity";
 pub const MEASUREMENT_HEADER: &str = "header";
 pub const MEASUREMENT_SNAPSHOT: &str = "snapshot";
 
 pub const TAG_TRIGGER: &str = "trigger";
 pub const TAG_VIN: &str = "vin";
 
+#[cfg(feature = "writer")]
+pub mod batch;
 pub mod connection;
 #[cfg(feature = "writer")]
 pub mod writer;
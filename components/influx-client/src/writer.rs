@@ -19,14 +19,26 @@
 
 //! Provides means to write a Vehicle's current status properties
 //! to an InfluxDB as Influx *measurements*.
-use fms_proto::fms::VehicleStatus;
-use influxrs::Measurement;
+use fms_proto::fms::{ConnectionStatus, VehicleStatus};
+use influxrs::{InfluxError, Measurement};
 use log::{debug, warn};
 use protobuf::well_known_types::timestamp::Timestamp;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::Arc;
 
+use crate::batch::{BatchWriter, BatchWriterConfig, BatchWriterMetrics};
 use crate::connection::{InfluxConnection, InfluxConnectionConfig};
 
+/// Gets the number of milliseconds since the UNIX epoch represented by a timestamp.
+///
+/// Returns `None` for timestamps before the epoch and for invalid timestamps.
+fn as_millis_since_epoch(timestamp: &Timestamp) -> Option<u128> {
+    let seconds = u128::try_from(timestamp.seconds).ok()?;
+    let nanos = u128::try_from(timestamp.nanos)
+        .ok()
+        .filter(|nanos| *nanos < 1_000_000_000)?;
+    Some(seconds * 1000 + nanos / 1_000_000)
+}
+
 fn build_header_measurement(
     vin: &str,
     trigger: &str,
@@ -38,6 +50,18 @@ fn build_header_measurement(
         .tag(crate::TAG_VIN, vin)
         .field(crate::FIELD_CREATED_DATE_TIME, created_date_time);
 
+    if let Some(event_time) = vehicle_status
+        .trigger
+        .as_ref()
+        .and_then(|trigger| trigger.event_time.as_ref())
+    {
+        match as_millis_since_epoch(event_time) {
+            Some(event_date_time) => {
+                builder = builder.field(crate::FIELD_TRIGGER_EVENT_DATE_TIME, event_date_time);
+            }
+            None => debug!("ignoring invalid trigger event time"),
+        }
+    }
     if let Some(value) = vehicle_status.hr_total_vehicle_distance {
         builder = builder.field(crate::FIELD_HR_TOTAL_VEHICLE_DISTANCE, value);
     }
@@ -183,9 +207,34 @@ fn build_snapshot_measurement(
     }
 }
 
+fn build_connectivity_measurement(
+    vin: &str,
+    created_date_time: u128,
+    connection_status: &ConnectionStatus,
+) -> Option<Measurement> {
+    let mut builder = Measurement::builder(crate::MEASUREMENT_CONNECTIVITY)
+        .tag(crate::TAG_VIN, vin)
+        .field(crate::FIELD_CONNECTED, connection_status.connected)
+        .field(crate::FIELD_CREATED_DATE_TIME, created_date_time)
+        .field(crate::FIELD_DEVICE_ID, connection_status.device_id.as_str())
+        .timestamp_ms(created_date_time as i128);
+    if let Some(adapter) = connection_status.adapter.as_ref() {
+        builder = builder.field(crate::FIELD_CONNECTION_ADAPTER, adapter.as_str());
+    }
+
+    match builder.build() {
+        Ok(measurement) => Some(measurement),
+        Err(e) => {
+            debug!("failed to create connectivity Measurement: {e}");
+            None
+        }
+    }
+}
+
 /// A facade to an InfluxDB server for publishing Vehicle status information.
 pub struct InfluxWriter {
-    influx_con: InfluxConnection,
+    influx_con: Arc<InfluxConnection>,
+    batch_writer: Option<BatchWriter>,
 }
 
 impl InfluxWriter {
@@ -194,7 +243,67 @@ impl InfluxWriter {
     /// Determines the parameters necessary for creating the writer from values specified on
     /// the command line or via environment variables as defined by [`super::add_command_line_args`].
     pub fn new(args: &InfluxConnectionConfig) -> Result<Self, Box<dyn std::error::Error>> {
-        InfluxConnection::new(args).map(|con| InfluxWriter { influx_con: con })
+        InfluxConnection::new(args).map(|con| InfluxWriter {
+            influx_con: Arc::new(con),
+            batch_writer: None,
+        })
+    }
+
+    /// Creates a new writer that buffers measurements and writes them to the InfluxDB server in batches.
+    ///
+    /// The measurements are written by means of a background task until [`Self::close`] is invoked.
+    ///
+    /// # Panics
+    ///
+    /// Panics if not called from within a Tokio runtime.
+    pub fn new_batched(
+        args: &InfluxConnectionConfig,
+        batch_config: &BatchWriterConfig,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let influx_con = InfluxConnection::new(args).map(Arc::new)?;
+        let batch_writer = BatchWriter::new(influx_con.clone(), batch_config)?;
+        Ok(InfluxWriter {
+            influx_con,
+            batch_writer: Some(batch_writer),
+        })
+    }
+
+    /// Gets information about the measurements that have not been written to the InfluxDB server yet.
+    ///
+    /// Returns `None` if the writer does not buffer measurements.
+    pub fn metrics(&self) -> Option<BatchWriterMetrics> {
+        self.batch_writer.as_ref().map(BatchWriter::metrics)
+    }
+
+    /// Writes the measurements that are currently buffered to the InfluxDB server
+    /// and stops writing measurements in the background.
+    ///
+    /// Does nothing if the writer does not buffer measurements.
+    pub async fn close(&self) {
+        if let Some(batch_writer) = self.batch_writer.as_ref() {
+            batch_writer.close().await;
+        }
+    }
+
+    async fn write_measurements(&self, measurements: &[Measurement]) -> Result<(), InfluxError> {
+        if let Some(batch_writer) = self.batch_writer.as_ref() {
+            return batch_writer.enqueue(
+                measurements
+                    .iter()
+                    .map(Measurement::to_line_protocol)
+                    .collect(),
+            );
+        }
+        if let Err(e) = self
+            .influx_con
+            .client
+            .write(self.influx_con.bucket.as_str(), measurements)
+            .await
+        {
+            warn!("failed to write data to influx: {e}");
+            return Err(e);
+        }
+        Ok(())
     }
 
     /// Writes Vehicle status information as measurements to the InfluxDB server.
@@ -211,6 +320,7 @@ impl InfluxWriter {
     ///   | tag   | trigger         | The type of event that triggered the reporting of the vehicle status. |
     ///   | tag   | vin             | The vehicle's identification number. |
     ///   | field | createdDateTime | The instant of time (milliseconds since UNIX epoch) at which the vehicle status information had been created. |
+    ///   | field | triggerEventDateTime | The instant of time (milliseconds since UNIX epoch) at which the event that triggered the reporting of the vehicle status has occurred. |
     ///   | field | hrTotalVehicleDistance | The accumulated distance travelled by the vehicle during its operation in meter. |
     ///   | field | grossCombinationVehicleWeight | The full vehicle weight in kg. |
     ///   | field | totalEngineHours | The total hours of operation for the vehicle combustion engine. |
@@ -247,19 +357,33 @@ impl InfluxWriter {
     ///   | field | driver2WorkingState | Tachograph Working state of the driver two. |
     ///   | field | ambientAirTemperature | The Ambient air temperature in Celsius. |
     ///   | field | parkingBrakeSwitch | Switch signal which indicates when the parking brake is set. |
-    pub async fn write_vehicle_status(&self, vehicle_status: &VehicleStatus) {
+    ///
+    /// Vehicle status information that lacks any of the VIN, the created timestamp or an rFMS
+    /// trigger is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the measurements could not be written to the InfluxDB server.
+    /// A writer that buffers measurements returns an error only if its buffer is full.
+    pub async fn write_vehicle_status(
+        &self,
+        vehicle_status: &VehicleStatus,
+    ) -> Result<(), InfluxError> {
         if vehicle_status.vin.is_empty() {
             debug!("ignoring vehicle status without VIN ...");
-            return;
+            return Ok(());
         }
-        let created_timestamp: u128 = match vehicle_status.created.clone().into_option() {
-            Some(ts) => <Timestamp as Into<SystemTime>>::into(ts)
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_millis(),
+        let created_timestamp: u128 = match vehicle_status.created.as_ref() {
+            Some(ts) => match as_millis_since_epoch(ts) {
+                Some(created_timestamp) => created_timestamp,
+                None => {
+                    debug!("ignoring vehicle status with invalid created timestamp");
+                    return Ok(());
+                }
+            },
             None => {
                 debug!("ignoring vehicle status without created timestamp");
-                return;
+                return Ok(());
             }
         };
         let trigger = match vehicle_status.trigger.clone().into_option() {
@@ -270,12 +394,12 @@ impl InfluxWriter {
                         "ignoring vehicle status with unsupported trigger context [{}]",
                         t.context
                     );
-                    return;
+                    return Ok(());
                 }
             },
             None => {
                 debug!("ignoring vehicle status without trigger");
-                return;
+                return Ok(());
             }
         };
 
@@ -300,17 +424,70 @@ impl InfluxWriter {
         }
 
         if !measurements.is_empty() {
-            if let Err(e) = self
-                .influx_con
-                .client
-                .write(
-                    self.influx_con.bucket.as_str(),
-                    measurements[..].try_into().unwrap(),
-                )
-                .await
-            {
-                warn!("failed to write data to influx: {e}");
-            }
+            self.write_measurements(&measurements).await?;
         }
+        Ok(())
+    }
+
+    /// Writes a change of a vehicle's connection status as a measurement to the InfluxDB server.
+    ///
+    /// This function writes the connection status by means of a *connectivity* measurement
+    /// which contains the following tags/fields:
+    ///
+    /// | Type  | Name            | Description                      |
+    /// | ----- | --------------- | -------------------------------- |
+    /// | tag   | vin             | The vehicle's identification number. |
+    /// | field | connected       | Indicates whether the vehicle is connected after the change. |
+    /// | field | createdDateTime | The instant of time (milliseconds since UNIX epoch) at which the connection status has changed. |
+    /// | field | deviceId        | The identifier of the device that has connected or disconnected. |
+    /// | field | adapter         | The type of protocol adapter that the device has connected to or disconnected from. |
+    ///
+    /// The measurement's timestamp is set to the instant of time at which the connection status has changed.
+    /// A connection status that lacks a timestamp is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the measurement could not be written to the InfluxDB server.
+    /// A writer that buffers measurements returns an error only if its buffer is full.
+    pub async fn write_connection_status(
+        &self,
+        vin: &str,
+        connection_status: &ConnectionStatus,
+    ) -> Result<(), InfluxError> {
+        let Some(time) = connection_status.time.as_ref() else {
+            debug!("ignoring connection status without timestamp");
+            return Ok(());
+        };
+        let Some(time) = as_millis_since_epoch(time) else {
+            debug!("ignoring connection status with invalid timestamp");
+            return Ok(());
+        };
+        let Some(measurement) = build_connectivity_measurement(vin, time, connection_status) else {
+            return Ok(());
+        };
+        debug!("writing connectivity measurement to influxdb");
+        self.write_measurements(&[measurement]).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_as_millis_since_epoch_rejects_invalid_timestamps() {
+        let timestamp = |seconds, nanos| Timestamp {
+            seconds,
+            nanos,
+            ..Default::default()
+        };
+        assert_eq!(
+            as_millis_since_epoch(&timestamp(1_700_000_000, 123_456_789)),
+            Some(1_700_000_000_123)
+        );
+        assert_eq!(as_millis_since_epoch(&timestamp(0, 0)), Some(0));
+        assert!(as_millis_since_epoch(&timestamp(-1, 0)).is_none());
+        assert!(as_millis_since_epoch(&timestamp(10, -1)).is_none());
+        assert!(as_millis_since_epoch(&timestamp(10, 1_000_000_000)).is_none());
     }
 }
@@ -0,0 +1,543 @@
+// SPDX-FileCopyrightText: 2023 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Options for creating MQTT clients and keeping them connected to an MQTT endpoint.
+//!
+//! The options are shared by the transports that connect to Hono's MQTT adapter
+//! and to arbitrary MQTT 5 brokers, so that both support the same TLS, authentication,
+//! session and buffering options.
+
+use clap::{Args, ValueEnum};
+use log::{debug, error, info, warn};
+use mqtt::{
+    AsyncClient, ConnectOptions, ConnectOptionsBuilder, CreateOptionsBuilder, PersistenceType,
+    Properties, PropertyCode, SslOptions, SslOptionsBuilder,
+};
+use paho_mqtt as mqtt;
+use rand::Rng;
+use std::{fmt::Display, path::PathBuf, sync::Arc, time::Duration};
+use tokio::sync::{watch, Notify};
+
+const PARAM_CA_PATH: &str = "ca-path";
+const PARAM_DEVICE_CERT: &str = "device-cert";
+const PARAM_DEVICE_KEY: &str = "device-key";
+const PARAM_ENABLE_HOSTNAME_VERIFICATION: &str = "enable-hostname-verification";
+const PARAM_MQTT_CLIENT_ID: &str = "mqtt-client-id";
+const PARAM_MQTT_MAX_BUFFERED_MESSAGES: &str = "mqtt-max-buffered-messages";
+const PARAM_MQTT_MESSAGE_EXPIRY: &str = "mqtt-message-expiry";
+const PARAM_MQTT_URI: &str = "mqtt-uri";
+const PARAM_MQTT_USERNAME: &str = "mqtt-username";
+const PARAM_MQTT_PASSWORD: &str = "mqtt-password";
+const PARAM_MQTT_PERSISTENCE_DIR: &str = "mqtt-persistence-dir";
+const PARAM_MQTT_PERSISTENT_SESSION: &str = "mqtt-persistent-session";
+const PARAM_MQTT_SESSION_EXPIRY: &str = "mqtt-session-expiry";
+const PARAM_TRUST_STORE_PATH: &str = "trust-store-path";
+
+const MIN_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(16);
+
+/// The state of the connection to the MQTT endpoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// The client is establishing the initial connection.
+    Connecting,
+    /// The client is connected to the MQTT endpoint.
+    Connected,
+    /// The connection has been lost and the client is trying to re-establish it.
+    Reconnecting,
+    /// The last attempt to (re-)connect has failed. The client will try again after a while.
+    Failed,
+    /// The client has been disconnected on purpose and does not try to reconnect.
+    Disconnected,
+}
+
+/// Gets the time to wait before the next attempt to connect.
+///
+/// The delay grows exponentially with the number of failed attempts. A random jitter is applied
+/// in order to prevent a large number of clients from reconnecting at the same time, e.g. after
+/// the MQTT endpoint has been restarted.
+fn reconnect_delay(failed_attempts: u32) -> Duration {
+    let exponent = failed_attempts.saturating_sub(1).min(16);
+    let delay = MIN_RECONNECT_DELAY
+        .saturating_mul(1 << exponent)
+        .min(MAX_RECONNECT_DELAY);
+    delay.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+}
+
+/// Keeps the client connected to the MQTT endpoint until the connection state is set to
+/// [`ConnectionState::Disconnected`].
+async fn maintain_connection(
+    client: AsyncClient,
+    connect_options: ConnectOptions,
+    state: watch::Sender<ConnectionState>,
+    connection_lost: Arc<Notify>,
+) {
+    let mut state_changes = state.subscribe();
+    let mut failed_attempts = 0;
+    let update_state = |new_state: ConnectionState| {
+        // the state must not be changed anymore once the client has been disconnected
+        state.send_if_modified(|current_state| {
+            if *current_state == ConnectionState::Disconnected || *current_state == new_state {
+                false
+            } else {
+                *current_state = new_state;
+                true
+            }
+        });
+    };
+    while *state.borrow() != ConnectionState::Disconnected {
+        match client.connect(connect_options.clone()).await {
+            Ok(_response) => {
+                info!(
+                    "successfully connected to MQTT endpoint [uri: {}]",
+                    client.server_uri()
+                );
+                failed_attempts = 0;
+                update_state(ConnectionState::Connected);
+                tokio::select! {
+                    _ = connection_lost.notified() => {
+                        warn!("lost connection to MQTT endpoint, reconnecting ...");
+                        update_state(ConnectionState::Reconnecting);
+                    }
+                    _ = state_changes.wait_for(|s| *s == ConnectionState::Disconnected) => {}
+                }
+            }
+            Err(e) => {
+                failed_attempts += 1;
+                let delay = reconnect_delay(failed_attempts);
+                warn!(
+                    "attempt to connect to MQTT endpoint failed, retrying in {:?}: {}",
+                    delay, e
+                );
+                update_state(ConnectionState::Failed);
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = state_changes.wait_for(|s| *s == ConnectionState::Disconnected) => {}
+                }
+            }
+        }
+    }
+    debug!("stopped maintaining connection to MQTT endpoint");
+}
+
+/// The version of the MQTT protocol to use for connecting to an MQTT endpoint.
+#[derive(Clone, ValueEnum)]
+pub enum MqttVersion {
+    V3,
+    V5,
+}
+
+impl Display for MqttVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MqttVersion::V3 => f.write_str("3.1.1"),
+            MqttVersion::V5 => f.write_str("5"),
+        }
+    }
+}
+
+/// The options for connecting to an MQTT endpoint.
+#[derive(Args)]
+pub struct MqttClientOptions {
+    /// The client identifier to use in the MQTT Connect Packet.
+    #[arg(id = PARAM_MQTT_CLIENT_ID, long = PARAM_MQTT_CLIENT_ID, value_name = "ID", env = "MQTT_CLIENT_ID")]
+    client_id: Option<String>,
+
+    /// The URI of the MQTT endpoint to connect to.
+    #[arg(long = PARAM_MQTT_URI, value_name = "URI", env = "MQTT_URI")]
+    server_uri: String,
+
+    /// The username to use for authenticating to the MQTT endpoint.
+    #[arg(long = PARAM_MQTT_USERNAME, value_name = "USERNAME", env = "MQTT_USERNAME")]
+    username: Option<String>,
+
+    /// The password to use for authenticating to the MQTT endpoint.
+    #[arg(long = PARAM_MQTT_PASSWORD, value_name = "PWD", env = "MQTT_PASSWORD")]
+    password: Option<String>,
+
+    /// The path to a PEM file containing the X.509 certificate that the client should use for authentication.
+    #[arg(long = PARAM_DEVICE_CERT, value_name = "PATH", env = "DEVICE_CERT", value_parser = clap::builder::PathBufValueParser::new())]
+    cert_path: Option<PathBuf>,
+
+    /// The path to a PEM file containing the private key that the client should use for authentication.
+    #[arg(long = PARAM_DEVICE_KEY, value_name = "PATH", env = "DEVICE_KEY", value_parser = clap::builder::PathBufValueParser::new())]
+    key_path: Option<PathBuf>,
+
+    /// The path to a folder that contains PEM files for trusted certificate authorities.
+    #[arg(long = PARAM_CA_PATH, value_name = "PATH", env = "CA_PATH", value_parser = clap::builder::PathBufValueParser::new())]
+    ca_path: Option<PathBuf>,
+
+    /// The path to a file that contains PEM encoded trusted certificates.
+    #[arg(long = PARAM_TRUST_STORE_PATH, value_name = "PATH", env = "TRUST_STORE_PATH", value_parser = clap::builder::PathBufValueParser::new())]
+    trust_store_path: Option<PathBuf>,
+
+    /// Indicates whether server certificates should be matched against the
+    /// hostname/IP address used by a client to connect to the server.
+    #[arg(long = PARAM_ENABLE_HOSTNAME_VERIFICATION, value_name = "FLAG", default_value = "true", env = "ENABLE_HOSTNAME_VERIFICATION")]
+    enable_hostname_verification: bool,
+
+    /// The period of time after which messages that have not been delivered by the broker
+    /// should expire, e.g. 30s or 5m. This is only used with MQTT v5 and only for messages
+    /// that do not have a time-to-live of their own.
+    #[arg(long = PARAM_MQTT_MESSAGE_EXPIRY, value_name = "DURATION_SPEC", env = "MQTT_MESSAGE_EXPIRY", value_parser = |s: &str| duration_str::parse(s))]
+    message_expiry: Option<Duration>,
+
+    /// Indicates whether the broker should keep the client's session (including subscriptions and
+    /// unacknowledged QoS 1 messages) when the client disconnects. This requires a client identifier.
+    #[arg(long = PARAM_MQTT_PERSISTENT_SESSION, env = "MQTT_PERSISTENT_SESSION", requires = PARAM_MQTT_CLIENT_ID)]
+    persistent_session: bool,
+
+    /// The period of time after which the broker may discard a persistent session once the client
+    /// has disconnected, e.g. 12h or 7d. This is only used with MQTT v5.
+    #[arg(long = PARAM_MQTT_SESSION_EXPIRY, value_name = "DURATION_SPEC", env = "MQTT_SESSION_EXPIRY", default_value = "1d", value_parser = |s: &str| duration_str::parse(s))]
+    session_expiry: Duration,
+
+    /// The path to a folder in which the client stores messages that have not been acknowledged
+    /// by the broker yet. This allows these messages to be delivered after the process has been
+    /// restarted. If not set, such messages are only kept in memory.
+    #[arg(long = PARAM_MQTT_PERSISTENCE_DIR, value_name = "PATH", env = "MQTT_PERSISTENCE_DIR", value_parser = clap::builder::PathBufValueParser::new())]
+    persistence_dir: Option<PathBuf>,
+
+    /// The maximum number of messages to buffer while the client is not connected to the broker.
+    /// Once the buffer is full, the oldest messages are discarded or, if a persistence folder is
+    /// set, new messages are rejected.
+    #[arg(long = PARAM_MQTT_MAX_BUFFERED_MESSAGES, value_name = "NUMBER", env = "MQTT_MAX_BUFFERED_MESSAGES", default_value = "10000", value_parser = clap::value_parser!(i32).range(1..))]
+    max_buffered_messages: i32,
+}
+
+impl MqttClientOptions {
+    /// Gets the TLS options for connecting to the MQTT endpoint.
+    pub fn ssl_options(&self) -> Result<SslOptions, Box<dyn std::error::Error>> {
+        let mut ssl_options_builder = SslOptionsBuilder::new();
+        match (self.cert_path.as_ref(), self.key_path.as_ref()) {
+            (Some(cert_path), Some(key_path)) => {
+                if let Err(e) = ssl_options_builder.key_store(cert_path) {
+                    error!("failed to set client certificate for MQTT client: {}", e);
+                    return Err(Box::from(e));
+                }
+                if let Err(e) = ssl_options_builder.private_key(key_path) {
+                    error!("failed to set private key for MQTT client: {}", e);
+                    return Err(Box::from(e));
+                }
+                info!("using client certificate for authenticating to MQTT endpoint");
+            }
+            _ => {
+                debug!("no client key material specified");
+            }
+        }
+
+        if let Some(path) = self.ca_path.as_ref() {
+            if let Err(e) = ssl_options_builder.ca_path(path) {
+                error!("failed to set CA path on MQTT client: {}", e);
+                return Err(Box::from(e));
+            }
+        }
+        if let Some(path) = self.trust_store_path.as_ref() {
+            if let Err(e) = ssl_options_builder.trust_store(path) {
+                error!("failed to set trust store path on MQTT client: {}", e);
+                return Err(Box::from(e));
+            }
+        }
+        ssl_options_builder.verify(self.enable_hostname_verification);
+        Ok(ssl_options_builder.finalize())
+    }
+
+    /// Checks if messages that have not been delivered yet are stored in files.
+    pub fn has_persistence(&self) -> bool {
+        self.persistence_dir.is_some()
+    }
+
+    /// Gets the period of time after which undelivered messages should expire.
+    pub fn message_expiry(&self) -> Option<Duration> {
+        self.message_expiry
+    }
+
+    /// Sets the TLS and authentication related options which are
+    /// the same for all versions of the MQTT protocol.
+    fn apply_security_options(
+        &self,
+        connect_options_builder: &mut ConnectOptionsBuilder,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        connect_options_builder.ssl_options(self.ssl_options()?);
+
+        match self.credentials() {
+            Some((username, password)) => {
+                connect_options_builder.user_name(username);
+                connect_options_builder.password(password);
+                info!("using username and password for authenticating to MQTT endpoint");
+            }
+            None => {
+                debug!("no credentials specified");
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets the username and password to use for authenticating to the MQTT endpoint (if set).
+    fn credentials(&self) -> Option<(&str, &str)> {
+        self.username.as_deref().zip(self.password.as_deref())
+    }
+
+    fn mqtt3_connect_options(
+        &self,
+    ) -> Result<paho_mqtt::ConnectOptions, Box<dyn std::error::Error>> {
+        let mut connect_options_builder = ConnectOptionsBuilder::new_v3();
+        connect_options_builder.connect_timeout(Duration::from_secs(10));
+        connect_options_builder.clean_session(!self.persistent_session);
+        connect_options_builder.keep_alive_interval(Duration::from_secs(10));
+        connect_options_builder.max_inflight(10);
+        self.apply_security_options(&mut connect_options_builder)?;
+        Ok(connect_options_builder.finalize())
+    }
+
+    fn mqtt5_connect_options(
+        &self,
+    ) -> Result<paho_mqtt::ConnectOptions, Box<dyn std::error::Error>> {
+        let mut connect_options_builder = ConnectOptionsBuilder::new_v5();
+        connect_options_builder.connect_timeout(Duration::from_secs(5));
+        connect_options_builder.clean_start(!self.persistent_session);
+        if self.persistent_session {
+            // with MQTT 5, the session ends when the network connection is closed
+            // unless a session expiry interval has been set
+            let session_expiry = u32::try_from(self.session_expiry.as_secs()).unwrap_or(u32::MAX);
+            let mut properties = Properties::new();
+            properties.push_u32(PropertyCode::SessionExpiryInterval, session_expiry)?;
+            connect_options_builder.properties(properties);
+        }
+        connect_options_builder.keep_alive_interval(Duration::from_secs(10));
+        connect_options_builder.max_inflight(10);
+        self.apply_security_options(&mut connect_options_builder)?;
+        Ok(connect_options_builder.finalize())
+    }
+
+    /// Creates an MQTT client for the configured endpoint without connecting it.
+    pub fn create_async_client(&self) -> Result<AsyncClient, Box<dyn std::error::Error>> {
+        let persistence = match self.persistence_dir.as_ref() {
+            Some(path) => {
+                info!(
+                    "storing unacknowledged messages in folder [{}]",
+                    path.display()
+                );
+                PersistenceType::FilePath(path.to_owned())
+            }
+            None => PersistenceType::None,
+        };
+        CreateOptionsBuilder::new()
+            .server_uri(self.server_uri.as_str())
+            .max_buffered_messages(self.max_buffered_messages)
+            .send_while_disconnected(true)
+            // persisted messages are expected to be delivered eventually
+            .delete_oldest_messages(self.persistence_dir.is_none())
+            .persistence(persistence)
+            .client_id(self.client_id.clone().unwrap_or_default())
+            .create_client()
+            .map_err(Box::from)
+    }
+
+    /// Creates an MQTT client and connects it to the configured endpoint using the given
+    /// version of the MQTT protocol.
+    ///
+    /// The given function is invoked with the newly created client before the connection
+    /// is being established. It can be used to register callbacks or message consumers.
+    ///
+    /// The connection is established (and re-established after it has been lost) in the
+    /// background. The returned channel can be used to keep track of the connection's state.
+    /// Setting the state to [`ConnectionState::Disconnected`] stops reconnecting.
+    pub fn connect<F>(
+        &self,
+        protocol_version: &MqttVersion,
+        prepare_client: F,
+    ) -> Result<(AsyncClient, watch::Sender<ConnectionState>), Box<dyn std::error::Error>>
+    where
+        F: FnOnce(&mut AsyncClient),
+    {
+        let connect_options = match protocol_version {
+            MqttVersion::V3 => self.mqtt3_connect_options()?,
+            MqttVersion::V5 => self.mqtt5_connect_options()?,
+        };
+        let mut client = self.create_async_client().inspect_err(|e| {
+            error!("failed to create MQTT client: {}", e);
+        })?;
+        prepare_client(&mut client);
+
+        let connection_lost = Arc::new(Notify::new());
+        let connection_lost_signal = connection_lost.clone();
+        client.set_connection_lost_callback(move |_client| {
+            connection_lost_signal.notify_one();
+        });
+        let (state, _) = watch::channel(ConnectionState::Connecting);
+        info!(
+            "connecting to MQTT endpoint [broker URI: {}, protocol version: {}]",
+            client.server_uri(),
+            protocol_version
+        );
+        tokio::spawn(maintain_connection(
+            client.clone(),
+            connect_options,
+            state.clone(),
+            connection_lost,
+        ));
+        Ok((client, state))
+    }
+}
+
+impl Display for MqttClientOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "MQTT Client Options [server-uri: {}]",
+            self.server_uri
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{Args, FromArgMatches};
+
+    use super::*;
+
+    #[test]
+    fn test_command_line_uses_defaults() {
+        let command = clap::Command::new("mqtt");
+        let command = MqttClientOptions::augment_args(command);
+        let matches =
+            command.get_matches_from(vec!["mqtt", "--mqtt-uri", "mqtts://non-existing.host.io"]);
+        let options = MqttClientOptions::from_arg_matches(&matches)
+            .expect("failed to create options from command line");
+
+        assert_eq!(options.server_uri, "mqtts://non-existing.host.io");
+        assert!(options.client_id.is_none());
+        assert!(options.username.is_none());
+        assert!(options.password.is_none());
+        assert!(options.cert_path.is_none());
+        assert!(options.key_path.is_none());
+        assert!(options.ca_path.is_none());
+        assert!(options.enable_hostname_verification);
+        assert!(options.message_expiry.is_none());
+        assert!(!options.persistent_session);
+        assert!(options.persistence_dir.is_none());
+        assert_eq!(options.max_buffered_messages, 10000);
+    }
+
+    #[test]
+    fn test_reconnect_delay_is_bounded() {
+        for failed_attempts in 1..40 {
+            let delay = reconnect_delay(failed_attempts);
+            assert!(delay >= MIN_RECONNECT_DELAY / 2);
+            assert!(delay <= MAX_RECONNECT_DELAY);
+        }
+        assert!(reconnect_delay(1) <= MIN_RECONNECT_DELAY);
+    }
+
+    #[test]
+    fn test_persistent_session_requires_client_id() {
+        let command = clap::Command::new("mqtt");
+        let command = MqttClientOptions::augment_args(command);
+        let result = command.clone().try_get_matches_from(vec![
+            "mqtt",
+            "--mqtt-uri",
+            "mqtts://non-existing.host.io",
+            "--mqtt-persistent-session",
+        ]);
+        assert!(result.is_err());
+
+        let matches = command.get_matches_from(vec![
+            "mqtt",
+            "--mqtt-uri",
+            "mqtts://non-existing.host.io",
+            "--mqtt-persistent-session",
+            "--mqtt-client-id",
+            "vehicle-1",
+        ]);
+        let options = MqttClientOptions::from_arg_matches(&matches)
+            .expect("failed to create options from command line");
+        let connect_options = options
+            .mqtt5_connect_options()
+            .expect("failed to create connect options");
+        assert!(!connect_options.clean_start());
+    }
+
+    #[test]
+    fn test_mqtt5_connect_options_support_credentials() {
+        let command = clap::Command::new("mqtt");
+        let command = MqttClientOptions::augment_args(command);
+        let matches = command.get_matches_from(vec![
+            "mqtt",
+            "--mqtt-uri",
+            "mqtts://non-existing.host.io",
+            "--mqtt-username",
+            "device@tenant",
+            "--mqtt-password",
+            "secret",
+            "--ca-path",
+            "/etc/ssl/certs",
+            "--trust-store-path",
+            "/etc/ssl/certs/ca-certificates.crt",
+        ]);
+        let options = MqttClientOptions::from_arg_matches(&matches)
+            .expect("failed to create options from command line");
+
+        assert_eq!(options.credentials(), Some(("device@tenant", "secret")));
+        let ssl_options = options.ssl_options().expect("failed to create SSL options");
+        assert_eq!(ssl_options.ca_path(), PathBuf::from("/etc/ssl/certs"));
+        assert_eq!(
+            ssl_options.trust_store(),
+            PathBuf::from("/etc/ssl/certs/ca-certificates.crt")
+        );
+        // no client certificate has been configured
+        assert_eq!(ssl_options.key_store(), PathBuf::new());
+
+        let connect_options = options
+            .mqtt5_connect_options()
+            .expect("failed to create connect options");
+        assert_eq!(connect_options.mqtt_version(), mqtt::MqttVersion::V5);
+        assert!(connect_options.clean_start());
+    }
+
+    #[test]
+    fn test_ssl_options_use_client_certificate() {
+        let command = clap::Command::new("mqtt");
+        let command = MqttClientOptions::augment_args(command);
+        let matches = command.get_matches_from(vec![
+            "mqtt",
+            "--mqtt-uri",
+            "mqtts://non-existing.host.io",
+            "--mqtt-username",
+            "device@tenant",
+            "--device-cert",
+            "/etc/certs/device.pem",
+            "--device-key",
+            "/etc/certs/device.key",
+        ]);
+        let options = MqttClientOptions::from_arg_matches(&matches)
+            .expect("failed to create options from command line");
+
+        // credentials are only used if both username and password are set
+        assert!(options.credentials().is_none());
+        let ssl_options = options.ssl_options().expect("failed to create SSL options");
+        assert_eq!(
+            ssl_options.key_store(),
+            PathBuf::from("/etc/certs/device.pem")
+        );
+        assert_eq!(
+            ssl_options.private_key(),
+            PathBuf::from("/etc/certs/device.key")
+        );
+    }
+}
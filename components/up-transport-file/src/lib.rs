@@ -0,0 +1,345 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    fs::File,
+    io::{self, BufReader, Write},
+    path::PathBuf,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use clap::Args;
+use fms_proto::fms::VehicleStatus;
+use log::{debug, error, info, warn};
+use record::{files_to_read, RotatingFileWriter};
+use up_rust::{
+    local_transport::LocalTransport, UCode, UListener, UMessage, UMessageBuilder, UStatus,
+    UTransport, UUri,
+};
+
+pub use record::RecordFormat;
+
+mod record;
+
+const PARAM_FILE_PATH: &str = "path";
+const PARAM_FORMAT: &str = "format";
+const PARAM_MAX_FILE_SIZE: &str = "max-file-size";
+const PARAM_MAX_FILES: &str = "max-files";
+const PARAM_REPLAY_TOPIC: &str = "replay-topic";
+
+const PATH_STDIO: &str = "-";
+
+#[derive(Args)]
+pub struct FileTransportConfig {
+    /// The path of the file to write vehicle status records to or to read them from.
+    /// Records are written to stdout or read from stdin if not set or set to '-'.
+    #[arg(long = PARAM_FILE_PATH, value_name = "PATH", env = "FILE_PATH", value_parser = clap::builder::PathBufValueParser::new())]
+    path: Option<PathBuf>,
+
+    /// The format to write vehicle status records in or to read them from.
+    #[arg(long = PARAM_FORMAT, value_name = "FORMAT", env = "FILE_FORMAT", default_value = "json", value_enum)]
+    format: RecordFormat,
+
+    /// The maximum size of the file in bytes. Once the file has reached this size, it gets rotated
+    /// to <PATH>.1 and a new file is started. A value of 0 disables rotation.
+    #[arg(long = PARAM_MAX_FILE_SIZE, value_name = "BYTES", env = "FILE_MAX_SIZE", default_value = "0")]
+    max_file_size: u64,
+
+    /// The maximum number of rotated files to keep.
+    #[arg(long = PARAM_MAX_FILES, value_name = "NUMBER", env = "FILE_MAX_FILES", default_value = "5")]
+    max_files: usize,
+
+    /// The topic to use as the source of the vehicle status events that are read from the file(s).
+    #[arg(long = PARAM_REPLAY_TOPIC, value_name = "URI", env = "FILE_REPLAY_TOPIC", default_value = "up://fms-forwarder/D100/1/D100", value_parser = up_rust::UUri::from_str)]
+    replay_topic: UUri,
+}
+
+impl FileTransportConfig {
+    fn file_path(&self) -> Option<PathBuf> {
+        self.path
+            .as_ref()
+            .filter(|path| path.as_os_str() != PATH_STDIO)
+            .cloned()
+    }
+}
+
+enum RecordWriter {
+    Stdout(io::Stdout),
+    File(RotatingFileWriter),
+}
+
+impl RecordWriter {
+    fn write_record(&mut self, record: &[u8]) -> io::Result<()> {
+        match self {
+            RecordWriter::Stdout(stdout) => {
+                let mut lock = stdout.lock();
+                lock.write_all(record)?;
+                lock.flush()
+            }
+            RecordWriter::File(writer) => writer.write_record(record),
+        }
+    }
+}
+
+/// A uProtocol transport implementation for capturing vehicle status events in a file
+/// and replaying them later on.
+///
+/// Sending a message writes the vehicle status contained in the message's payload to the
+/// file, or to stdout if no file has been configured. Starting the replay reads all
+/// vehicle status records from the file (including the files that it has been rotated to),
+/// or from stdin if no file has been configured, and delivers them to the registered listeners
+/// as notifications originating from the configured replay topic.
+///
+/// [`FileTransport::start_replay`] needs to be invoked after all listeners have been registered,
+/// because records that have already been replayed are not delivered to listeners that are
+/// registered later on.
+pub struct FileTransport {
+    path: Option<PathBuf>,
+    format: RecordFormat,
+    max_file_size: u64,
+    max_files: usize,
+    replay_topic: UUri,
+    writer: Mutex<Option<RecordWriter>>,
+    local_transport: Arc<LocalTransport>,
+    replay_started: AtomicBool,
+}
+
+impl FileTransport {
+    pub fn new(config: &FileTransportConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        if let Some(path) = config.file_path() {
+            if path.is_dir() {
+                return Err(format!("{} is a directory", path.display()).into());
+            }
+        }
+        Ok(FileTransport {
+            path: config.file_path(),
+            format: config.format,
+            max_file_size: config.max_file_size,
+            max_files: config.max_files,
+            replay_topic: config.replay_topic.clone(),
+            writer: Mutex::new(None),
+            local_transport: Arc::new(LocalTransport::default()),
+            replay_started: AtomicBool::new(false),
+        })
+    }
+
+    fn write_vehicle_status(&self, vehicle_status: &VehicleStatus) -> io::Result<()> {
+        let record = self.format.encode(vehicle_status)?;
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_e| io::Error::other("writer lock is poisoned"))?;
+        // the file is opened lazily in order to not create
+        // an empty file if the transport is used for reading only
+        if writer.is_none() {
+            *writer = Some(match &self.path {
+                Some(path) => {
+                    info!("writing vehicle status records to {}", path.display());
+                    RecordWriter::File(RotatingFileWriter::new(
+                        path.clone(),
+                        self.max_file_size,
+                        self.max_files,
+                    )?)
+                }
+                None => RecordWriter::Stdout(io::stdout()),
+            });
+        }
+        writer.as_mut().map_or(Ok(()), |w| w.write_record(&record))
+    }
+
+    /// Starts replaying the vehicle status records to the registered listeners in the background.
+    ///
+    /// The records are replayed only once, i.e. invoking this function again has no effect.
+    ///
+    /// # Panics
+    ///
+    /// Panics if not called from within a Tokio runtime.
+    pub fn start_replay(&self) {
+        if self.replay_started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<VehicleStatus>(100);
+        let path = self.path.clone();
+        let format = self.format;
+        tokio::task::spawn_blocking(move || {
+            let result = match path {
+                Some(path) => files_to_read(&path).iter().try_for_each(|file| {
+                    info!("reading vehicle status records from {}", file.display());
+                    let reader = BufReader::new(File::open(file)?);
+                    format.read_records(reader, |vehicle_status| {
+                        tx.blocking_send(vehicle_status).is_ok()
+                    })
+                }),
+                None => format.read_records(io::stdin().lock(), |vehicle_status| {
+                    tx.blocking_send(vehicle_status).is_ok()
+                }),
+            };
+            if let Err(e) = result {
+                error!("failed to read vehicle status records: {e}");
+            }
+        });
+
+        let local_transport = self.local_transport.clone();
+        let topic = self.replay_topic.clone();
+        tokio::spawn(async move {
+            let mut count = 0u64;
+            while let Some(vehicle_status) = rx.recv().await {
+                match UMessageBuilder::publish(topic.clone())
+                    .build_with_protobuf_payload(&vehicle_status)
+                {
+                    Ok(msg) => {
+                        if let Err(e) = local_transport.send(msg).await {
+                            debug!("failed to dispatch vehicle status: {e}");
+                        } else {
+                            count += 1;
+                        }
+                    }
+                    Err(e) => {
+                        warn!("failed to create message for vehicle status: {e}");
+                    }
+                }
+            }
+            info!("finished replaying {count} vehicle status records");
+        });
+    }
+}
+
+#[async_trait::async_trait]
+impl UTransport for FileTransport {
+    async fn send(&self, message: UMessage) -> Result<(), UStatus> {
+        let vehicle_status = message.extract_protobuf::<VehicleStatus>().map_err(|e| {
+            UStatus::fail_with_code(
+                UCode::INVALID_ARGUMENT,
+                format!("message does not contain vehicle status: {e}"),
+            )
+        })?;
+        self.write_vehicle_status(&vehicle_status).map_err(|e| {
+            UStatus::fail_with_code(
+                UCode::UNAVAILABLE,
+                format!("failed to write vehicle status: {e}"),
+            )
+        })
+    }
+
+    async fn register_listener(
+        &self,
+        source_filter: &UUri,
+        sink_filter: Option<&UUri>,
+        listener: Arc<dyn UListener>,
+    ) -> Result<(), UStatus> {
+        if !source_filter.matches(&self.replay_topic) {
+            warn!(
+                "source filter [{}] does not match replay topic [{}]",
+                source_filter.to_uri(false),
+                self.replay_topic.to_uri(false)
+            );
+        }
+        self.local_transport
+            .register_listener(source_filter, sink_filter, listener)
+            .await
+    }
+
+    async fn unregister_listener(
+        &self,
+        source_filter: &UUri,
+        sink_filter: Option<&UUri>,
+        listener: Arc<dyn UListener>,
+    ) -> Result<(), UStatus> {
+        self.local_transport
+            .unregister_listener(source_filter, sink_filter, listener)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    struct CollectingListener {
+        tx: mpsc::UnboundedSender<VehicleStatus>,
+    }
+
+    #[async_trait::async_trait]
+    impl UListener for CollectingListener {
+        async fn on_receive(&self, msg: UMessage) {
+            let _ = self.tx.send(msg.extract_protobuf().unwrap());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_captured_vehicle_status_can_be_replayed() {
+        let dir =
+            std::env::temp_dir().join(format!("up-transport-file-replay-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let topic = UUri::from_str("up://fms-forwarder/D100/1/D100").unwrap();
+        let config = FileTransportConfig {
+            path: Some(dir.join("capture.bin")),
+            format: RecordFormat::LengthDelimitedProtobuf,
+            max_file_size: 0,
+            max_files: 0,
+            replay_topic: topic.clone(),
+        };
+
+        let capturing_transport = FileTransport::new(&config).unwrap();
+        for vin in ["VIN1", "VIN2"] {
+            let mut vehicle_status = VehicleStatus::new();
+            vehicle_status.vin = vin.to_string();
+            let msg = UMessageBuilder::publish(topic.clone())
+                .build_with_protobuf_payload(&vehicle_status)
+                .unwrap();
+            capturing_transport.send(msg).await.unwrap();
+        }
+
+        let replaying_transport = FileTransport::new(&config).unwrap();
+        let mut receivers = Vec::new();
+        for source_filter in ["up://*/D100/1/D100", "up://fms-forwarder/D100/1/D100"] {
+            let (tx, rx) = mpsc::unbounded_channel();
+            replaying_transport
+                .register_listener(
+                    &UUri::from_str(source_filter).unwrap(),
+                    None,
+                    Arc::new(CollectingListener { tx }),
+                )
+                .await
+                .unwrap();
+            receivers.push(rx);
+        }
+        // nothing is replayed before the replay has been started
+        tokio::task::yield_now().await;
+        assert!(receivers.iter_mut().all(|rx| rx.try_recv().is_err()));
+
+        replaying_transport.start_replay();
+        replaying_transport.start_replay();
+        for rx in receivers.iter_mut() {
+            assert_eq!(rx.recv().await.unwrap().vin, "VIN1");
+            assert_eq!(rx.recv().await.unwrap().vin, "VIN2");
+        }
+        // the records are replayed once only
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert!(receivers.iter_mut().all(|rx| rx.try_recv().is_err()));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
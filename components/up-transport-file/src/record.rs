@@ -0,0 +1,287 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Encoding and decoding of vehicle status records and rotation of the files they are written to.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use fms_proto::fms::VehicleStatus;
+use protobuf::{CodedInputStream, Message};
+
+/// The format that vehicle status records are written in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum RecordFormat {
+    /// One JSON object per line, using the canonical protobuf JSON mapping.
+    #[value(name = "json")]
+    JsonLines,
+    /// The binary protobuf encoding, each record being prefixed with its length as a varint.
+    #[value(name = "protobuf")]
+    LengthDelimitedProtobuf,
+}
+
+impl RecordFormat {
+    /// Encodes a vehicle status into a record.
+    pub fn encode(&self, vehicle_status: &VehicleStatus) -> io::Result<Vec<u8>> {
+        match self {
+            RecordFormat::JsonLines => protobuf_json_mapping::print_to_string(vehicle_status)
+                .map(|json| {
+                    let mut record = json.into_bytes();
+                    record.push(b'\n');
+                    record
+                })
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            RecordFormat::LengthDelimitedProtobuf => vehicle_status
+                .write_length_delimited_to_bytes()
+                .map_err(io::Error::from),
+        }
+    }
+
+    /// Reads all records from a reader.
+    ///
+    /// The given consumer is invoked for each record that has been read. Reading stops
+    /// if the consumer returns `false`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reader's content cannot be decoded.
+    pub fn read_records<R, F>(&self, reader: R, mut consumer: F) -> io::Result<()>
+    where
+        R: BufRead,
+        F: FnMut(VehicleStatus) -> bool,
+    {
+        match self {
+            RecordFormat::JsonLines => {
+                for line in reader.lines() {
+                    let line = line?;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let vehicle_status =
+                        protobuf_json_mapping::parse_from_str::<VehicleStatus>(&line)
+                            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                    if !consumer(vehicle_status) {
+                        break;
+                    }
+                }
+                Ok(())
+            }
+            RecordFormat::LengthDelimitedProtobuf => {
+                let mut reader = reader;
+                let mut input = CodedInputStream::from_buf_read(&mut reader);
+                while !input.eof()? {
+                    let vehicle_status = input.read_message::<VehicleStatus>()?;
+                    if !consumer(vehicle_status) {
+                        break;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Gets the path of a rotated file.
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(format!(".{index}"));
+    PathBuf::from(rotated)
+}
+
+/// Gets the files that contain the records written to a path, oldest first.
+///
+/// This includes the files that the original file has been rotated to.
+pub fn files_to_read(path: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = (1..)
+        .map(|index| rotated_path(path, index))
+        .take_while(|p| p.exists())
+        .collect();
+    files.reverse();
+    files.push(path.to_path_buf());
+    files
+}
+
+/// A writer that appends records to a file, rotating the file once it reaches a maximum size.
+///
+/// When rotating, the current file is renamed to `<path>.1`, an existing `<path>.1` is
+/// renamed to `<path>.2` and so on. Files exceeding the maximum number of rotated files
+/// are removed.
+pub struct RotatingFileWriter {
+    path: PathBuf,
+    max_file_size: u64,
+    max_rotated_files: usize,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFileWriter {
+    /// Creates a new writer.
+    ///
+    /// A `max_file_size` of zero disables rotation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened for appending.
+    pub fn new(path: PathBuf, max_file_size: u64, max_rotated_files: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(RotatingFileWriter {
+            path,
+            max_file_size,
+            max_rotated_files,
+            file,
+            size,
+        })
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.max_rotated_files == 0 {
+            self.file.set_len(0)?;
+        } else {
+            let oldest = rotated_path(&self.path, self.max_rotated_files);
+            if oldest.exists() {
+                fs::remove_file(&oldest)?;
+            }
+            for index in (1..self.max_rotated_files).rev() {
+                let from = rotated_path(&self.path, index);
+                if from.exists() {
+                    fs::rename(&from, rotated_path(&self.path, index + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated_path(&self.path, 1))?;
+            self.file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+
+    /// Appends a record to the file.
+    ///
+    /// The file is rotated before writing if the record would make it exceed the maximum size.
+    pub fn write_record(&mut self, record: &[u8]) -> io::Result<()> {
+        if self.max_file_size > 0
+            && self.size > 0
+            && self.size + record.len() as u64 > self.max_file_size
+        {
+            self.rotate()?;
+        }
+        self.file.write_all(record)?;
+        self.file.flush()?;
+        self.size += record.len() as u64;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use super::*;
+
+    fn new_vehicle_status(vin: &str) -> VehicleStatus {
+        let mut vehicle_status = VehicleStatus::new();
+        vehicle_status.vin = vin.to_string();
+        vehicle_status.hr_total_vehicle_distance = Some(123_456);
+        vehicle_status.trigger.mut_or_insert_default().type_ = "TIMER".to_string();
+        vehicle_status
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("up-transport-file-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_records_can_be_read_back() {
+        for format in [
+            RecordFormat::JsonLines,
+            RecordFormat::LengthDelimitedProtobuf,
+        ] {
+            let mut buffer = Vec::new();
+            for vin in ["VIN1", "VIN2", "VIN3"] {
+                buffer.extend(format.encode(&new_vehicle_status(vin)).unwrap());
+            }
+            let mut vins = Vec::new();
+            format
+                .read_records(BufReader::new(buffer.as_slice()), |vehicle_status| {
+                    assert_eq!(vehicle_status.hr_total_vehicle_distance, Some(123_456));
+                    assert_eq!(vehicle_status.trigger.type_, "TIMER");
+                    vins.push(vehicle_status.vin);
+                    true
+                })
+                .unwrap();
+            assert_eq!(vins, vec!["VIN1", "VIN2", "VIN3"]);
+        }
+    }
+
+    #[test]
+    fn test_json_records_are_single_lines() {
+        let record = RecordFormat::JsonLines
+            .encode(&new_vehicle_status("VIN1"))
+            .unwrap();
+        let json = String::from_utf8(record).unwrap();
+        assert!(json.ends_with('\n'));
+        assert_eq!(json.lines().count(), 1);
+        assert!(json.contains("VIN1"));
+    }
+
+    #[test]
+    fn test_writer_rotates_files() {
+        let dir = temp_dir("rotation");
+        let path = dir.join("capture.jsonl");
+        let format = RecordFormat::JsonLines;
+        let record_size = format.encode(&new_vehicle_status("VIN0")).unwrap().len() as u64;
+        let mut writer = RotatingFileWriter::new(path.clone(), 2 * record_size, 2).unwrap();
+        for index in 0..7 {
+            let record = format
+                .encode(&new_vehicle_status(&format!("VIN{index}")))
+                .unwrap();
+            writer.write_record(&record).unwrap();
+        }
+
+        let files = files_to_read(&path);
+        assert_eq!(
+            files,
+            vec![rotated_path(&path, 2), rotated_path(&path, 1), path.clone()]
+        );
+        let mut vins = Vec::new();
+        for file in files {
+            let reader = BufReader::new(File::open(file).unwrap());
+            format
+                .read_records(reader, |vehicle_status| {
+                    vins.push(vehicle_status.vin);
+                    true
+                })
+                .unwrap();
+        }
+        // the oldest records have been discarded
+        assert_eq!(vins, vec!["VIN2", "VIN3", "VIN4", "VIN5", "VIN6"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
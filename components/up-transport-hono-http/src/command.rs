@@ -0,0 +1,40 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Support for Hono's command & control message exchange pattern.
+//!
+//! The HTTP adapter piggy-backs a command on the response to a request that contains
+//! a `hono-ttd` header. The transport polls for commands by means of empty notifications,
+//! so that regular messages are not held back while the adapter waits for a command.
+//! The name of the command is conveyed in the `hono-command` header and the identifier
+//! to correlate the response with in the `hono-cmd-req-id` header. The response to a
+//! command is sent to `/command/res/${req-id}` with the status code being conveyed in
+//! the `hono-cmd-status` header.
+
+pub(crate) const CONTENT_TYPE_EMPTY_NOTIFICATION: &str =
+    "application/vnd.eclipse-hono-empty-notification";
+pub(crate) const HEADER_COMMAND: &str = "hono-command";
+pub(crate) const HEADER_COMMAND_REQUEST_ID: &str = "hono-cmd-req-id";
+pub(crate) const HEADER_COMMAND_STATUS: &str = "hono-cmd-status";
+pub(crate) const HEADER_TIME_TILL_DISCONNECT: &str = "hono-ttd";
+
+/// Gets the path of the resource to post the response to a command to.
+pub(crate) fn response_path(request_id: &str) -> String {
+    format!("/command/res/{}", request_id)
+}
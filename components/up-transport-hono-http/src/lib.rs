@@ -0,0 +1,703 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use clap::Args;
+use hono_wire_format::{command::PendingRequests, is_event, parse_priority, WireFormat};
+use isahc::{
+    auth::{Authentication, Credentials},
+    config::{CaCertificate, ClientCertificate, Configurable, PrivateKey, SslOption},
+    AsyncBody, AsyncReadResponseExt, HttpClient, Request, Response,
+};
+use log::{debug, info, warn};
+use tokio::task::JoinHandle;
+use up_rust::{
+    local_transport::LocalTransport, UAttributes, UCode, UListener, UMessage, UPriority, UStatus,
+    UTransport, UUri,
+};
+
+mod command;
+
+const PARAM_CA_FILE: &str = "ca-file";
+const PARAM_DEVICE_CERT: &str = "device-cert";
+const PARAM_DEVICE_KEY: &str = "device-key";
+const PARAM_ENABLE_HOSTNAME_VERIFICATION: &str = "enable-hostname-verification";
+const PARAM_EVENT_PRIORITY: &str = "event-priority";
+const PARAM_HTTP_ADAPTER_URI: &str = "http-adapter-uri";
+const PARAM_HTTP_PASSWORD: &str = "http-password";
+const PARAM_HTTP_USERNAME: &str = "http-username";
+const PARAM_TIME_TILL_DISCONNECT: &str = "ttd";
+const PARAM_WIRE_FORMAT: &str = "wire-format";
+
+const PATH_EVENT: &str = "/event";
+const PATH_TELEMETRY: &str = "/telemetry";
+
+// the time to wait for the HTTP adapter's response in addition to the time till disconnect
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+// the time to wait before polling for commands again after a poll request has failed
+const POLL_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Args)]
+pub struct HonoHttpTransportConfig {
+    /// The base URI of Hono's HTTP adapter, e.g. https://hono.eclipseprojects.io:8443.
+    #[arg(long = PARAM_HTTP_ADAPTER_URI, value_name = "URI", env = "HTTP_ADAPTER_URI")]
+    adapter_uri: String,
+
+    /// The username to use for authenticating to the HTTP adapter, e.g. device@tenant.
+    #[arg(id = PARAM_HTTP_USERNAME, long = PARAM_HTTP_USERNAME, value_name = "USERNAME", env = "HTTP_USERNAME", requires = PARAM_HTTP_PASSWORD)]
+    username: Option<String>,
+
+    /// The password to use for authenticating to the HTTP adapter.
+    #[arg(id = PARAM_HTTP_PASSWORD, long = PARAM_HTTP_PASSWORD, value_name = "PWD", env = "HTTP_PASSWORD", requires = PARAM_HTTP_USERNAME)]
+    password: Option<String>,
+
+    /// The path to a PEM file containing the X.509 certificate that the device should use for authentication.
+    #[arg(id = PARAM_DEVICE_CERT, long = PARAM_DEVICE_CERT, value_name = "PATH", env = "DEVICE_CERT", requires = PARAM_DEVICE_KEY, value_parser = clap::builder::PathBufValueParser::new())]
+    cert_path: Option<PathBuf>,
+
+    /// The path to a PEM file containing the private key that the device should use for authentication.
+    #[arg(id = PARAM_DEVICE_KEY, long = PARAM_DEVICE_KEY, value_name = "PATH", env = "DEVICE_KEY", requires = PARAM_DEVICE_CERT, value_parser = clap::builder::PathBufValueParser::new())]
+    key_path: Option<PathBuf>,
+
+    /// The path to a file that contains PEM encoded trusted certificates.
+    #[arg(long = PARAM_CA_FILE, value_name = "PATH", env = "CA_FILE", value_parser = clap::builder::PathBufValueParser::new())]
+    ca_file: Option<PathBuf>,
+
+    /// Indicates whether server certificates should be matched against the
+    /// hostname/IP address used by a client to connect to the server.
+    #[arg(long = PARAM_ENABLE_HOSTNAME_VERIFICATION, value_name = "FLAG", default_value = "true", env = "ENABLE_HOSTNAME_VERIFICATION")]
+    enable_hostname_verification: bool,
+
+    /// The number of seconds that the HTTP adapter should wait for a command to be sent to the device
+    /// before responding to a command poll request. Commands are only received if this is set.
+    #[arg(long = PARAM_TIME_TILL_DISCONNECT, value_name = "SECONDS", env = "HONO_TTD", value_parser = clap::value_parser!(u32).range(1..))]
+    time_till_disconnect: Option<u32>,
+
+    /// The minimum uProtocol priority (CS0 - CS6) of messages that are sent to Hono's event endpoint.
+    /// All other messages are sent to Hono's telemetry endpoint.
+    #[arg(long = PARAM_EVENT_PRIORITY, value_name = "CODE", env = "EVENT_PRIORITY", default_value = "CS2", value_parser = parse_priority)]
+    event_priority: UPriority,
+
+    /// The format to post messages in. Note that raw protobuf does not contain any of the uProtocol
+    /// message attributes.
+    #[arg(long = PARAM_WIRE_FORMAT, value_name = "FORMAT", env = "WIRE_FORMAT", value_enum, default_value_t = WireFormat::CloudEventsProtobuf)]
+    wire_format: WireFormat,
+}
+
+/// Maps the status code of a response from the HTTP adapter to a uProtocol status code.
+fn ucode_for_status(status: u16) -> UCode {
+    match status {
+        400 | 413 => UCode::INVALID_ARGUMENT,
+        401 => UCode::UNAUTHENTICATED,
+        403 => UCode::PERMISSION_DENIED,
+        404 => UCode::NOT_FOUND,
+        429 => UCode::RESOURCE_EXHAUSTED,
+        503 => UCode::UNAVAILABLE,
+        _ => UCode::INTERNAL,
+    }
+}
+
+/// A uProtocol transport implementation for exchanging messages with Eclipse Hono's HTTP adapter.
+///
+/// Messages are posted in the configured wire format to the adapter's event endpoint if their
+/// priority is at least the configured event priority, otherwise they are posted to the telemetry
+/// endpoint.
+///
+/// If a time till disconnect has been configured, the transport continuously polls the adapter for
+/// commands in the background by means of empty notifications, once the first listener has been
+/// registered. The posting of messages is therefore not delayed by the adapter waiting for a command. Commands that are piggy-backed on the adapter's
+/// responses are expected to contain a uProtocol message in a CloudEvent (in the format indicated
+/// by the response's content type) and are dispatched to the registered listeners. Response messages
+/// are posted to the adapter as the response to the command that the corresponding request has been
+/// created from.
+pub struct HonoHttpTransport {
+    http_client: HttpClient,
+    adapter_uri: String,
+    event_priority: UPriority,
+    wire_format: WireFormat,
+    local_transport: Arc<LocalTransport>,
+    pending_commands: Arc<PendingRequests<String>>,
+    time_till_disconnect: Option<u32>,
+    command_poller: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl HonoHttpTransport {
+    /// Creates a new transport.
+    pub fn new(config: &HonoHttpTransportConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut builder = HttpClient::builder().timeout(REQUEST_TIMEOUT);
+        if let (Some(username), Some(password)) =
+            (config.username.as_ref(), config.password.as_ref())
+        {
+            info!("using username and password for authenticating to HTTP adapter");
+            builder = builder
+                .authentication(Authentication::basic())
+                .credentials(Credentials::new(username, password));
+        }
+        if let (Some(cert_path), Some(key_path)) =
+            (config.cert_path.as_ref(), config.key_path.as_ref())
+        {
+            info!("using client certificate for authenticating to HTTP adapter");
+            builder = builder.ssl_client_certificate(ClientCertificate::pem_file(
+                cert_path,
+                PrivateKey::pem_file(key_path, None),
+            ));
+        }
+        if let Some(ca_file) = config.ca_file.as_ref() {
+            builder = builder.ssl_ca_certificate(CaCertificate::file(ca_file));
+        }
+        if !config.enable_hostname_verification {
+            builder = builder.ssl_options(SslOption::DANGER_ACCEPT_INVALID_HOSTS);
+        }
+        let http_client = builder.build()?;
+        let adapter_uri = config.adapter_uri.trim_end_matches('/').to_string();
+        let local_transport = Arc::new(LocalTransport::default());
+        let pending_commands = Arc::new(PendingRequests::default());
+        Ok(HonoHttpTransport {
+            http_client,
+            adapter_uri,
+            event_priority: config.event_priority,
+            wire_format: config.wire_format,
+            local_transport,
+            pending_commands,
+            time_till_disconnect: config.time_till_disconnect,
+            command_poller: Mutex::new(None),
+        })
+    }
+
+    /// Starts polling for commands, unless no time till disconnect has been configured
+    /// or polling has already been started.
+    ///
+    /// # Panics
+    ///
+    /// Panics if not called from within a Tokio runtime.
+    fn start_polling_commands(&self) {
+        let Some(ttd) = self.time_till_disconnect else {
+            return;
+        };
+        let Ok(mut command_poller) = self.command_poller.lock() else {
+            return;
+        };
+        if command_poller.is_none() {
+            info!("polling HTTP adapter for commands [ttd: {}s]", ttd);
+            *command_poller = Some(tokio::spawn(Self::poll_commands(
+                self.http_client.clone(),
+                format!("{}{}", self.adapter_uri, PATH_TELEMETRY),
+                ttd,
+                self.local_transport.clone(),
+                self.pending_commands.clone(),
+            )));
+        }
+    }
+
+    /// Polls the HTTP adapter for commands until the transport is dropped.
+    ///
+    /// Each poll request is an empty notification which asks the adapter to wait for a command
+    /// for the given number of seconds before responding.
+    async fn poll_commands(
+        http_client: HttpClient,
+        uri: String,
+        time_till_disconnect: u32,
+        local_transport: Arc<LocalTransport>,
+        pending_commands: Arc<PendingRequests<String>>,
+    ) {
+        let timeout = REQUEST_TIMEOUT + Duration::from_secs(u64::from(time_till_disconnect));
+        loop {
+            let request = match Request::post(uri.as_str())
+                .timeout(timeout)
+                .header("content-type", command::CONTENT_TYPE_EMPTY_NOTIFICATION)
+                .header(command::HEADER_TIME_TILL_DISCONNECT, time_till_disconnect)
+                .body(Vec::new())
+            {
+                Ok(request) => request,
+                Err(e) => {
+                    warn!("failed to create command poll request: {}", e);
+                    return;
+                }
+            };
+            match http_client.send_async(request).await {
+                Ok(response) if response.status().is_success() => {
+                    Self::dispatch_command(response, &local_transport, &pending_commands).await;
+                    continue;
+                }
+                Ok(response) => {
+                    warn!(
+                        "HTTP adapter rejected command poll request [status: {}]",
+                        response.status()
+                    );
+                }
+                Err(e) => {
+                    warn!("error polling HTTP adapter for commands: {}", e);
+                }
+            }
+            tokio::time::sleep(POLL_RETRY_DELAY).await;
+        }
+    }
+
+    /// Creates the request to post a message with to the HTTP adapter.
+    fn create_request(
+        &self,
+        attributes: &UAttributes,
+        payload: Vec<u8>,
+    ) -> Result<Request<Vec<u8>>, UStatus> {
+        let request_builder = if attributes.is_response() {
+            let request_id = attributes.reqid.get_or_default().to_hyphenated_string();
+            let hono_request_id = self.pending_commands.remove(&request_id).ok_or_else(|| {
+                UStatus::fail_with_code(
+                    UCode::NOT_FOUND,
+                    format!("no pending command for request [id: {request_id}]"),
+                )
+            })?;
+            Request::post(format!(
+                "{}{}",
+                self.adapter_uri,
+                command::response_path(&hono_request_id)
+            ))
+            .header(
+                command::HEADER_COMMAND_STATUS,
+                hono_wire_format::command::response_status(attributes),
+            )
+        } else {
+            let path = if is_event(attributes, self.event_priority) {
+                PATH_EVENT
+            } else {
+                PATH_TELEMETRY
+            };
+            Request::post(format!("{}{}", self.adapter_uri, path))
+        };
+        request_builder
+            .header("content-type", self.wire_format.content_type())
+            .body(payload)
+            .map_err(|e| UStatus::fail_with_code(UCode::INTERNAL, e.to_string()))
+    }
+
+    /// Dispatches the command piggy-backed on a response from the HTTP adapter
+    /// to the registered listeners.
+    async fn dispatch_command(
+        mut response: Response<AsyncBody>,
+        local_transport: &LocalTransport,
+        pending_commands: &PendingRequests<String>,
+    ) {
+        let Some(command_name) = response
+            .headers()
+            .get(command::HEADER_COMMAND)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+        else {
+            return;
+        };
+        let hono_request_id = response
+            .headers()
+            .get(command::HEADER_COMMAND_REQUEST_ID)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let content_type = response
+            .headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let umessage = match response.bytes().await {
+            Ok(payload) => hono_wire_format::decode(content_type.as_deref(), &payload, None),
+            Err(e) => Err(e.to_string()),
+        };
+        match umessage {
+            Ok(umessage) => {
+                debug!(
+                    "received command [name: {}, request ID: {}]",
+                    command_name,
+                    hono_request_id.as_deref().unwrap_or("none")
+                );
+                if let (true, Some(hono_request_id)) = (umessage.is_request(), hono_request_id) {
+                    let attributes = umessage.attributes.get_or_default();
+                    pending_commands.add(
+                        attributes.id.get_or_default().to_hyphenated_string(),
+                        hono_request_id,
+                        attributes.ttl,
+                    );
+                }
+                let _ = local_transport.send(umessage).await;
+            }
+            Err(e) => {
+                warn!(
+                    "ignoring command [name: {}] not containing a uProtocol message: {}",
+                    command_name, e
+                );
+            }
+        }
+    }
+}
+
+impl Drop for HonoHttpTransport {
+    fn drop(&mut self) {
+        if let Some(command_poller) = self
+            .command_poller
+            .get_mut()
+            .ok()
+            .and_then(|command_poller| command_poller.take())
+        {
+            command_poller.abort();
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl UTransport for HonoHttpTransport {
+    async fn send(&self, message: UMessage) -> Result<(), UStatus> {
+        let attributes = message.attributes.get_or_default().clone();
+        let payload = self.wire_format.encode(message)?;
+        let request = self.create_request(&attributes, payload)?;
+        let uri = request.uri().to_string();
+        match self.http_client.send_async(request).await {
+            Ok(response) if response.status().is_success() => {
+                debug!(
+                    "successfully posted message to HTTP adapter [uri: {}, status: {}]",
+                    uri,
+                    response.status()
+                );
+                Ok(())
+            }
+            Ok(response) => {
+                warn!(
+                    "HTTP adapter rejected message [uri: {}, status: {}]",
+                    uri,
+                    response.status()
+                );
+                Err(UStatus::fail_with_code(
+                    ucode_for_status(response.status().as_u16()),
+                    format!("HTTP adapter responded with status {}", response.status()),
+                ))
+            }
+            Err(e) => {
+                warn!(
+                    "error posting message to HTTP adapter [uri: {}]: {}",
+                    uri, e
+                );
+                Err(UStatus::fail_with_code(UCode::UNAVAILABLE, e.to_string()))
+            }
+        }
+    }
+
+    async fn register_listener(
+        &self,
+        source_filter: &UUri,
+        sink_filter: Option<&UUri>,
+        listener: Arc<dyn UListener>,
+    ) -> Result<(), UStatus> {
+        self.local_transport
+            .register_listener(source_filter, sink_filter, listener)
+            .await?;
+        // commands can only be dispatched once a listener has been registered
+        self.start_polling_commands();
+        Ok(())
+    }
+
+    async fn unregister_listener(
+        &self,
+        source_filter: &UUri,
+        sink_filter: Option<&UUri>,
+        listener: Arc<dyn UListener>,
+    ) -> Result<(), UStatus> {
+        self.local_transport
+            .unregister_listener(source_filter, sink_filter, listener)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            mpsc, Mutex,
+        },
+        thread,
+    };
+
+    use clap::{Command, FromArgMatches};
+    use tokio::sync::Notify;
+    use up_rust::UMessageBuilder;
+
+    use super::*;
+
+    struct RecordedRequest {
+        request_line: String,
+        headers: Vec<(String, String)>,
+    }
+
+    impl RecordedRequest {
+        fn header(&self, name: &str) -> Option<&str> {
+            self.headers
+                .iter()
+                .find(|(n, _v)| n.eq_ignore_ascii_case(name))
+                .map(|(_n, v)| v.as_str())
+        }
+
+        fn is_command_poll(&self) -> bool {
+            self.header("content-type") == Some(command::CONTENT_TYPE_EMPTY_NOTIFICATION)
+        }
+    }
+
+    /// Starts a minimal stand-in for Hono's HTTP adapter which answers requests with the
+    /// response created by the given function. Requests that the function does not create
+    /// a response for are left pending, like the adapter does while waiting for a command.
+    fn start_http_adapter<F>(respond: F) -> (String, mpsc::Receiver<RecordedRequest>)
+    where
+        F: Fn(&RecordedRequest) -> Option<Vec<u8>> + Send + Sync + 'static,
+    {
+        let server = TcpListener::bind("127.0.0.1:0").expect("failed to bind to local port");
+        let address = server.local_addr().unwrap();
+        let (tx, rx) = mpsc::channel();
+        let respond = Arc::new(respond);
+        thread::spawn(move || {
+            for stream in server.incoming() {
+                let Ok(mut stream) = stream else {
+                    break;
+                };
+                let tx = tx.clone();
+                let respond = respond.clone();
+                thread::spawn(move || {
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut request_line = String::new();
+                    reader.read_line(&mut request_line).unwrap();
+                    let mut headers = vec![];
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        let line = line.trim_end();
+                        if line.is_empty() {
+                            break;
+                        }
+                        if let Some((name, value)) = line.split_once(':') {
+                            headers.push((name.trim().to_string(), value.trim().to_string()));
+                        }
+                    }
+                    let recorded_request = RecordedRequest {
+                        request_line: request_line.trim_end().to_string(),
+                        headers,
+                    };
+                    let content_length = recorded_request
+                        .header("content-length")
+                        .and_then(|v| v.parse::<usize>().ok())
+                        .unwrap_or(0);
+                    let mut body = vec![0u8; content_length];
+                    reader.read_exact(&mut body).unwrap();
+                    match respond(&recorded_request) {
+                        Some(response) => {
+                            stream.write_all(&response).unwrap();
+                            stream.flush().unwrap();
+                        }
+                        None => thread::sleep(Duration::from_secs(60)),
+                    }
+                    let _ = tx.send(recorded_request);
+                });
+            }
+        });
+        (format!("http://{}", address), rx)
+    }
+
+    fn http_response(status_line: &str, headers: &[(&str, &str)], body: &[u8]) -> Vec<u8> {
+        let mut response = format!("HTTP/1.1 {}\r\n", status_line);
+        for (name, value) in headers {
+            response.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        response.push_str(&format!(
+            "content-length: {}\r\nconnection: close\r\n\r\n",
+            body.len()
+        ));
+        let mut response = response.into_bytes();
+        response.extend_from_slice(body);
+        response
+    }
+
+    struct RecordingListener {
+        requests: Mutex<Vec<UMessage>>,
+        received: Notify,
+    }
+
+    #[async_trait::async_trait]
+    impl UListener for RecordingListener {
+        async fn on_receive(&self, msg: UMessage) {
+            self.requests.lock().unwrap().push(msg);
+            self.received.notify_one();
+        }
+    }
+
+    fn config(args: &[&str]) -> HonoHttpTransportConfig {
+        let command = HonoHttpTransportConfig::augment_args(Command::new("hono-http"));
+        let matches = command.get_matches_from(args);
+        HonoHttpTransportConfig::from_arg_matches(&matches).unwrap()
+    }
+
+    fn status_message(priority: UPriority) -> UMessage {
+        let topic = UUri::try_from_parts("vehicle", 0x1000, 0x01, 0x8000).unwrap();
+        UMessageBuilder::publish(topic)
+            .with_priority(priority)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_send_posts_messages_to_telemetry_or_event_endpoint() {
+        let (adapter_uri, requests) =
+            start_http_adapter(|_request| Some(http_response("202 Accepted", &[], &[])));
+        let transport = HonoHttpTransport::new(&config(&[
+            "hono-http",
+            "--http-adapter-uri",
+            adapter_uri.as_str(),
+            "--http-username",
+            "device@tenant",
+            "--http-password",
+            "secret",
+            "--wire-format",
+            "cloudevents-json",
+        ]))
+        .unwrap();
+
+        let telemetry = status_message(UPriority::UPRIORITY_CS1);
+        assert!(transport.send(telemetry).await.is_ok());
+        let request = requests.recv().unwrap();
+        assert_eq!(request.request_line, "POST /telemetry HTTP/1.1");
+        assert_eq!(
+            request.header("content-type"),
+            Some(hono_wire_format::CONTENT_TYPE_CLOUDEVENTS_JSON)
+        );
+        assert_eq!(
+            request.header("authorization"),
+            Some("Basic ZGV2aWNlQHRlbmFudDpzZWNyZXQ=")
+        );
+        assert!(request
+            .header(command::HEADER_TIME_TILL_DISCONNECT)
+            .is_none());
+
+        let event = status_message(UPriority::UPRIORITY_CS2);
+        assert!(transport.send(event).await.is_ok());
+        let request = requests.recv().unwrap();
+        assert_eq!(request.request_line, "POST /event HTTP/1.1");
+        assert!(request
+            .header(command::HEADER_TIME_TILL_DISCONNECT)
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_send_maps_error_status_to_ucode() {
+        let (adapter_uri, _requests) =
+            start_http_adapter(|_request| Some(http_response("403 Forbidden", &[], &[])));
+        let transport = HonoHttpTransport::new(&config(&[
+            "hono-http",
+            "--http-adapter-uri",
+            adapter_uri.as_str(),
+        ]))
+        .unwrap();
+
+        let result = transport
+            .send(status_message(UPriority::UPRIORITY_CS1))
+            .await;
+        assert_eq!(result.unwrap_err().get_code(), UCode::PERMISSION_DENIED);
+        assert_eq!(ucode_for_status(413), UCode::INVALID_ARGUMENT);
+        assert_eq!(ucode_for_status(429), UCode::RESOURCE_EXHAUSTED);
+        assert_eq!(ucode_for_status(503), UCode::UNAVAILABLE);
+        assert_eq!(ucode_for_status(500), UCode::INTERNAL);
+    }
+
+    #[tokio::test]
+    async fn test_polled_command_is_dispatched_and_answered() {
+        let method = UUri::try_from_parts("vehicle", 0x1000, 0x01, 0x0001).unwrap();
+        let reply_to = UUri::try_from_parts("backend", 0x2000, 0x01, 0x0000).unwrap();
+        let command = UMessageBuilder::request(method.clone(), reply_to, 10_000)
+            .build()
+            .unwrap();
+        let command_event = WireFormat::CloudEventsProtobuf.encode(command).unwrap();
+        let polls = AtomicUsize::new(0);
+        let (adapter_uri, requests) = start_http_adapter(move |request| {
+            if !request.is_command_poll() {
+                return Some(http_response("202 Accepted", &[], &[]));
+            }
+            // only the first poll request yields a command
+            (polls.fetch_add(1, Ordering::SeqCst) == 0).then(|| {
+                http_response(
+                    "200 OK",
+                    &[
+                        (
+                            "content-type",
+                            WireFormat::CloudEventsProtobuf.content_type(),
+                        ),
+                        (command::HEADER_COMMAND, "uprotocol"),
+                        (command::HEADER_COMMAND_REQUEST_ID, "cmd-1"),
+                    ],
+                    &command_event,
+                )
+            })
+        });
+
+        let listener = Arc::new(RecordingListener {
+            requests: Mutex::new(vec![]),
+            received: Notify::new(),
+        });
+        let transport = HonoHttpTransport::new(&config(&[
+            "hono-http",
+            "--http-adapter-uri",
+            adapter_uri.as_str(),
+            "--ttd",
+            "5",
+        ]))
+        .unwrap();
+        assert!(transport.command_poller.lock().unwrap().is_none());
+        transport
+            .register_listener(&UUri::any(), Some(&method), listener.clone())
+            .await
+            .unwrap();
+
+        listener.received.notified().await;
+        let request = requests.recv().unwrap();
+        assert!(request.is_command_poll());
+        assert_eq!(request.request_line, "POST /telemetry HTTP/1.1");
+        assert_eq!(
+            request.header(command::HEADER_TIME_TILL_DISCONNECT),
+            Some("5")
+        );
+        let received_command = listener.requests.lock().unwrap().pop().unwrap();
+        assert!(received_command.is_request());
+
+        // regular messages are not held back while polling for commands
+        assert!(transport
+            .send(status_message(UPriority::UPRIORITY_CS1))
+            .await
+            .is_ok());
+        let request = requests.recv().unwrap();
+        assert!(!request.is_command_poll());
+        assert!(request
+            .header(command::HEADER_TIME_TILL_DISCONNECT)
+            .is_none());
+
+        let response =
+            UMessageBuilder::response_for_request(received_command.attributes.get_or_default())
+                .build()
+                .unwrap();
+        assert!(transport.send(response).await.is_ok());
+        let request = requests.recv().unwrap();
+        assert_eq!(request.request_line, "POST /command/res/cmd-1 HTTP/1.1");
+        assert_eq!(request.header(command::HEADER_COMMAND_STATUS), Some("200"));
+    }
+}
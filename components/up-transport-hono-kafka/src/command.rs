@@ -0,0 +1,118 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Support for sending commands to devices via Hono's Kafka based Command & Control API.
+//!
+//! Commands are sent to the `hono.command.${tenant}` topic, using the identifier of the device
+//! as the record's key. Responses to commands are received on the `hono.command_response.${tenant}`
+//! topic and are correlated with the command by means of the `correlation-id` header.
+
+use hono_wire_format::command::PendingRequests;
+use up_rust::{UAttributes, UCode, UMessage, UMessageBuilder};
+
+pub(crate) const HEADER_CONTENT_TYPE: &str = "content-type";
+pub(crate) const HEADER_CORRELATION_ID: &str = "correlation-id";
+pub(crate) const HEADER_DEVICE_ID: &str = "device_id";
+pub(crate) const HEADER_RESPONSE_REQUIRED: &str = "response-required";
+pub(crate) const HEADER_STATUS: &str = "status";
+pub(crate) const HEADER_SUBJECT: &str = "subject";
+
+/// Gets the name of the topic to send commands to devices of a tenant to.
+pub(crate) fn command_topic(tenant_id: &str) -> String {
+    format!("hono.command.{}", tenant_id)
+}
+
+/// Gets the name of the topic that responses to commands sent to devices of a tenant are received on.
+pub(crate) fn command_response_topic(tenant_id: &str) -> String {
+    format!("hono.command_response.{}", tenant_id)
+}
+
+/// Maps the status code of a command response to a uProtocol status code.
+fn ucode_for_status(status: u16) -> UCode {
+    match status {
+        200..=299 => UCode::OK,
+        400 => UCode::INVALID_ARGUMENT,
+        401 => UCode::UNAUTHENTICATED,
+        403 => UCode::PERMISSION_DENIED,
+        404 => UCode::NOT_FOUND,
+        408 | 504 => UCode::DEADLINE_EXCEEDED,
+        409 => UCode::ALREADY_EXISTS,
+        429 => UCode::RESOURCE_EXHAUSTED,
+        501 => UCode::UNIMPLEMENTED,
+        503 => UCode::UNAVAILABLE,
+        _ => UCode::INTERNAL,
+    }
+}
+
+/// Creates a uProtocol response message for a command response that does not
+/// contain a uProtocol message itself, e.g. because the command could not be
+/// delivered to the device.
+///
+/// The pending requests are keyed by the correlation ID of the command, which is the ID
+/// of the uProtocol request message.
+///
+/// Returns `None` if no (unexpired) request exists for the given correlation ID.
+pub(crate) fn response_for_status(
+    pending_requests: &PendingRequests<UAttributes>,
+    correlation_id: &str,
+    status: u16,
+) -> Option<UMessage> {
+    let request_attributes = pending_requests.remove(correlation_id)?;
+    UMessageBuilder::response_for_request(&request_attributes)
+        .with_comm_status(ucode_for_status(status))
+        .build()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use up_rust::UUri;
+
+    use super::*;
+
+    #[test]
+    fn test_response_for_status_uses_pending_request() {
+        let method = UUri::from_str("//vehicle/1000/1/1").unwrap();
+        let reply_to = UUri::from_str("//backend/2000/1/0").unwrap();
+        let request = UMessageBuilder::request(method, reply_to, 5_000)
+            .build()
+            .unwrap();
+        let attributes = request.attributes.get_or_default();
+        let correlation_id = attributes.id.get_or_default().to_hyphenated_string();
+
+        let pending_requests = PendingRequests::default();
+        assert!(response_for_status(&pending_requests, &correlation_id, 503).is_none());
+
+        pending_requests.add(correlation_id.clone(), attributes.clone(), attributes.ttl);
+        let response = response_for_status(&pending_requests, &correlation_id, 503).unwrap();
+        let response_attributes = response.attributes.get_or_default();
+        assert!(response.is_response());
+        assert_eq!(response_attributes.reqid, attributes.id);
+        assert_eq!(
+            response_attributes
+                .commstatus
+                .map(|s| s.enum_value_or_default()),
+            Some(UCode::UNAVAILABLE)
+        );
+        // the request is no longer pending
+        assert!(pending_requests.remove(&correlation_id).is_none());
+    }
+}
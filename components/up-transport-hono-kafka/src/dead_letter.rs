@@ -0,0 +1,253 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Support for keeping track of messages that have been rejected because they do not
+//! contain a (decodable) uProtocol message.
+//!
+//! Rejected messages are counted per reason and can be forwarded to a dead-letter Kafka topic
+//! or a local file for later analysis, e.g. in order to identify misbehaving devices.
+
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs::{File, OpenOptions},
+    io::Write,
+    path::Path,
+    sync::Mutex,
+    time::Duration,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rdkafka::{
+    message::{BorrowedMessage, Header, Headers, OwnedHeaders},
+    producer::{FutureProducer, FutureRecord},
+    util::Timeout,
+    Message,
+};
+use serde_json::{json, Map, Value};
+
+pub(crate) const HEADER_REJECTION_REASON: &str = "rejection-reason";
+pub(crate) const HEADER_SOURCE_OFFSET: &str = "source-offset";
+pub(crate) const HEADER_SOURCE_PARTITION: &str = "source-partition";
+pub(crate) const HEADER_SOURCE_TOPIC: &str = "source-topic";
+
+// the maximum time to wait for a rejected message to be enqueued if the producer's queue is full
+const DEAD_LETTER_QUEUE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The reasons for rejecting a message received from Hono.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum RejectionReason {
+    /// The message does not contain any headers.
+    MissingHeaders,
+    /// The message does not contain a device ID.
+    MissingDeviceId,
+    /// The message does not contain a content type.
+    MissingContentType,
+    /// The message does not contain a payload.
+    MissingPayload,
+    /// The message's content type is not supported.
+    UnsupportedContentType,
+    /// The message contains a raw payload but no default topic has been configured.
+    MissingDefaultTopic,
+    /// The message's payload could not be decoded.
+    MalformedPayload,
+}
+
+impl RejectionReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RejectionReason::MissingHeaders => "missing-headers",
+            RejectionReason::MissingDeviceId => "missing-device-id",
+            RejectionReason::MissingContentType => "missing-content-type",
+            RejectionReason::MissingPayload => "missing-payload",
+            RejectionReason::UnsupportedContentType => "unsupported-content-type",
+            RejectionReason::MissingDefaultTopic => "missing-default-topic",
+            RejectionReason::MalformedPayload => "malformed-payload",
+        }
+    }
+}
+
+impl Display for RejectionReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Counts the messages that have been rejected, per reason.
+#[derive(Default)]
+pub(crate) struct RejectionCounters {
+    counts: Mutex<HashMap<RejectionReason, u64>>,
+}
+
+impl RejectionCounters {
+    /// Increments the number of messages rejected for a reason.
+    ///
+    /// Returns the new number of messages rejected for the reason.
+    pub fn increment(&self, reason: RejectionReason) -> u64 {
+        self.counts.lock().map_or(0, |mut counts| {
+            let count = counts.entry(reason).or_default();
+            *count += 1;
+            *count
+        })
+    }
+
+    /// Gets the number of messages that have been rejected, keyed by reason.
+    pub fn snapshot(&self) -> HashMap<String, u64> {
+        self.counts
+            .lock()
+            .map(|counts| {
+                counts
+                    .iter()
+                    .map(|(reason, count)| (reason.to_string(), *count))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// A destination for rejected messages.
+pub(crate) enum DeadLetterSink {
+    /// A Kafka topic that rejected messages are produced to with their original key, payload
+    /// and headers, and additional headers indicating the reason and source of the rejection.
+    Topic {
+        producer: FutureProducer,
+        topic: String,
+    },
+    /// A file that rejected messages are appended to as JSON objects, one per line.
+    File(Mutex<File>),
+}
+
+impl DeadLetterSink {
+    /// Creates a sink that appends rejected messages to a file.
+    pub fn file<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map(|file| DeadLetterSink::File(Mutex::new(file)))
+    }
+
+    /// Forwards a rejected message to this sink.
+    pub async fn send(
+        &self,
+        m: &BorrowedMessage<'_>,
+        reason: RejectionReason,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            DeadLetterSink::Topic { producer, topic } => {
+                let partition = m.partition().to_string();
+                let offset = m.offset().to_string();
+                let mut headers = OwnedHeaders::new();
+                if let Some(original_headers) = m.headers() {
+                    for header in original_headers.iter() {
+                        headers = headers.insert(Header {
+                            key: header.key,
+                            value: header.value,
+                        });
+                    }
+                }
+                let headers = headers
+                    .insert(Header {
+                        key: HEADER_REJECTION_REASON,
+                        value: Some(reason.as_str()),
+                    })
+                    .insert(Header {
+                        key: HEADER_SOURCE_TOPIC,
+                        value: Some(m.topic()),
+                    })
+                    .insert(Header {
+                        key: HEADER_SOURCE_PARTITION,
+                        value: Some(&partition),
+                    })
+                    .insert(Header {
+                        key: HEADER_SOURCE_OFFSET,
+                        value: Some(&offset),
+                    });
+                let mut record = FutureRecord::<[u8], [u8]>::to(topic).headers(headers);
+                if let Some(key) = m.key() {
+                    record = record.key(key);
+                }
+                if let Some(payload) = m.payload() {
+                    record = record.payload(payload);
+                }
+                producer
+                    .send(record, Timeout::After(DEAD_LETTER_QUEUE_TIMEOUT))
+                    .await
+                    .map(|_delivery| ())
+                    .map_err(|(e, _message)| Box::new(e) as Box<dyn std::error::Error>)
+            }
+            DeadLetterSink::File(file) => {
+                let mut line = serde_json::to_vec(&to_json(m, reason))?;
+                line.push(b'\n');
+                let mut file = file
+                    .lock()
+                    .map_err(|_e| "dead-letter file lock is poisoned")?;
+                file.write_all(&line)?;
+                file.flush().map_err(Box::from)
+            }
+        }
+    }
+}
+
+/// Creates a JSON representation of a rejected message.
+///
+/// Header values and the payload are represented as UTF-8 strings if possible,
+/// otherwise they are Base64 encoded.
+fn to_json(m: &BorrowedMessage<'_>, reason: RejectionReason) -> Value {
+    let encode = |bytes: &[u8]| match std::str::from_utf8(bytes) {
+        Ok(text) => json!(text),
+        Err(_) => json!({ "base64": STANDARD.encode(bytes) }),
+    };
+    let mut headers = Map::new();
+    if let Some(original_headers) = m.headers() {
+        for header in original_headers.iter() {
+            headers.insert(
+                header.key.to_string(),
+                header.value.map_or(Value::Null, encode),
+            );
+        }
+    }
+    json!({
+        HEADER_REJECTION_REASON: reason.as_str(),
+        HEADER_SOURCE_TOPIC: m.topic(),
+        HEADER_SOURCE_PARTITION: m.partition(),
+        HEADER_SOURCE_OFFSET: m.offset(),
+        "key": m.key().map_or(Value::Null, encode),
+        "headers": headers,
+        "payload": m.payload().map_or(Value::Null, encode),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejection_counters_count_per_reason() {
+        let counters = RejectionCounters::default();
+        assert_eq!(counters.increment(RejectionReason::MissingDeviceId), 1);
+        assert_eq!(counters.increment(RejectionReason::MissingDeviceId), 2);
+        assert_eq!(counters.increment(RejectionReason::MalformedPayload), 1);
+
+        let snapshot = counters.snapshot();
+        assert_eq!(snapshot.get("missing-device-id"), Some(&2));
+        assert_eq!(snapshot.get("malformed-payload"), Some(&1));
+        assert_eq!(snapshot.get("missing-payload"), None);
+    }
+}
@@ -19,36 +19,61 @@
 
 use std::{
     collections::HashMap,
-    fs::File,
-    io::{BufRead, BufReader},
+    future::Future,
     path::PathBuf,
-    sync::Arc,
-    time::Duration,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use clap::Args;
-use futures::TryStreamExt;
-use log::{debug, error, info, trace};
-use protobuf::Message as ProtoMessage;
+use futures::StreamExt;
+use hono_wire_format::{command::PendingRequests, WireFormat};
+use log::{debug, error, info, trace, warn};
 use rdkafka::{
     config::RDKafkaLogLevel,
-    consumer::{Consumer, StreamConsumer},
-    message::{BorrowedHeaders, BorrowedMessage, Headers},
+    consumer::{CommitMode, Consumer, StreamConsumer},
+    error::{KafkaError, RDKafkaErrorCode},
+    message::{BorrowedHeaders, BorrowedMessage, Header, Headers, OwnedHeaders},
+    producer::{FutureProducer, FutureRecord},
+    util::Timeout,
     ClientConfig, Message,
 };
+use tokio::{sync::Notify, task::JoinHandle, time::Interval};
 use up_rust::{
-    local_transport::LocalTransport, CloudEvent, UCode, UListener, UMessage, UStatus, UTransport,
+    local_transport::LocalTransport, UAttributes, UCode, UListener, UMessage, UStatus, UTransport,
     UUri,
 };
 
+use crate::dead_letter::{DeadLetterSink, RejectionCounters, RejectionReason};
+use crate::tenant::TopicClass;
+
+mod command;
+mod dead_letter;
+mod notification;
+pub mod processing;
+pub mod tenant;
+
 const HEADER_NAME_ORIG_ADDRESS: &str = "orig_address";
 
+const PARAM_CONNECTION_STATUS_TOPIC: &str = "connection-status-topic";
+const PARAM_DEAD_LETTER_FILE: &str = "dead-letter-file";
+const PARAM_DEAD_LETTER_TOPIC: &str = "dead-letter-topic";
+const PARAM_DEFAULT_TOPIC: &str = "default-topic";
+const PARAM_KAFKA_COMMIT_INTERVAL: &str = "kafka-commit-interval";
+const PARAM_KAFKA_MANUAL_COMMIT: &str = "kafka-manual-commit";
+const PARAM_KAFKA_PROPERTY: &str = "kafka-property";
 const PARAM_KAFKA_PROPERTIES_FILE: &str = "kafka-properties-file";
 const PARAM_HONO_TENANT_ID: &str = "hono-tenant-id";
+const PARAM_HONO_TOPICS: &str = "hono-topics";
+const PARAM_WIRE_FORMAT: &str = "wire-format";
+
+// the maximum time to wait for a command to be enqueued if the producer's queue is full
+const COMMAND_QUEUE_TIMEOUT: Duration = Duration::from_secs(5);
 
-// TODO: this should be replaced with a constant from up-rust
-// see https://github.com/eclipse-uprotocol/up-rust/issues/223
-const MIME_TYPE_CLOUDEVENTS_PROTOBUF: &str = "application/cloudevents+protobuf";
+// the delays between attempts to process a message that a listener has failed to process
+const MIN_RETRY_DELAY: Duration = Duration::from_millis(500);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
 
 fn add_property_bag_to_map(property_bag: String, headers: &mut HashMap<String, String>) {
     property_bag.split('&').for_each(|p| {
@@ -91,103 +116,425 @@ fn get_headers_as_map(headers: &BorrowedHeaders) -> HashMap<String, String> {
     result
 }
 
-fn extract_umessage_from_cloudevent(payload: &[u8]) -> Option<UMessage> {
-    match CloudEvent::parse_from_bytes(payload) {
-        Ok(cloudevent) => match UMessage::try_from(cloudevent) {
-            Err(e) => {
-                info!("failed to extract payload from CloudEvent: {}", e);
-                None
-            }
-            Ok(msg) => Some(msg),
-        },
-        Err(e) => {
-            info!("failed to deserialize CloudEvent: {}", e);
-            None
-        }
-    }
+/// Gets the instant of time (milliseconds since UNIX epoch) at which a message has been created.
+fn creation_time_millis(
+    m: &BorrowedMessage<'_>,
+    message_properties: &HashMap<String, String>,
+) -> i64 {
+    message_properties
+        .get(notification::HEADER_CREATION_TIME)
+        .and_then(|creation_time| creation_time.parse().ok())
+        .or_else(|| m.timestamp().to_millis())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |now| now.as_millis() as i64)
+        })
 }
 
-fn extract_umessage_from_kafka_message(m: &BorrowedMessage<'_>) -> Option<UMessage> {
-    if let Some(headers) = m.headers() {
-        let message_properties = get_headers_as_map(headers);
+/// Extracts the uProtocol message from a Kafka message.
+///
+/// Returns `None` for empty notifications, which do not contain a uProtocol message.
+fn extract_umessage_from_kafka_message(
+    m: &BorrowedMessage<'_>,
+    default_topic: Option<&UUri>,
+    connection_status_topic: &UUri,
+) -> Result<Option<UMessage>, RejectionReason> {
+    let Some(headers) = m.headers() else {
+        debug!("ignoring message without headers");
+        return Err(RejectionReason::MissingHeaders);
+    };
+    let message_properties = get_headers_as_map(headers);
 
-        if let Some(device_id) = message_properties.get("device_id") {
-            debug!("received message from vehicle {}", device_id);
-        } else {
-            debug!("discarding message from unknown device");
-            return None;
-        };
+    let Some(device_id) = message_properties.get(command::HEADER_DEVICE_ID) else {
+        debug!("discarding message from unknown device");
+        return Err(RejectionReason::MissingDeviceId);
+    };
+    debug!("received message from vehicle {}", device_id);
+    let authority = tenant::device_authority(tenant::tenant_of_topic(m.topic()), device_id);
 
-        match (
-            message_properties.get("content-type").map(String::as_str),
-            m.payload(),
-        ) {
-            (Some(MIME_TYPE_CLOUDEVENTS_PROTOBUF), Some(payload)) => {
-                debug!("received message containing CloudEvent");
-                extract_umessage_from_cloudevent(payload)
-            }
-            (_, None) => {
-                debug!("ignoring message without payload");
-                None
+    let content_type = message_properties
+        .get(command::HEADER_CONTENT_TYPE)
+        .map(String::as_str);
+    if content_type.is_some_and(notification::is_empty_notification) {
+        debug!("ignoring empty notification");
+        return Ok(None);
+    }
+    let umessage = match (content_type, m.payload()) {
+        (Some(content_type), Some(payload))
+            if notification::is_connection_notification(content_type) =>
+        {
+            debug!("received connection notification");
+            notification::connection_status_message(
+                device_id,
+                creation_time_millis(m, &message_properties),
+                payload,
+                connection_status_topic,
+            )
+            .map_err(|e| {
+                info!("ignoring connection notification: {e}");
+                RejectionReason::MalformedPayload
+            })
+        }
+        (Some(content_type), Some(payload)) => {
+            debug!("received message [content-type: {content_type}]");
+            match WireFormat::from_content_type(content_type) {
+                None => {
+                    info!("ignoring message with unsupported content type [{content_type}]");
+                    Err(RejectionReason::UnsupportedContentType)
+                }
+                Some(WireFormat::Protobuf) if default_topic.is_none() => {
+                    info!(
+                        "ignoring message with raw payload because no default topic has been set"
+                    );
+                    Err(RejectionReason::MissingDefaultTopic)
+                }
+                Some(_wire_format) => {
+                    hono_wire_format::decode(Some(content_type), payload, default_topic).map_err(
+                        |e| {
+                            info!("ignoring message: {e}");
+                            RejectionReason::MalformedPayload
+                        },
+                    )
+                }
             }
-            _ => None,
         }
-    } else {
-        debug!("ignoring message without headers");
-        None
-    }
+        (None, Some(_payload)) => {
+            debug!("ignoring message without content type");
+            Err(RejectionReason::MissingContentType)
+        }
+        (_, None) => {
+            debug!("ignoring message without payload");
+            Err(RejectionReason::MissingPayload)
+        }
+    };
+
+    umessage.map(|msg| Some(with_source_authority(msg, authority)))
+}
+
+/// Sets the authority of a message's source URI to the authority identifying the device that has sent
+/// the message, i.e. `<device-id>.<tenant-id>`.
+///
+/// The device ID has been authenticated by Hono, so consumers can rely on the source authority
+/// instead of on the (unverified) source URI that has been set by the device.
+fn with_source_authority(mut msg: UMessage, authority: String) -> UMessage {
+    msg.attributes
+        .mut_or_insert_default()
+        .source
+        .mut_or_insert_default()
+        .authority_name = authority;
+    msg
+}
+
+/// Creates a uProtocol response message for a command response that does not contain
+/// a uProtocol message itself, e.g. because the command could not be delivered to the device.
+fn response_for_failed_command(
+    m: &BorrowedMessage<'_>,
+    pending_requests: &PendingRequests<UAttributes>,
+) -> Option<UMessage> {
+    let message_properties = get_headers_as_map(m.headers()?);
+    let correlation_id = message_properties.get(command::HEADER_CORRELATION_ID)?;
+    let status = message_properties
+        .get(command::HEADER_STATUS)
+        .and_then(|status| status.parse::<u16>().ok())?;
+    debug!("received command response [correlation-id: {correlation_id}, status: {status}]");
+    command::response_for_status(pending_requests, correlation_id, status)
 }
 
 #[derive(Args)]
 pub struct HonoKafkaTransportConfig {
     /// The path to a file containing Kafka client properties for connecting to Hono's Kafka broker(s).
-    #[arg(long = PARAM_KAFKA_PROPERTIES_FILE, value_name = "PATH", env = "KAFKA_PROPERTIES_FILE", value_parser = clap::builder::PathBufValueParser::new())]
-    kafka_property_file: PathBuf,
+    /// The file is expected to be in Java properties format. Property values may contain references
+    /// to environment variables of the form ${VAR_NAME}.
+    #[arg(long = PARAM_KAFKA_PROPERTIES_FILE, value_name = "PATH", env = "KAFKA_PROPERTIES_FILE", required_unless_present = PARAM_KAFKA_PROPERTY, value_parser = clap::builder::PathBufValueParser::new())]
+    kafka_property_file: Option<PathBuf>,
+
+    /// A Kafka client property to set, overriding the value from the properties file (if any).
+    /// May be used multiple times. The value may contain references to environment variables
+    /// of the form ${VAR_NAME}.
+    #[arg(id = PARAM_KAFKA_PROPERTY, long = PARAM_KAFKA_PROPERTY, value_name = "KEY=VALUE", value_parser = config_properties::parse_key_value)]
+    kafka_properties: Vec<(String, String)>,
+
+    /// The identifiers of the Hono tenants to consume messages for, separated by commas.
+    /// May also be used multiple times. Tenant identifiers must not contain any dots.
+    #[arg(long = PARAM_HONO_TENANT_ID, value_name = "ID", env = "HONO_TENANT_ID", required = true, value_delimiter = ',', value_parser = tenant::parse_tenant_id)]
+    hono_tenant_ids: Vec<String>,
 
-    /// The identifier of the Hono tenant to consume messages for.
-    #[arg(long = PARAM_HONO_TENANT_ID, value_name = "ID", env = "HONO_TENANT_ID")]
-    hono_tenant_id: String,
+    /// The classes of Hono topics to consume messages from, separated by commas.
+    /// Responses to commands can only be received if the command-response topics are consumed.
+    #[arg(long = PARAM_HONO_TOPICS, value_name = "CLASS", env = "HONO_TOPICS", value_enum, value_delimiter = ',', default_values_t = [TopicClass::Telemetry, TopicClass::Event, TopicClass::CommandResponse])]
+    hono_topics: Vec<TopicClass>,
+
+    /// The topic to publish messages to that contain a raw (protobuf) VehicleStatus instead of a
+    /// uProtocol message, e.g. messages from third-party devices. Such messages are ignored
+    /// if not set.
+    #[arg(long = PARAM_DEFAULT_TOPIC, value_name = "URI", env = "DEFAULT_TOPIC", value_parser = UUri::from_str)]
+    default_topic: Option<UUri>,
+
+    /// The topic to publish the connection status of devices to, as reported by Hono's device
+    /// connection notifications. The authority of the topic is replaced with the device's ID.
+    #[arg(long = PARAM_CONNECTION_STATUS_TOPIC, value_name = "URI", env = "CONNECTION_STATUS_TOPIC", default_value = "up://hono/D102/1/D102", value_parser = UUri::from_str)]
+    connection_status_topic: UUri,
+
+    /// The format to send commands to devices in.
+    #[arg(long = PARAM_WIRE_FORMAT, value_name = "FORMAT", env = "WIRE_FORMAT", value_enum, default_value_t = WireFormat::CloudEventsProtobuf)]
+    wire_format: WireFormat,
+
+    /// Indicates whether the offsets of received messages should only be committed after all listeners
+    /// have finished processing them. Otherwise, offsets are committed automatically by the Kafka client,
+    /// possibly before the messages have been processed.
+    #[arg(long = PARAM_KAFKA_MANUAL_COMMIT, env = "KAFKA_MANUAL_COMMIT")]
+    manual_commit: bool,
+
+    /// The interval at which the offsets of processed messages are committed when using manual
+    /// offset commits, e.g. 5s or 1m.
+    #[arg(long = PARAM_KAFKA_COMMIT_INTERVAL, value_name = "DURATION_SPEC", env = "KAFKA_COMMIT_INTERVAL", default_value = "5s", value_parser = |s: &str| duration_str::parse(s))]
+    commit_interval: Duration,
+
+    /// The Kafka topic to forward messages to that do not contain a (decodable) uProtocol message.
+    /// The messages retain their original key, payload and headers and have an additional header
+    /// indicating the reason for their rejection.
+    #[arg(long = PARAM_DEAD_LETTER_TOPIC, value_name = "TOPIC", env = "DEAD_LETTER_TOPIC", conflicts_with = PARAM_DEAD_LETTER_FILE)]
+    dead_letter_topic: Option<String>,
+
+    /// The path to a file to append messages to (as JSON objects, one per line) that do not contain
+    /// a (decodable) uProtocol message.
+    #[arg(id = PARAM_DEAD_LETTER_FILE, long = PARAM_DEAD_LETTER_FILE, value_name = "PATH", env = "DEAD_LETTER_FILE", value_parser = clap::builder::PathBufValueParser::new())]
+    dead_letter_file: Option<PathBuf>,
 }
 
 impl HonoKafkaTransportConfig {
-    fn read_lines(&self) -> Result<std::io::Lines<BufReader<File>>, Box<dyn std::error::Error>> {
-        // Open the file in read-only mode.
-        match File::open(&self.kafka_property_file) {
-            Ok(file) => {
-                // Read the file line by line, and return an iterator of the lines of the file.
-                Ok(BufReader::new(file).lines())
-            }
-            Err(e) => Err(Box::new(e)),
-        }
+    /// Checks if the offsets of received messages are only committed after all listeners
+    /// have finished processing them.
+    pub fn uses_manual_commit(&self) -> bool {
+        self.manual_commit
     }
 
     fn get_kafka_client_config(&self) -> Result<ClientConfig, Box<dyn std::error::Error>> {
-        self.read_lines().map(|lines| {
-            let mut client_config = ClientConfig::new();
-            for line in lines {
-                match line {
-                    Ok(property) => match property.split_once('=') {
-                        Some((key, value)) => {
-                            client_config.set(key, value);
+        let mut properties = match self.kafka_property_file.as_ref() {
+            Some(path) => config_properties::parse_properties(&std::fs::read_to_string(path)?),
+            None => Vec::new(),
+        };
+        // properties set on the command line take precedence over the ones from the file
+        properties.extend(self.kafka_properties.iter().cloned());
+
+        let mut client_config = ClientConfig::new();
+        for (key, value) in config_properties::interpolate_env(properties)? {
+            client_config.set(key, value);
+        }
+        Ok(client_config)
+    }
+}
+
+/// Waits for the next time to commit offsets at.
+///
+/// Never completes if offsets are not committed manually.
+async fn next_commit(commit_timer: &mut Option<Interval>) {
+    match commit_timer {
+        Some(timer) => {
+            timer.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// The state of the consumption of messages from Hono's Kafka topics.
+enum ConsumerTask<C = MessageConsumer> {
+    /// The consumer has been created but does not consume any messages yet.
+    Idle(C),
+    /// The consumer is consuming messages in the background.
+    Running(JoinHandle<()>),
+}
+
+impl<C> ConsumerTask<C> {
+    /// Spawns the given consumer's main loop, unless it is already running.
+    fn start<F>(self, run: impl FnOnce(C) -> F) -> Self
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        match self {
+            ConsumerTask::Idle(consumer) => ConsumerTask::Running(tokio::spawn(run(consumer))),
+            running => running,
+        }
+    }
+}
+
+/// Consumes messages from Hono's Kafka topics and dispatches them to the registered listeners.
+struct MessageConsumer {
+    consumer: StreamConsumer,
+    local_transport: Arc<LocalTransport>,
+    pending_requests: Arc<PendingRequests<UAttributes>>,
+    default_topic: Option<UUri>,
+    connection_status_topic: UUri,
+    dead_letter_sink: Option<DeadLetterSink>,
+    rejection_counters: Arc<RejectionCounters>,
+    // the interval at which to commit offsets, if offsets are committed manually
+    commit_interval: Option<Duration>,
+    stop_signal: Arc<Notify>,
+}
+
+impl MessageConsumer {
+    async fn run(self) {
+        info!("starting message consumer");
+        let mut stream = self.consumer.stream();
+        let mut commit_timer = self.commit_interval.map(tokio::time::interval);
+        loop {
+            // consumption stops once the stop signal has been received but only after
+            // the message currently being processed has been delivered to the listeners
+            tokio::select! {
+                _ = self.stop_signal.notified() => break,
+                _ = next_commit(&mut commit_timer) => {
+                    self.commit(CommitMode::Async);
+                }
+                next = stream.next() => match next {
+                    Some(Ok(borrowed_message)) => {
+                        if !self.dispatch(&borrowed_message).await {
+                            break;
                         }
-                        None => {
-                            debug!("cannot parse line into property: {}", property);
+                        if self.commit_interval.is_some() {
+                            if let Err(e) =
+                                self.consumer.store_offset_from_message(&borrowed_message)
+                            {
+                                warn!("failed to store offset of processed message: {e}");
+                            }
                         }
-                    },
-                    Err(e) => {
-                        debug!("cannot read line from file: {e}");
                     }
+                    Some(Err(e)) => {
+                        error!("could not start consumer for Hono topics: {}", e);
+                        break;
+                    }
+                    None => break,
                 }
             }
-            client_config
-        })
+        }
+        if self.commit_interval.is_some() {
+            self.commit(CommitMode::Sync);
+        }
+        info!("message consumer has been stopped");
+    }
+
+    /// Counts a rejected message and forwards it to the dead-letter sink (if configured).
+    async fn reject(&self, m: &BorrowedMessage<'_>, reason: RejectionReason) {
+        let count = self.rejection_counters.increment(reason);
+        debug!("rejected message [reason: {reason}, total rejected for reason: {count}]");
+        if let Some(sink) = self.dead_letter_sink.as_ref() {
+            if let Err(e) = sink.send(m, reason).await {
+                warn!(
+                    "failed to forward rejected message [topic: {}, partition: {}, offset: {}] to dead-letter sink: {}",
+                    m.topic(),
+                    m.partition(),
+                    m.offset(),
+                    e
+                );
+            }
+        }
+    }
+
+    /// Commits the offsets of the messages that have been processed.
+    fn commit(&self, mode: CommitMode) {
+        match self.consumer.commit_consumer_state(mode) {
+            Ok(()) => trace!("committed offsets of processed messages"),
+            Err(KafkaError::ConsumerCommit(RDKafkaErrorCode::NoOffset)) => {
+                trace!("no new offsets to commit");
+            }
+            Err(e) => warn!("failed to commit offsets of processed messages: {e}"),
+        }
+    }
+
+    /// Dispatches a message to the registered listeners.
+    ///
+    /// When committing offsets manually, the message is dispatched again (after a delay) until
+    /// all listeners have processed it successfully.
+    ///
+    /// Returns `false` if the stop signal has been received before the message has been processed.
+    async fn dispatch(&self, m: &BorrowedMessage<'_>) -> bool {
+        let msg = match extract_umessage_from_kafka_message(
+            m,
+            self.default_topic.as_ref(),
+            &self.connection_status_topic,
+        ) {
+            Ok(Some(msg)) => msg,
+            Ok(None) => return true,
+            Err(reason) => match response_for_failed_command(m, &self.pending_requests) {
+                Some(msg) => msg,
+                None => {
+                    self.reject(m, reason).await;
+                    return true;
+                }
+            },
+        };
+        if msg.is_response() {
+            let request_id = msg
+                .attributes
+                .get_or_default()
+                .reqid
+                .get_or_default()
+                .to_hyphenated_string();
+            self.pending_requests.remove(&request_id);
+        }
+        if self.commit_interval.is_none() {
+            let _ = self.local_transport.send(msg).await;
+            return true;
+        }
+        let mut retry_delay = MIN_RETRY_DELAY;
+        loop {
+            let dispatch = async {
+                let _ = self.local_transport.send(msg.clone()).await;
+            };
+            if processing::track(dispatch).await {
+                return true;
+            }
+            warn!(
+                "listener has failed to process message [topic: {}, partition: {}, offset: {}], retrying in {:?}",
+                m.topic(),
+                m.partition(),
+                m.offset(),
+                retry_delay
+            );
+            tokio::select! {
+                _ = self.stop_signal.notified() => return false,
+                _ = tokio::time::sleep(retry_delay) => {}
+            }
+            retry_delay = (retry_delay * 2).min(MAX_RETRY_DELAY);
+        }
     }
 }
 
 /// A simple uProtocol transport implementation for receiving messages from Eclipse Hono's
 /// Apache Kafka based messaging infrastructure.
+///
+/// Messages of multiple tenants can be consumed from the telemetry, event and/or command response
+/// topics. The authority of a received message's source URI is set to `<device-id>.<tenant-id>`,
+/// identifying the (authenticated) device that has sent the message.
+///
+/// The format of a message's payload is determined from its content type. Messages may contain
+/// CloudEvents in protobuf or JSON format or a raw (protobuf) VehicleStatus. The latter are
+/// published to the configured default topic. Hono's device connection notifications are published
+/// as [`fms_proto::fms::ConnectionStatus`] messages to the configured connection status topic.
+///
+/// By default, the offsets of received messages are committed automatically by the Kafka client.
+/// With manual offset commits, the offset of a message is only committed after all listeners have
+/// processed the message. Listeners can use [`processing::report_failure`] to indicate that they
+/// have failed to process a message, which is then delivered again.
+///
+/// Messages are only consumed after [`HonoKafkaTransport::start`] has been invoked, which should
+/// be done once all listeners have been registered. Otherwise, messages might be considered to
+/// have been processed without having been delivered to any listener.
+///
+/// Messages that are sent via this transport are sent as commands to the device identified by the
+/// authority of the message's sink URI, i.e. `<device-id>.<tenant-id>`. Responses to request messages
+/// are dispatched to the registered listeners, e.g. an RPC client. If the command response does not contain a uProtocol message, e.g.
+/// because the command could not be delivered to the device, a response message is created with
+/// a communication status that corresponds to the command response's status code.
 pub struct HonoKafkaTransport {
+    producer: FutureProducer,
+    tenant_ids: Vec<String>,
+    wire_format: WireFormat,
+    pending_requests: Arc<PendingRequests<UAttributes>>,
+    rejection_counters: Arc<RejectionCounters>,
     local_transport: Arc<LocalTransport>,
+    stop_consumer: Arc<Notify>,
+    consumer_task: Mutex<Option<ConsumerTask>>,
 }
 
 impl HonoKafkaTransport {
@@ -195,22 +542,43 @@ impl HonoKafkaTransport {
         config_params: HonoKafkaTransportConfig,
     ) -> Result<Self, Box<dyn std::error::Error>> {
         let mut client_config = config_params.get_kafka_client_config()?;
+        let producer = client_config.create::<FutureProducer>()?;
+
+        let commit_interval = if config_params.manual_commit {
+            info!(
+                "committing offsets of processed messages every {:?}",
+                config_params.commit_interval
+            );
+            client_config
+                .set("enable.auto.commit", "false")
+                .set("enable.auto.offset.store", "false");
+            Some(config_params.commit_interval)
+        } else {
+            None
+        };
 
         // Create the `StreamConsumer`, to receive the messages from the topic in form of a `Stream`.
         let consumer = client_config
             .set_log_level(RDKafkaLogLevel::Debug)
             .create::<StreamConsumer>()?;
 
-        let telemetry_topic = format!("hono.telemetry.{}", config_params.hono_tenant_id);
-        let event_topic = format!("hono.event.{}", config_params.hono_tenant_id);
-        let response_topic = format!("hono.command_response.{}", config_params.hono_tenant_id);
-
-        let topics = &[
-            telemetry_topic.as_str(),
-            event_topic.as_str(),
-            response_topic.as_str(),
-        ];
-        for topic_name in topics {
+        if config_params.hono_topics.is_empty() {
+            return Err(Box::from(
+                "at least one class of Hono topics must be consumed",
+            ));
+        }
+        let topic_names: Vec<String> = config_params
+            .hono_tenant_ids
+            .iter()
+            .flat_map(|tenant_id| {
+                config_params
+                    .hono_topics
+                    .iter()
+                    .map(move |topic_class| topic_class.topic(tenant_id))
+            })
+            .collect();
+        let topics: Vec<&str> = topic_names.iter().map(String::as_str).collect();
+        for topic_name in &topics {
             match consumer.fetch_metadata(Some(topic_name), Duration::from_secs(10)) {
                 Err(e) => {
                     error!(
@@ -234,44 +602,194 @@ impl HonoKafkaTransport {
             }
         }
 
-        if let Err(e) = consumer.subscribe(topics) {
+        if let Err(e) = consumer.subscribe(&topics) {
             error!("failed to subscribe to topic: {}", e);
             return Err(Box::new(e));
         } else {
-            info!("successfully subscribed to tenant's topics");
+            info!("successfully subscribed to topics {:?}", topics);
         }
 
         let local_transport = Arc::new(LocalTransport::default());
-        let local_transport_ref = local_transport.clone();
-        tokio::spawn(async move {
-            info!("starting message consumer");
-            consumer
-                .stream()
-                .try_for_each(|borrowed_message| {
-                    let cloned_local_transport = local_transport_ref.clone();
-                    async move {
-                        if let Some(msg) = extract_umessage_from_kafka_message(&borrowed_message) {
-                            let _ = cloned_local_transport.send(msg).await;
-                        }
-                        Ok(())
-                    }
+        let pending_requests = Arc::new(PendingRequests::default());
+        let stop_consumer = Arc::new(Notify::new());
+        let dead_letter_sink = match (
+            config_params.dead_letter_topic.as_ref(),
+            config_params.dead_letter_file.as_ref(),
+        ) {
+            (Some(topic), _) => {
+                info!("forwarding rejected messages to dead-letter topic [{topic}]");
+                Some(DeadLetterSink::Topic {
+                    producer: producer.clone(),
+                    topic: topic.to_owned(),
                 })
-                .await
-                .unwrap_or_else(|e| {
-                    error!("could not start consumer for Hono topics: {}", e);
-                });
-        });
-        Ok(HonoKafkaTransport { local_transport })
+            }
+            (None, Some(path)) => {
+                info!(
+                    "appending rejected messages to dead-letter file [{}]",
+                    path.display()
+                );
+                Some(DeadLetterSink::file(path)?)
+            }
+            (None, None) => None,
+        };
+        let rejection_counters = Arc::new(RejectionCounters::default());
+        let message_consumer = MessageConsumer {
+            consumer,
+            local_transport: local_transport.clone(),
+            pending_requests: pending_requests.clone(),
+            default_topic: config_params.default_topic.clone(),
+            connection_status_topic: config_params.connection_status_topic.clone(),
+            dead_letter_sink,
+            rejection_counters: rejection_counters.clone(),
+            commit_interval,
+            stop_signal: stop_consumer.clone(),
+        };
+        Ok(HonoKafkaTransport {
+            producer,
+            tenant_ids: config_params.hono_tenant_ids,
+            wire_format: config_params.wire_format,
+            pending_requests,
+            rejection_counters,
+            local_transport,
+            stop_consumer,
+            consumer_task: Mutex::new(Some(ConsumerTask::Idle(message_consumer))),
+        })
+    }
+
+    /// Starts consuming messages from Hono's Kafka topics.
+    ///
+    /// This function should be invoked after all listeners have been registered, so that
+    /// no messages get lost. Does nothing if consumption has already been started.
+    ///
+    /// # Panics
+    ///
+    /// Panics if not called from within a Tokio runtime.
+    pub fn start(&self) {
+        let Ok(mut consumer_task) = self.consumer_task.lock() else {
+            return;
+        };
+        *consumer_task = consumer_task
+            .take()
+            .map(|task| task.start(MessageConsumer::run));
+    }
+
+    /// Stops consuming messages from Hono's Kafka topics.
+    ///
+    /// Waits for the message currently being processed (if any) to be delivered to the
+    /// registered listeners before closing the connection to the Kafka broker(s).
+    pub async fn shutdown(&self) {
+        self.stop_consumer.notify_one();
+        let consumer_task = self
+            .consumer_task
+            .lock()
+            .ok()
+            .and_then(|mut consumer_task| consumer_task.take());
+        if let Some(ConsumerTask::Running(consumer_task)) = consumer_task {
+            if let Err(e) = consumer_task.await {
+                warn!("message consumer has terminated abnormally: {e}");
+            }
+        }
+        let rejected_messages = self.rejected_messages();
+        if !rejected_messages.is_empty() {
+            info!("rejected messages per reason: {:?}", rejected_messages);
+        }
+    }
+
+    /// Determines the tenant and the device to send a command to from the authority
+    /// of a message's sink URI.
+    ///
+    /// The authority is expected to be of the form `<device-id>.<tenant-id>`. If consuming messages
+    /// of a single tenant only, the authority may also consist of the device ID only.
+    fn command_target<'a>(&'a self, authority: &'a str) -> Option<(&'a str, &'a str)> {
+        match tenant::split_device_authority(authority) {
+            Some((device_id, tenant_id)) if self.tenant_ids.iter().any(|id| id == tenant_id) => {
+                Some((tenant_id, device_id))
+            }
+            _ if self.tenant_ids.len() == 1 => Some((self.tenant_ids[0].as_str(), authority)),
+            _ => None,
+        }
+    }
+
+    /// Gets the number of received messages that have been rejected because they
+    /// do not contain a (decodable) uProtocol message, keyed by the reason for the rejection.
+    pub fn rejected_messages(&self) -> HashMap<String, u64> {
+        self.rejection_counters.snapshot()
     }
 }
 
 #[async_trait::async_trait]
 impl UTransport for HonoKafkaTransport {
-    async fn send(&self, _message: UMessage) -> Result<(), UStatus> {
-        Err(UStatus::fail_with_code(
-            UCode::UNIMPLEMENTED,
-            "not implemented",
-        ))
+    async fn send(&self, message: UMessage) -> Result<(), UStatus> {
+        let attributes = message.attributes.get_or_default().clone();
+        let (tenant_id, device_id) = attributes
+            .sink
+            .as_ref()
+            .map(|sink| sink.authority_name.as_str())
+            .filter(|authority| !authority.is_empty() && *authority != "*")
+            .and_then(|authority| self.command_target(authority))
+            .ok_or_else(|| {
+                UStatus::fail_with_code(
+                    UCode::INVALID_ARGUMENT,
+                    "message must have a sink URI containing <device-id>.<tenant-id> as authority",
+                )
+            })?;
+        let correlation_id = attributes.id.get_or_default().to_hyphenated_string();
+        let subject = attributes
+            .type_
+            .enum_value_or_default()
+            .to_cloudevent_type();
+        let payload = self.wire_format.encode(message)?;
+        let mut headers = OwnedHeaders::new()
+            .insert(Header {
+                key: command::HEADER_DEVICE_ID,
+                value: Some(device_id),
+            })
+            .insert(Header {
+                key: command::HEADER_SUBJECT,
+                value: Some(&subject),
+            })
+            .insert(Header {
+                key: command::HEADER_CONTENT_TYPE,
+                value: Some(self.wire_format.content_type()),
+            })
+            .insert(Header {
+                key: command::HEADER_CORRELATION_ID,
+                value: Some(&correlation_id),
+            });
+        if attributes.is_request() {
+            headers = headers.insert(Header {
+                key: command::HEADER_RESPONSE_REQUIRED,
+                value: Some("true"),
+            });
+            self.pending_requests
+                .add(correlation_id.clone(), attributes.clone(), attributes.ttl);
+        }
+        let command_topic = command::command_topic(tenant_id);
+        let record = FutureRecord::to(&command_topic)
+            .key(device_id)
+            .payload(&payload)
+            .headers(headers);
+        match self
+            .producer
+            .send(record, Timeout::After(COMMAND_QUEUE_TIMEOUT))
+            .await
+        {
+            Ok(_delivery) => {
+                debug!(
+                    "successfully sent command to device [tenant ID: {}, device ID: {}, correlation-id: {}]",
+                    tenant_id, device_id, correlation_id
+                );
+                Ok(())
+            }
+            Err((e, _message)) => {
+                warn!(
+                    "failed to send command to device [tenant ID: {}, device ID: {}]: {}",
+                    tenant_id, device_id, e
+                );
+                self.pending_requests.remove(&correlation_id);
+                Err(UStatus::fail_with_code(UCode::UNAVAILABLE, e.to_string()))
+            }
+        }
     }
 
     async fn register_listener(
@@ -296,3 +814,31 @@ impl UTransport for HonoKafkaTransport {
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_consumer_runs_only_after_start() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let run = |tx: mpsc::UnboundedSender<()>| async move {
+            let _ = tx.send(());
+        };
+
+        let task = ConsumerTask::Idle(tx);
+        tokio::task::yield_now().await;
+        assert!(rx.try_recv().is_err(), "consumer must not run before start");
+
+        let task = task.start(run);
+        assert!(rx.recv().await.is_some());
+        // starting a running consumer does not spawn another one
+        let ConsumerTask::Running(handle) = task.start(run) else {
+            panic!("consumer should be running");
+        };
+        handle.await.unwrap();
+        assert!(rx.recv().await.is_none());
+    }
+}
@@ -0,0 +1,129 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Support for Hono's device connection notifications.
+//!
+//! Hono's protocol adapters publish a notification to the tenant's event topic whenever a device
+//! connects to or disconnects from an adapter. The notifications are mapped to [`ConnectionStatus`]
+//! messages which are published to a uProtocol topic.
+
+use fms_proto::fms::ConnectionStatus;
+use protobuf::well_known_types::timestamp::Timestamp;
+use serde_json::Value;
+use up_rust::{UMessage, UMessageBuilder, UUri};
+
+pub(crate) const CONTENT_TYPE_DC_NOTIFICATION: &str =
+    "application/vnd.eclipse-hono-dc-notification+json";
+pub(crate) const CONTENT_TYPE_EMPTY_NOTIFICATION: &str =
+    "application/vnd.eclipse-hono-empty-notification";
+pub(crate) const HEADER_CREATION_TIME: &str = "creation-time";
+
+const CAUSE_CONNECTED: &str = "connected";
+const CAUSE_DISCONNECTED: &str = "disconnected";
+
+fn has_media_type(content_type: &str, expected_media_type: &str) -> bool {
+    content_type
+        .split(';')
+        .next()
+        .is_some_and(|media_type| media_type.trim().eq_ignore_ascii_case(expected_media_type))
+}
+
+/// Checks if a content type is the one used by Hono for device connection notifications.
+pub(crate) fn is_connection_notification(content_type: &str) -> bool {
+    has_media_type(content_type, CONTENT_TYPE_DC_NOTIFICATION)
+}
+
+/// Checks if a content type is the one used for empty notifications, which devices send
+/// to indicate that they are ready to receive commands, e.g. when polling via Hono's HTTP adapter.
+pub(crate) fn is_empty_notification(content_type: &str) -> bool {
+    has_media_type(content_type, CONTENT_TYPE_EMPTY_NOTIFICATION)
+}
+
+/// Creates a uProtocol message for a device connection notification.
+///
+/// The payload is expected to be a JSON object like
+///
+/// ```json
+/// {"cause": "connected", "remote-id": "10.0.0.1:34567", "source": "hono-mqtt", "data": {}}
+/// ```
+///
+/// The message contains a [`ConnectionStatus`] and is published to the given topic.
+pub(crate) fn connection_status_message(
+    device_id: &str,
+    creation_time_millis: i64,
+    payload: &[u8],
+    topic: &UUri,
+) -> Result<UMessage, String> {
+    let notification: Value = serde_json::from_slice(payload)
+        .map_err(|e| format!("failed to parse connection notification: {e}"))?;
+    let connected = match notification.get("cause").and_then(Value::as_str) {
+        Some(CAUSE_CONNECTED) => true,
+        Some(CAUSE_DISCONNECTED) => false,
+        _ => return Err("connection notification contains unsupported cause".to_string()),
+    };
+    let connection_status = ConnectionStatus {
+        device_id: device_id.to_string(),
+        connected,
+        time: Some(Timestamp {
+            seconds: creation_time_millis.div_euclid(1000),
+            nanos: (creation_time_millis.rem_euclid(1000) * 1_000_000) as i32,
+            ..Default::default()
+        })
+        .into(),
+        adapter: notification
+            .get("source")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        ..Default::default()
+    };
+    UMessageBuilder::publish(topic.to_owned())
+        .build_with_wrapped_protobuf_payload(&connection_status)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_connection_status_message() {
+        let topic = UUri::from_str("up://hono/D102/1/D102").unwrap();
+        assert!(is_connection_notification(
+            "application/vnd.eclipse-hono-dc-notification+json; charset=utf-8"
+        ));
+        assert!(!is_connection_notification("application/json"));
+        assert!(!is_connection_notification(CONTENT_TYPE_EMPTY_NOTIFICATION));
+        assert!(is_empty_notification(CONTENT_TYPE_EMPTY_NOTIFICATION));
+
+        let payload = br#"{"cause": "disconnected", "remote-id": "10.0.0.1:34567", "source": "hono-mqtt", "data": {}}"#;
+        let msg = connection_status_message("truck-1", 1_700_000_000_123, payload, &topic).unwrap();
+        assert_eq!(msg.attributes.source.get_or_default(), &topic);
+        let connection_status = msg.extract_protobuf::<ConnectionStatus>().unwrap();
+        assert_eq!(connection_status.device_id, "truck-1");
+        assert!(!connection_status.connected);
+        assert_eq!(connection_status.time.seconds, 1_700_000_000);
+        assert_eq!(connection_status.time.nanos, 123_000_000);
+        assert_eq!(connection_status.adapter.as_deref(), Some("hono-mqtt"));
+
+        assert!(connection_status_message("truck-1", 0, br#"{"cause": "ttd"}"#, &topic).is_err());
+        assert!(connection_status_message("truck-1", 0, b"not JSON", &topic).is_err());
+    }
+}
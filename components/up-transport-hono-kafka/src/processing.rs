@@ -0,0 +1,66 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Support for listeners to report the outcome of processing a received message.
+//!
+//! Received messages are dispatched to all matching listeners sequentially, i.e. the processing
+//! of a message is complete once all of the listeners' `on_receive` functions have returned.
+//! Listeners that could not process a message, e.g. because a back end service was unavailable,
+//! can indicate this by means of [`report_failure`] before returning. When consuming messages with
+//! manual offset commits, the message is then delivered again and its offset is not committed.
+
+use std::{cell::Cell, future::Future};
+
+tokio::task_local! {
+    static PROCESSING_FAILED: Cell<bool>;
+}
+
+/// Reports that the message currently being processed by a listener could not be processed.
+///
+/// This function must be invoked from within the listener's `on_receive` function (i.e. not from
+/// a task spawned by the listener). It has no effect if the message has not been received by
+/// means of a transport that tracks the outcome of processing.
+pub fn report_failure() {
+    let _ = PROCESSING_FAILED.try_with(|failed| failed.set(true));
+}
+
+/// Runs the dispatching of a message to listeners.
+///
+/// Returns `true` if none of the listeners has reported a failure.
+pub(crate) async fn track<F: Future<Output = ()>>(dispatch: F) -> bool {
+    PROCESSING_FAILED
+        .scope(Cell::new(false), async move {
+            dispatch.await;
+            !PROCESSING_FAILED.with(Cell::get)
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_track_detects_reported_failure() {
+        assert!(track(async {}).await);
+        assert!(!track(async { report_failure() }).await);
+        // reporting a failure outside of a tracked dispatch has no effect
+        report_failure();
+    }
+}
@@ -0,0 +1,99 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Support for consuming messages of multiple Hono tenants.
+//!
+//! Device identifiers are only unique within the scope of a tenant. The transport therefore identifies
+//! devices by means of an authority of the form `<device-id>.<tenant-id>`, e.g. `truck-4711.customer-a`.
+//! Tenant identifiers must not contain any dots, so that the authority can be split unambiguously.
+
+use clap::ValueEnum;
+
+/// The classes of topics that Hono publishes messages of a tenant's devices to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum TopicClass {
+    /// Telemetry messages, published to the `hono.telemetry.${tenant}` topic.
+    Telemetry,
+    /// Events, including device connection notifications, published to the `hono.event.${tenant}` topic.
+    Event,
+    /// Responses to commands, published to the `hono.command_response.${tenant}` topic.
+    CommandResponse,
+}
+
+impl TopicClass {
+    /// Gets the name of the Kafka topic of this class for a tenant.
+    pub(crate) fn topic(&self, tenant_id: &str) -> String {
+        match self {
+            TopicClass::Telemetry => format!("hono.telemetry.{}", tenant_id),
+            TopicClass::Event => format!("hono.event.{}", tenant_id),
+            TopicClass::CommandResponse => crate::command::command_response_topic(tenant_id),
+        }
+    }
+}
+
+/// Parses a tenant identifier from a command line argument.
+pub(crate) fn parse_tenant_id(s: &str) -> Result<String, String> {
+    if s.is_empty() {
+        Err("tenant ID must not be empty".to_string())
+    } else if s.contains('.') {
+        Err("tenant ID must not contain any dots".to_string())
+    } else {
+        Ok(s.to_string())
+    }
+}
+
+/// Gets the identifier of the tenant that a message has been received from,
+/// based on the name of the Kafka topic that the message has been received on.
+pub(crate) fn tenant_of_topic(topic: &str) -> &str {
+    topic.rsplit('.').next().unwrap_or(topic)
+}
+
+/// Creates the authority that identifies a device of a tenant.
+pub fn device_authority(tenant_id: &str, device_id: &str) -> String {
+    format!("{}.{}", device_id, tenant_id)
+}
+
+/// Splits an authority that identifies a device of a tenant into the
+/// device identifier and the tenant identifier.
+pub fn split_device_authority(authority: &str) -> Option<(&str, &str)> {
+    authority
+        .rsplit_once('.')
+        .filter(|(device_id, tenant_id)| !device_id.is_empty() && !tenant_id.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_device_authority() {
+        let authority = device_authority("customer-a", "org.eclipse.truck-1");
+        assert_eq!(authority, "org.eclipse.truck-1.customer-a");
+        assert_eq!(
+            split_device_authority(&authority),
+            Some(("org.eclipse.truck-1", "customer-a"))
+        );
+        assert_eq!(split_device_authority("truck-1"), None);
+
+        let topic = TopicClass::CommandResponse.topic("customer-a");
+        assert_eq!(topic, "hono.command_response.customer-a");
+        assert_eq!(tenant_of_topic(&topic), "customer-a");
+        assert!(parse_tenant_id("customer.a").is_err());
+    }
+}
@@ -0,0 +1,117 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Support for Hono's command & control message exchange pattern.
+//!
+//! Hono delivers commands to a device by means of publishing them to topic
+//! `command///req/${req-id}/${command}`. The `req-id` is empty for one-way commands.
+//! A device sends the response to a (request/response) command by publishing it to topic
+//! `command///res/${req-id}/${status}`.
+//!
+//! A gateway receives the commands for the devices that it acts on behalf of on topic
+//! `command//${device-id}/req/${req-id}/${command}` and sends responses to topic
+//! `command//${device-id}/res/${req-id}/${status}`.
+
+/// The topic filter to use for subscribing to commands sent to the (authenticated) device.
+pub(crate) const TOPIC_FILTER_COMMAND_REQUESTS: &str = "command///req/#";
+
+/// The topic filter to use for subscribing to commands sent to any of the devices
+/// that a gateway acts on behalf of.
+pub(crate) const TOPIC_FILTER_GATEWAY_COMMAND_REQUESTS: &str = "command//+/req/#";
+
+/// The properties of a command that are conveyed in the topic name.
+#[derive(Debug, PartialEq)]
+pub(crate) struct CommandTopic {
+    /// The identifier of the device that the command has been sent to,
+    /// if it is not the authenticated device itself.
+    pub device_id: Option<String>,
+    /// The identifier that the command response needs to be correlated with, if any.
+    pub request_id: Option<String>,
+    /// The name of the command.
+    pub name: String,
+}
+
+/// Parses the name of a topic that a command has been received on.
+///
+/// Returns `None` if the topic is not a command request topic.
+pub(crate) fn parse_command_topic(topic: &str) -> Option<CommandTopic> {
+    // strip off the property bag, if any
+    let topic = topic.split_once("/?").map_or(topic, |(name, _)| name);
+    let segments: Vec<&str> = topic.splitn(6, '/').collect();
+    match segments.as_slice() {
+        ["command" | "c", _tenant, device_id, "req" | "q", request_id, name]
+            if !name.is_empty() =>
+        {
+            Some(CommandTopic {
+                device_id: Some(device_id.to_string()).filter(|id| !id.is_empty()),
+                request_id: Some(request_id.to_string()).filter(|id| !id.is_empty()),
+                name: name.to_string(),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Gets the topic to publish the response to a command to.
+///
+/// The device ID is only required if the response is sent by a gateway.
+pub(crate) fn response_topic(device_id: Option<&str>, request_id: &str, status: u16) -> String {
+    format!(
+        "command//{}/res/{}/{}",
+        device_id.unwrap_or_default(),
+        request_id,
+        status
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command_topic() {
+        assert_eq!(
+            parse_command_topic("command///req/1010f8ab0b53-bd96-4dfe/setSpeedLimit"),
+            Some(CommandTopic {
+                device_id: None,
+                request_id: Some("1010f8ab0b53-bd96-4dfe".to_string()),
+                name: "setSpeedLimit".to_string()
+            })
+        );
+        assert_eq!(
+            parse_command_topic("c//VIN0001/q//reset/?content-type=text%2Fplain"),
+            Some(CommandTopic {
+                device_id: Some("VIN0001".to_string()),
+                request_id: None,
+                name: "reset".to_string()
+            })
+        );
+        assert_eq!(parse_command_topic("command///res/abc/200"), None);
+        assert_eq!(parse_command_topic("telemetry"), None);
+    }
+
+    #[test]
+    fn test_response_topic_contains_status() {
+        assert_eq!(response_topic(None, "abc", 404), "command///res/abc/404");
+        assert_eq!(
+            response_topic(Some("VIN0001"), "abc", 200),
+            "command//VIN0001/res/abc/200"
+        );
+    }
+}
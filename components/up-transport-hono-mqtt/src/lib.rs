@@ -17,82 +17,415 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
+use std::{sync::Arc, time::Duration};
+
 use clap::Args;
-use log::{debug, warn};
-use paho_mqtt::{AsyncClient, MessageBuilder};
-use protobuf::Message;
-use up_rust::{CloudEvent, UCode, UMessage, UStatus, UTransport};
+use hono_wire_format::{command::PendingRequests, is_event, parse_priority, WireFormat};
+use log::{debug, info, warn};
+use mqtt_connection::{MqttClientOptions, MqttVersion};
+use paho_mqtt::{
+    AsyncClient, AsyncReceiver, DisconnectOptionsBuilder, MessageBuilder, PropertyCode, QOS_0,
+    QOS_1,
+};
+use tokio::sync::watch;
+use up_rust::{
+    local_transport::LocalTransport, UAttributes, UCode, UListener, UMessage, UPriority, UStatus,
+    UTransport, UUri,
+};
 use url_encor::Encoder;
 
-mod mqtt_connection;
+pub use mqtt_connection::ConnectionState;
+
+mod command;
+mod properties;
+
+const PARAM_EVENT_PRIORITY: &str = "event-priority";
+const PARAM_GATEWAY_MODE: &str = "gateway-mode";
+const PARAM_MQTT_PROTOCOL_VERSION: &str = "mqtt-protocol-version";
+const PARAM_WIRE_FORMAT: &str = "wire-format";
+
+const TOPIC_EVENT: &str = "event";
+const TOPIC_TELEMETRY: &str = "telemetry";
 
-// TODO: this should be replaced with a constant from up-rust
-// see https://github.com/eclipse-uprotocol/up-rust/issues/223
-const MIME_TYPE_CLOUDEVENTS_PROTOBUF: &str = "application/cloudevents+protobuf";
+// the maximum number of received commands that are buffered before being dispatched to listeners
+const COMMAND_BUFFER_SIZE: usize = 25;
 
 #[derive(Args)]
 pub struct HonoMqttTransportConfig {
     #[command(flatten)]
-    mqtt_client_options: mqtt_connection::MqttClientOptions,
+    mqtt_client_options: MqttClientOptions,
+
+    /// The version of the MQTT protocol to use for connecting to the MQTT adapter.
+    #[arg(
+        value_enum,
+        long = PARAM_MQTT_PROTOCOL_VERSION,
+        default_value = "v3",
+        env = "MQTT_PROTOCOL_VERSION"
+    )]
+    protocol_version: MqttVersion,
+
+    /// The minimum uProtocol priority (CS0 - CS6) of messages that are sent to Hono's event endpoint
+    /// using QoS 1. All other messages are sent to Hono's telemetry endpoint using QoS 0.
+    #[arg(long = PARAM_EVENT_PRIORITY, value_name = "CODE", env = "EVENT_PRIORITY", default_value = "CS2", value_parser = parse_priority)]
+    event_priority: UPriority,
+
+    /// Indicates whether the transport should act as a Hono gateway that publishes messages on behalf of
+    /// other devices. The identifier of the device is taken from the authority of each message's source URI.
+    #[arg(long = PARAM_GATEWAY_MODE, env = "GATEWAY_MODE")]
+    gateway_mode: bool,
+
+    /// The format to publish messages in. Note that raw protobuf does not contain any of the uProtocol
+    /// message attributes, unless MQTT 5 is used.
+    #[arg(long = PARAM_WIRE_FORMAT, value_name = "FORMAT", env = "WIRE_FORMAT", value_enum, default_value_t = WireFormat::CloudEventsProtobuf)]
+    wire_format: WireFormat,
+}
+
+/// Gets the name of the topic to publish messages to a Hono endpoint with.
+///
+/// With MQTT v3.1.1, the content type is added to the topic's property bag.
+fn endpoint_topic(endpoint: &str, content_type: &str, protocol_version: &MqttVersion) -> String {
+    match protocol_version {
+        MqttVersion::V3 => {
+            let content_type = content_type.to_string().url_encode();
+            format!("{}/?content-type={}", endpoint, content_type)
+        }
+        MqttVersion::V5 => endpoint.to_string(),
+    }
 }
 
 pub struct HonoMqttTransport {
     mqtt_client: AsyncClient,
-    topic: String,
+    connection_state: watch::Sender<ConnectionState>,
+    persistent_buffer: bool,
+    protocol_version: MqttVersion,
+    message_expiry: Option<Duration>,
+    event_priority: UPriority,
+    gateway_mode: bool,
+    wire_format: WireFormat,
+    local_transport: Arc<LocalTransport>,
+    pending_commands: Arc<PendingRequests<String>>,
 }
 
-/// A simple uProtocol transport implementation for sending messages to Eclipse Hono's
-/// MQTT adapter (using MQTT v3.1.1).
+/// A simple uProtocol transport implementation for exchanging messages with Eclipse Hono's
+/// MQTT adapter (using MQTT v3.1.1 or v5).
+///
+/// Messages are sent to Hono's event endpoint using QoS 1 if their priority is at least
+/// the configured event priority. All other messages are sent as telemetry using QoS 0.
+///
+/// In gateway mode, messages are published on behalf of the device identified by the authority
+/// of the message's source URI, e.g. the vehicle's VIN. This allows a single MQTT connection
+/// to be used for many vehicles. Commands are then received for all of these devices.
+///
+/// Messages are published as CloudEvents (in protobuf or JSON format) or as raw protobuf,
+/// depending on the configured wire format.
+///
+/// With MQTT v3.1.1, the message's content type is conveyed in the property bag of the topic name.
+/// With MQTT v5, the content type, the message's expiry and the uProtocol message attributes
+/// are conveyed in the PUBLISH packet's properties instead.
+///
+/// Commands sent to the device via Hono's command & control API are expected to contain
+/// a uProtocol message in a CloudEvent and are dispatched to the registered listeners.
+/// With MQTT v5, the CloudEvent's format is determined from the command's content type,
+/// otherwise it is expected to be in protobuf format. Response messages are sent back to Hono
+/// as the response to the command that the corresponding request has been created from.
 impl HonoMqttTransport {
     /// Creates a new transport.
     ///
     /// Determines the parameters necessary for creating the publisher from values specified on
     /// the command line or via environment variables as defined by [`add_command_line_args`].
     ///
-    /// The publisher returned is configured to keep trying to (re-)connect to the configured
-    /// MQTT endpoint using a client certificate of username/password credentials.
+    /// The transport returned keeps trying to (re-)connect to the configured MQTT endpoint
+    /// in the background, using a client certificate or username/password credentials.
+    /// The state of the connection can be tracked by means of [`Self::connection_state`].
     pub async fn new(config: &HonoMqttTransportConfig) -> Result<Self, Box<dyn std::error::Error>> {
-        let content_type = MIME_TYPE_CLOUDEVENTS_PROTOBUF.to_string().url_encode();
-        let topic = format!("telemetry/?content-type={}", content_type);
+        let options = &config.mqtt_client_options;
+        let command_topic_filter = if config.gateway_mode {
+            command::TOPIC_FILTER_GATEWAY_COMMAND_REQUESTS
+        } else {
+            command::TOPIC_FILTER_COMMAND_REQUESTS
+        };
+        let mut command_stream = None;
+        let (mqtt_client, connection_state) =
+            options.connect(&config.protocol_version, |client| {
+                command_stream = Some(client.get_stream(COMMAND_BUFFER_SIZE));
+            })?;
+        tokio::spawn(Self::subscribe_to_commands(
+            mqtt_client.clone(),
+            connection_state.subscribe(),
+            command_topic_filter,
+        ));
+
+        let local_transport = Arc::new(LocalTransport::default());
+        let pending_commands = Arc::new(PendingRequests::default());
+        if let Some(command_stream) = command_stream {
+            tokio::spawn(Self::dispatch_commands(
+                command_stream,
+                local_transport.clone(),
+                pending_commands.clone(),
+            ));
+        }
+        Ok(HonoMqttTransport {
+            mqtt_client,
+            connection_state,
+            persistent_buffer: options.has_persistence(),
+            protocol_version: config.protocol_version.clone(),
+            message_expiry: options.message_expiry(),
+            event_priority: config.event_priority,
+            gateway_mode: config.gateway_mode,
+            wire_format: config.wire_format,
+            local_transport,
+            pending_commands,
+        })
+    }
+
+    /// Gets a channel for keeping track of the state of the connection to the MQTT endpoint.
+    pub fn connection_state(&self) -> watch::Receiver<ConnectionState> {
+        self.connection_state.subscribe()
+    }
+
+    /// Checks if messages that are sent while the connection to the MQTT endpoint is not established
+    /// are stored in files until they can be delivered.
+    ///
+    /// Such messages survive restarts of the process, so senders do not need to hold them back
+    /// until the connection has been (re-)established.
+    pub fn has_persistent_buffer(&self) -> bool {
+        self.persistent_buffer
+    }
+
+    /// Subscribes to the command topic whenever a connection has been established.
+    ///
+    /// This is necessary because subscriptions might not survive a reconnect.
+    async fn subscribe_to_commands(
+        mqtt_client: AsyncClient,
+        mut connection_state: watch::Receiver<ConnectionState>,
+        topic_filter: &'static str,
+    ) {
+        loop {
+            let state = *connection_state.borrow_and_update();
+            match state {
+                ConnectionState::Disconnected => break,
+                ConnectionState::Connected => {
+                    debug!("subscribing to command topic [{}]", topic_filter);
+                    if let Err(e) = mqtt_client.subscribe(topic_filter, QOS_1).await {
+                        warn!(
+                            "failed to subscribe to command topic [{}]: {}",
+                            topic_filter, e
+                        );
+                    }
+                }
+                _ => {}
+            }
+            if connection_state.changed().await.is_err() {
+                break;
+            }
+        }
+    }
 
-        config
-            .mqtt_client_options
-            .connect()
+    /// Dispatches the uProtocol messages contained in commands received from Hono
+    /// to the registered listeners.
+    async fn dispatch_commands(
+        command_stream: AsyncReceiver<Option<paho_mqtt::Message>>,
+        local_transport: Arc<LocalTransport>,
+        pending_commands: Arc<PendingRequests<String>>,
+    ) {
+        // the stream yields None whenever the connection to the MQTT endpoint has been lost
+        // and ends once the MQTT client has been dropped
+        while let Ok(received) = command_stream.recv().await {
+            let Some(command) = received else {
+                continue;
+            };
+            let Some(command_topic) = command::parse_command_topic(command.topic()) else {
+                debug!(
+                    "ignoring message received on non-command topic [{}]",
+                    command.topic()
+                );
+                continue;
+            };
+            // with MQTT v5, the format of the command is indicated by its content type
+            let content_type = command.properties().get_string(PropertyCode::ContentType);
+            let umessage =
+                hono_wire_format::decode(content_type.as_deref(), command.payload(), None);
+            match umessage {
+                Ok(umessage) => {
+                    debug!(
+                        "received command [name: {}, device ID: {}, request ID: {}]",
+                        command_topic.name,
+                        command_topic.device_id.as_deref().unwrap_or("none"),
+                        command_topic.request_id.as_deref().unwrap_or("none")
+                    );
+                    if let (true, Some(request_id)) =
+                        (umessage.is_request(), command_topic.request_id)
+                    {
+                        let attributes = umessage.attributes.get_or_default();
+                        pending_commands.add(
+                            attributes.id.get_or_default().to_hyphenated_string(),
+                            request_id,
+                            attributes.ttl,
+                        );
+                    }
+                    let _ = local_transport.send(umessage).await;
+                }
+                Err(e) => {
+                    warn!(
+                        "ignoring command [name: {}] not containing a uProtocol message: {}",
+                        command_topic.name, e
+                    );
+                }
+            }
+        }
+        debug!("stopped dispatching commands");
+    }
+
+    /// Gets the identifier of the device to publish a message on behalf of.
+    ///
+    /// Returns `None` if the transport is not running in gateway mode, i.e. if messages
+    /// are published by the authenticated device itself.
+    fn get_device_id(&self, attributes: &UAttributes) -> Result<Option<String>, UStatus> {
+        if !self.gateway_mode {
+            return Ok(None);
+        }
+        attributes
+            .source
+            .as_ref()
+            .map(|source| source.authority_name.to_owned())
+            .filter(|authority| !authority.is_empty())
+            .map(Some)
+            .ok_or_else(|| {
+                UStatus::fail_with_code(
+                    UCode::INVALID_ARGUMENT,
+                    "in gateway mode, the message's source URI must contain an authority",
+                )
+            })
+    }
+
+    fn endpoint_topic(&self, endpoint: &str) -> String {
+        endpoint_topic(
+            endpoint,
+            self.wire_format.content_type(),
+            &self.protocol_version,
+        )
+    }
+
+    /// Gets the topic and QoS to publish a message with.
+    ///
+    /// Response messages are published to the command response topic of the command that
+    /// the corresponding request message has been created from. All other messages are
+    /// published as events or telemetry, depending on their priority.
+    fn get_publish_topic(&self, attributes: &UAttributes) -> Result<(String, i32), UStatus> {
+        let device_id = self.get_device_id(attributes)?;
+        if !attributes.is_response() {
+            let (endpoint, qos) = if is_event(attributes, self.event_priority) {
+                (TOPIC_EVENT, QOS_1)
+            } else {
+                (TOPIC_TELEMETRY, QOS_0)
+            };
+            let topic = match device_id {
+                Some(device_id) => format!("{}//{}", endpoint, device_id),
+                None => endpoint.to_string(),
+            };
+            return Ok((self.endpoint_topic(&topic), qos));
+        }
+        let request_id = attributes.reqid.get_or_default().to_hyphenated_string();
+        // the command is removed once the response has been published successfully
+        let hono_request_id = self.pending_commands.get(&request_id).ok_or_else(|| {
+            UStatus::fail_with_code(
+                UCode::NOT_FOUND,
+                format!("no pending command for request [id: {request_id}]"),
+            )
+        })?;
+        let response_topic = command::response_topic(
+            device_id.as_deref(),
+            &hono_request_id,
+            hono_wire_format::command::response_status(attributes),
+        );
+        Ok((self.endpoint_topic(&response_topic), QOS_0))
+    }
+
+    /// Disconnects from the MQTT endpoint.
+    ///
+    /// Messages that have already been handed over to the MQTT client are being
+    /// delivered before the connection is closed, unless the given timeout elapses.
+    pub async fn disconnect(&self, timeout: Duration) -> Result<(), Box<dyn std::error::Error>> {
+        info!(
+            "disconnecting from MQTT endpoint [uri: {}]",
+            self.mqtt_client.server_uri()
+        );
+        // prevent the client from reconnecting
+        self.connection_state
+            .send_replace(ConnectionState::Disconnected);
+        let disconnect_options = DisconnectOptionsBuilder::new().timeout(timeout).finalize();
+        self.mqtt_client
+            .disconnect(disconnect_options)
             .await
-            .map(|mqtt_client| HonoMqttTransport { mqtt_client, topic })
+            .map(|_response| ())
+            .map_err(Box::from)
     }
 }
 
 #[async_trait::async_trait]
 impl UTransport for HonoMqttTransport {
     async fn send(&self, message: UMessage) -> Result<(), UStatus> {
-        let event = CloudEvent::try_from(message)
-            .map_err(|e| UStatus::fail_with_code(UCode::INVALID_ARGUMENT, e.to_string()))?;
-        let payload = event.write_to_bytes().map_err(|_e| {
-            UStatus::fail_with_code(UCode::INTERNAL, "failed to serialize CloudEvent to JSON")
-        })?;
-        let msg = MessageBuilder::new()
-            .topic(self.topic.clone())
-            .payload(payload)
-            .finalize();
+        let attributes = message.attributes.get_or_default().clone();
+        let (topic, qos) = self.get_publish_topic(&attributes)?;
+        let payload = self.wire_format.encode(message)?;
+        let mut message_builder = MessageBuilder::new()
+            .topic(topic.clone())
+            .qos(qos)
+            .payload(payload);
+        if let MqttVersion::V5 = self.protocol_version {
+            let message_properties = properties::message_properties(
+                &attributes,
+                self.wire_format.content_type(),
+                self.message_expiry,
+            )
+            .map_err(|e| UStatus::fail_with_code(UCode::INTERNAL, e.to_string()))?;
+            message_builder = message_builder.properties(message_properties);
+        }
+        let msg = message_builder.finalize();
         match self.mqtt_client.publish(msg).await {
             Ok(_t) => {
+                if attributes.is_response() {
+                    self.pending_commands
+                        .remove(&attributes.reqid.get_or_default().to_hyphenated_string());
+                }
                 debug!(
-                    "successfully published vehicle status to MQTT endpoint [uri: {}, topic: {}]",
+                    "successfully published message to MQTT endpoint [uri: {}, topic: {}]",
                     self.mqtt_client.server_uri(),
-                    self.topic
+                    topic
                 );
                 Ok(())
             }
             Err(e) => {
                 warn!(
-                    "error publishing vehicle status to MQTT endpoint [uri: {}, topic: {}]: {}",
+                    "error publishing message to MQTT endpoint [uri: {}, topic: {}]: {}",
                     self.mqtt_client.server_uri(),
-                    self.topic,
+                    topic,
                     e
                 );
                 Err(UStatus::fail_with_code(UCode::INTERNAL, e.to_string()))
             }
         }
     }
+
+    async fn register_listener(
+        &self,
+        source_filter: &UUri,
+        sink_filter: Option<&UUri>,
+        listener: Arc<dyn UListener>,
+    ) -> Result<(), UStatus> {
+        self.local_transport
+            .register_listener(source_filter, sink_filter, listener)
+            .await
+    }
+
+    async fn unregister_listener(
+        &self,
+        source_filter: &UUri,
+        sink_filter: Option<&UUri>,
+        listener: Arc<dyn UListener>,
+    ) -> Result<(), UStatus> {
+        self.local_transport
+            .unregister_listener(source_filter, sink_filter, listener)
+            .await
+    }
 }
@@ -0,0 +1,120 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Mapping of uProtocol message attributes to MQTT 5 message properties.
+//!
+//! The attributes are conveyed as user properties as defined by the uProtocol MQTT 5 transport
+//! specification, i.e. using the attributes' protobuf field numbers as property names.
+
+use std::time::Duration;
+
+use hono_wire_format::attributes;
+use paho_mqtt::{Properties, PropertyCode};
+use up_rust::UAttributes;
+
+/// Gets the number of seconds after which a message should expire.
+///
+/// The message's time-to-live takes precedence over the default expiry.
+fn message_expiry_interval(
+    attributes: &UAttributes,
+    default_expiry: Option<Duration>,
+) -> Option<u32> {
+    match attributes.ttl {
+        Some(ttl) if ttl > 0 => Some(ttl.div_ceil(1000)),
+        _ => default_expiry
+            .map(|expiry| u32::try_from(expiry.as_secs()).unwrap_or(u32::MAX))
+            .filter(|seconds| *seconds > 0),
+    }
+}
+
+/// Creates the MQTT 5 properties to include in the PUBLISH packet for a uProtocol message.
+pub(crate) fn message_properties(
+    attributes: &UAttributes,
+    content_type: &str,
+    default_expiry: Option<Duration>,
+) -> Result<Properties, paho_mqtt::Error> {
+    let mut properties = Properties::new();
+    properties.push_string(PropertyCode::ContentType, content_type)?;
+    if let Some(seconds) = message_expiry_interval(attributes, default_expiry) {
+        properties.push_u32(PropertyCode::MessageExpiryInterval, seconds)?;
+    }
+    for (name, value) in attributes::to_pairs(attributes) {
+        properties.push_string_pair(PropertyCode::UserProperty, name, &value)?;
+    }
+    Ok(properties)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use up_rust::{UMessageBuilder, UPriority, UUri};
+
+    use super::*;
+
+    #[test]
+    fn test_message_properties_contain_attributes() {
+        let topic = UUri::from_str("//vehicle/D100/1/D100").unwrap();
+        let message = UMessageBuilder::publish(topic)
+            .with_priority(UPriority::UPRIORITY_CS2)
+            .with_ttl(1500)
+            .build()
+            .unwrap();
+        let attributes = message.attributes.get_or_default();
+        let properties =
+            message_properties(attributes, "application/cloudevents+protobuf", None).unwrap();
+
+        assert_eq!(
+            properties.get_string(PropertyCode::ContentType).as_deref(),
+            Some("application/cloudevents+protobuf")
+        );
+        let user_properties: Vec<(String, String)> = properties.user_iter().collect();
+        let contains = |name: &str, value: &str| {
+            user_properties.contains(&(name.to_string(), value.to_string()))
+        };
+        assert!(contains("uP", "1"));
+        assert!(contains(attributes::NAME_SOURCE, "//vehicle/D100/1/D100"));
+        assert!(contains(attributes::NAME_TYPE, "1"));
+        assert!(contains(attributes::NAME_PRIORITY, "3"));
+        assert!(contains(attributes::NAME_TTL, "1500"));
+        assert!(user_properties
+            .iter()
+            .any(|(name, _)| name == attributes::NAME_ID));
+        assert!(!user_properties
+            .iter()
+            .any(|(name, _)| name == attributes::NAME_SINK));
+        assert_eq!(
+            attributes::from_pairs(user_properties).as_ref(),
+            Ok(attributes)
+        );
+
+        // the TTL is rounded up to full seconds
+        assert_eq!(message_expiry_interval(attributes, None), Some(2));
+    }
+
+    #[test]
+    fn test_message_expiry_uses_default() {
+        let attributes = UAttributes::new();
+        assert_eq!(message_expiry_interval(&attributes, None), None);
+        assert_eq!(
+            message_expiry_interval(&attributes, Some(Duration::from_secs(30))),
+            Some(30)
+        );
+    }
+}
@@ -0,0 +1,332 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use clap::Args;
+use futures::StreamExt;
+use log::{debug, error, info, trace, warn};
+use rdkafka::{
+    consumer::{Consumer, StreamConsumer},
+    message::{BorrowedMessage, Headers},
+    producer::{FutureProducer, FutureRecord},
+    util::Timeout,
+    ClientConfig, Message,
+};
+use tokio::{sync::Notify, task::JoinHandle};
+use up_rust::{
+    local_transport::LocalTransport, UCode, UListener, UMessage, UStatus, UTransport, UUri,
+};
+
+use crate::mapping::Subscription;
+
+mod mapping;
+
+const PARAM_KAFKA_BOOTSTRAP_SERVERS: &str = "kafka-bootstrap-servers";
+const PARAM_KAFKA_GROUP_ID: &str = "kafka-group-id";
+const PARAM_KAFKA_PROPERTIES_FILE: &str = "kafka-properties-file";
+const PARAM_KAFKA_PROPERTY: &str = "kafka-property";
+const PARAM_KAFKA_TOPIC_PREFIX: &str = "kafka-topic-prefix";
+
+// the maximum time to wait for a message to be enqueued if the producer's queue is full
+const SEND_QUEUE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Args)]
+pub struct KafkaTransportConfig {
+    /// The addresses of the Kafka brokers to connect to, e.g. kafka-0:9092,kafka-1:9092.
+    #[arg(long = PARAM_KAFKA_BOOTSTRAP_SERVERS, value_name = "HOST:PORT[,...]", env = "KAFKA_BOOTSTRAP_SERVERS")]
+    bootstrap_servers: String,
+
+    /// The consumer group to join when receiving messages. All instances that are using
+    /// the same group share the partitions of the topics that they are subscribed to.
+    #[arg(long = PARAM_KAFKA_GROUP_ID, value_name = "ID", env = "KAFKA_GROUP_ID", default_value = "up-transport-kafka")]
+    group_id: String,
+
+    /// The path to a file containing additional Kafka client properties, e.g. for configuring SASL.
+    /// The file is expected to be in Java properties format. Property values may contain references
+    /// to environment variables of the form ${VAR_NAME}.
+    #[arg(long = PARAM_KAFKA_PROPERTIES_FILE, value_name = "PATH", env = "KAFKA_PROPERTIES_FILE", value_parser = clap::builder::PathBufValueParser::new())]
+    kafka_property_file: Option<PathBuf>,
+
+    /// A Kafka client property to set, e.g. security.protocol=SSL, overriding the value from the
+    /// properties file (if any). May be used multiple times. The value may contain references to
+    /// environment variables of the form ${VAR_NAME}.
+    #[arg(long = PARAM_KAFKA_PROPERTY, value_name = "KEY=VALUE", value_parser = config_properties::parse_key_value)]
+    kafka_properties: Vec<(String, String)>,
+
+    /// The prefix of the names of the topics that messages are exchanged on.
+    #[arg(long = PARAM_KAFKA_TOPIC_PREFIX, value_name = "PREFIX", env = "KAFKA_TOPIC_PREFIX", default_value = "up")]
+    topic_prefix: String,
+}
+
+impl KafkaTransportConfig {
+    fn client_config(&self) -> Result<ClientConfig, Box<dyn std::error::Error>> {
+        let mut properties = match self.kafka_property_file.as_ref() {
+            Some(path) => config_properties::parse_properties(&std::fs::read_to_string(path)?),
+            None => Vec::new(),
+        };
+        // properties set on the command line take precedence over the ones from the file
+        properties.push((
+            "bootstrap.servers".to_string(),
+            self.bootstrap_servers.clone(),
+        ));
+        properties.push(("group.id".to_string(), self.group_id.clone()));
+        properties.extend(self.kafka_properties.iter().cloned());
+
+        let mut client_config = ClientConfig::new();
+        for (key, value) in config_properties::interpolate_env(properties)? {
+            client_config.set(key, value);
+        }
+        Ok(client_config)
+    }
+}
+
+/// A uProtocol transport implementation for exchanging messages via Apache Kafka directly,
+/// i.e. without any additional messaging infrastructure like Eclipse Hono.
+///
+/// Messages are sent to topics that correspond to the resource that a message is published from
+/// or sent to, using the authority of the resource's URI (e.g. the VIN) as the record key. The message
+/// attributes are conveyed in the record's headers and the payload is sent as is.
+///
+/// Listeners are registered with the Kafka consumer group that has been configured, i.e. the topic
+/// partitions are distributed among all instances of the transport that use the same group.
+pub struct KafkaTransport {
+    producer: FutureProducer,
+    consumer: Arc<StreamConsumer>,
+    topic_prefix: String,
+    local_transport: Arc<LocalTransport>,
+    // the number of listeners registered per subscription
+    subscriptions: Mutex<HashMap<Subscription, usize>>,
+    stop_consumer: Arc<Notify>,
+    consumer_task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl KafkaTransport {
+    /// Creates a new transport.
+    ///
+    /// The transport does not subscribe to any topics until a listener gets registered.
+    pub fn new(config: &KafkaTransportConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let client_config = config.client_config()?;
+        let producer = client_config.create::<FutureProducer>()?;
+        let consumer = client_config.create::<StreamConsumer>().map(Arc::new)?;
+        info!(
+            "created Kafka client [bootstrap servers: {}, group ID: {}]",
+            config.bootstrap_servers, config.group_id
+        );
+
+        let local_transport = Arc::new(LocalTransport::default());
+        let stop_consumer = Arc::new(Notify::new());
+        let consumer_task = tokio::spawn(Self::dispatch_messages(
+            consumer.clone(),
+            local_transport.clone(),
+            stop_consumer.clone(),
+        ));
+        Ok(KafkaTransport {
+            producer,
+            consumer,
+            topic_prefix: config.topic_prefix.to_owned(),
+            local_transport,
+            subscriptions: Mutex::new(HashMap::new()),
+            stop_consumer,
+            consumer_task: Mutex::new(Some(consumer_task)),
+        })
+    }
+
+    fn umessage_from_record(m: &BorrowedMessage<'_>) -> Result<UMessage, String> {
+        let headers = m
+            .headers()
+            .ok_or_else(|| "record does not contain any headers".to_string())?;
+        let attributes = mapping::attributes_from_headers(headers.iter().filter_map(|header| {
+            header
+                .value
+                .and_then(|value| std::str::from_utf8(value).ok())
+                .map(|value| (header.key, value))
+        }))?;
+        Ok(UMessage {
+            attributes: Some(attributes).into(),
+            payload: m.payload().map(bytes::Bytes::copy_from_slice),
+            ..Default::default()
+        })
+    }
+
+    /// Dispatches the uProtocol messages received from the Kafka broker(s) to the registered listeners.
+    async fn dispatch_messages(
+        consumer: Arc<StreamConsumer>,
+        local_transport: Arc<LocalTransport>,
+        stop_signal: Arc<Notify>,
+    ) {
+        let mut stream = consumer.stream();
+        loop {
+            tokio::select! {
+                _ = stop_signal.notified() => break,
+                next = stream.next() => match next {
+                    Some(Ok(m)) => match Self::umessage_from_record(&m) {
+                        Ok(umessage) => {
+                            let _ = local_transport.send(umessage).await;
+                        }
+                        Err(e) => {
+                            debug!("ignoring record received on topic [{}]: {}", m.topic(), e);
+                        }
+                    },
+                    Some(Err(e)) => {
+                        // errors are not fatal, e.g. if a topic that a listener
+                        // has been registered for does not exist (yet)
+                        warn!("error receiving messages from Kafka: {}", e);
+                    }
+                    None => break,
+                }
+            }
+        }
+        debug!("stopped dispatching messages");
+    }
+
+    /// Updates the consumer's subscription to the topics of all registered listeners.
+    fn update_subscription(
+        &self,
+        subscriptions: &HashMap<Subscription, usize>,
+    ) -> Result<(), UStatus> {
+        if subscriptions.is_empty() {
+            debug!("unsubscribing from all topics");
+            self.consumer.unsubscribe();
+            return Ok(());
+        }
+        let topics: Vec<&str> = subscriptions.keys().map(Subscription::as_str).collect();
+        debug!("subscribing to topics {:?}", topics);
+        self.consumer.subscribe(&topics).map_err(|e| {
+            warn!("failed to subscribe to topics: {}", e);
+            UStatus::fail_with_code(UCode::UNAVAILABLE, e.to_string())
+        })
+    }
+
+    /// Stops receiving messages from the Kafka broker(s).
+    ///
+    /// Waits for the message currently being processed (if any) to be delivered to the
+    /// registered listeners.
+    pub async fn shutdown(&self) {
+        self.stop_consumer.notify_one();
+        let consumer_task = self
+            .consumer_task
+            .lock()
+            .ok()
+            .and_then(|mut consumer_task| consumer_task.take());
+        if let Some(consumer_task) = consumer_task {
+            if let Err(e) = consumer_task.await {
+                warn!("message consumer has terminated abnormally: {e}");
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl UTransport for KafkaTransport {
+    async fn send(&self, message: UMessage) -> Result<(), UStatus> {
+        let attributes = message.attributes.get_or_default();
+        let (topic, key) = mapping::topic_and_key(&self.topic_prefix, attributes)?;
+        let headers = mapping::message_headers(attributes);
+        let payload = message.payload.unwrap_or_default();
+        let mut record = FutureRecord::<str, [u8]>::to(&topic)
+            .payload(&payload)
+            .headers(headers);
+        if let Some(key) = key.as_deref() {
+            record = record.key(key);
+        }
+        match self
+            .producer
+            .send(record, Timeout::After(SEND_QUEUE_TIMEOUT))
+            .await
+        {
+            Ok(_delivery) => {
+                trace!(
+                    "successfully sent message [topic: {}, key: {:?}]",
+                    topic,
+                    key
+                );
+                Ok(())
+            }
+            Err((e, _message)) => {
+                warn!("error sending message [topic: {}]: {}", topic, e);
+                Err(UStatus::fail_with_code(UCode::UNAVAILABLE, e.to_string()))
+            }
+        }
+    }
+
+    async fn register_listener(
+        &self,
+        source_filter: &UUri,
+        sink_filter: Option<&UUri>,
+        listener: Arc<dyn UListener>,
+    ) -> Result<(), UStatus> {
+        let subscription = mapping::subscription(&self.topic_prefix, source_filter, sink_filter);
+        self.local_transport
+            .register_listener(source_filter, sink_filter, listener.clone())
+            .await?;
+        let result = match self.subscriptions.lock() {
+            Ok(mut subscriptions) => {
+                let listeners = subscriptions.entry(subscription.clone()).or_insert(0);
+                *listeners += 1;
+                if *listeners > 1 {
+                    return Ok(());
+                }
+                let result = self.update_subscription(&subscriptions);
+                if result.is_err() {
+                    subscriptions.remove(&subscription);
+                }
+                result
+            }
+            Err(e) => Err(UStatus::fail_with_code(UCode::INTERNAL, e.to_string())),
+        };
+        if result.is_err() {
+            let _ = self
+                .local_transport
+                .unregister_listener(source_filter, sink_filter, listener)
+                .await;
+        }
+        result
+    }
+
+    async fn unregister_listener(
+        &self,
+        source_filter: &UUri,
+        sink_filter: Option<&UUri>,
+        listener: Arc<dyn UListener>,
+    ) -> Result<(), UStatus> {
+        let subscription = mapping::subscription(&self.topic_prefix, source_filter, sink_filter);
+        self.local_transport
+            .unregister_listener(source_filter, sink_filter, listener)
+            .await?;
+        if let Ok(mut subscriptions) = self.subscriptions.lock() {
+            match subscriptions.get_mut(&subscription) {
+                Some(listeners) if *listeners > 1 => *listeners -= 1,
+                Some(_) => {
+                    subscriptions.remove(&subscription);
+                    if let Err(e) = self.update_subscription(&subscriptions) {
+                        error!("failed to update subscription: {}", e.get_message());
+                    }
+                }
+                None => {}
+            }
+        }
+        Ok(())
+    }
+}
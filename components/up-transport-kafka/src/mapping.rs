@@ -0,0 +1,224 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Mapping of uProtocol messages to Kafka topics, keys and headers.
+//!
+//! Messages are published to topics named `{prefix}.{ue_id}.{ue_version_major}.{resource_id}` with
+//! all numbers being encoded as upper case hex strings, e.g. `up.D100.1.D100`. The topic is derived
+//! from the source URI of *publish* messages and from the sink URI of all other messages, i.e. the
+//! topic corresponds to the resource that a message is published from or sent to. The authority of
+//! the same URI is used as the record's key, so that all messages of a vehicle end up in the same
+//! partition (and are therefore delivered in order).
+//!
+//! The message attributes are conveyed as Kafka headers, using the attributes' protobuf field numbers
+//! as header names.
+
+use hono_wire_format::attributes;
+use rdkafka::message::{Header, OwnedHeaders};
+use up_rust::{UAttributes, UCode, UStatus, UUri};
+
+/// A subscription to the Kafka topic(s) that correspond to a URI filter.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Subscription {
+    /// A single topic.
+    Topic(String),
+    /// All topics matching a regular expression.
+    Pattern(String),
+}
+
+impl Subscription {
+    /// Gets the topic name or pattern as expected by the Kafka client.
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            Subscription::Topic(topic) | Subscription::Pattern(topic) => topic,
+        }
+    }
+}
+
+/// Gets the URI that determines the topic and key of a message.
+fn addressing_uri(attributes: &UAttributes) -> Result<&UUri, UStatus> {
+    let uri = if attributes.is_publish() {
+        attributes.source.as_ref()
+    } else {
+        attributes.sink.as_ref()
+    };
+    uri.ok_or_else(|| {
+        UStatus::fail_with_code(
+            UCode::INVALID_ARGUMENT,
+            "message has no source or sink URI to derive topic from",
+        )
+    })
+}
+
+/// Gets the name of the topic to publish a message to and the key to use for the record.
+///
+/// The key is `None` if the URI that the topic is derived from has no or a wildcard authority.
+pub(crate) fn topic_and_key(
+    prefix: &str,
+    attributes: &UAttributes,
+) -> Result<(String, Option<String>), UStatus> {
+    let uri = addressing_uri(attributes)?;
+    if uri.has_wildcard_entity_type()
+        || uri.has_wildcard_entity_instance()
+        || uri.has_wildcard_version()
+        || uri.has_wildcard_resource_id()
+    {
+        return Err(UStatus::fail_with_code(
+            UCode::INVALID_ARGUMENT,
+            "URI used for deriving topic must not contain wildcards",
+        ));
+    }
+    let topic = format!(
+        "{}.{:X}.{:X}.{:X}",
+        prefix, uri.ue_id, uri.ue_version_major, uri.resource_id
+    );
+    let key = Some(uri.authority_name.to_owned())
+        .filter(|authority| !authority.is_empty() && !uri.has_wildcard_authority());
+    Ok((topic, key))
+}
+
+/// Gets the subscription to use for receiving messages matching the given source and sink filters.
+///
+/// Note that the subscription may match more messages than the URI filters, in particular
+/// it includes messages from/to all authorities.
+pub(crate) fn subscription(
+    prefix: &str,
+    source_filter: &UUri,
+    sink_filter: Option<&UUri>,
+) -> Subscription {
+    let uri = sink_filter.unwrap_or(source_filter);
+    let entity = if uri.has_wildcard_entity_type() || uri.has_wildcard_entity_instance() {
+        None
+    } else {
+        Some(format!("{:X}", uri.ue_id))
+    };
+    let version = if uri.has_wildcard_version() {
+        None
+    } else {
+        Some(format!("{:X}", uri.ue_version_major))
+    };
+    let resource = if uri.has_wildcard_resource_id() {
+        None
+    } else {
+        Some(format!("{:X}", uri.resource_id))
+    };
+    match (entity, version, resource) {
+        (Some(entity), Some(version), Some(resource)) => {
+            Subscription::Topic(format!("{prefix}.{entity}.{version}.{resource}"))
+        }
+        (entity, version, resource) => {
+            let segment = |value: Option<String>| value.unwrap_or_else(|| "[0-9A-F]+".to_string());
+            Subscription::Pattern(format!(
+                r"^{}\.{}\.{}\.{}$",
+                prefix.replace('.', r"\."),
+                segment(entity),
+                segment(version),
+                segment(resource)
+            ))
+        }
+    }
+}
+
+/// Creates the Kafka headers to include in a record for a uProtocol message.
+pub(crate) fn message_headers(attributes: &UAttributes) -> OwnedHeaders {
+    let headers = attributes::to_pairs(attributes);
+    headers.iter().fold(
+        OwnedHeaders::new_with_capacity(headers.len()),
+        |owned, (key, value)| {
+            owned.insert(Header {
+                key,
+                value: Some(value.as_str()),
+            })
+        },
+    )
+}
+
+/// Creates uProtocol message attributes from the headers of a Kafka record.
+///
+/// Unknown headers are ignored.
+///
+/// # Errors
+///
+/// Returns an error if the headers do not indicate a uProtocol message
+/// or if any of the attribute values is invalid.
+pub(crate) fn attributes_from_headers<'a, I>(headers: I) -> Result<UAttributes, String>
+where
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    attributes::from_pairs(headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use rdkafka::message::Headers;
+    use up_rust::{UMessageBuilder, UPriority};
+
+    use super::*;
+
+    #[test]
+    fn test_subscription_maps_wildcards() {
+        let source_filter = UUri::from_str("up://*/D100/1/D100").unwrap();
+        assert_eq!(
+            subscription("up", &source_filter, None),
+            Subscription::Topic("up.D100.1.D100".to_string())
+        );
+
+        let sink_filter = UUri::from_str("up://fms-consumer/D101/1/FFFF").unwrap();
+        assert_eq!(
+            subscription("fms.up", &source_filter, Some(&sink_filter)),
+            Subscription::Pattern(r"^fms\.up\.D101\.1\.[0-9A-F]+$".to_string())
+        );
+    }
+
+    #[test]
+    fn test_attributes_survive_roundtrip() {
+        let topic = UUri::from_str("up://WBA12345678901234/D100/1/D100").unwrap();
+        let message = UMessageBuilder::publish(topic)
+            .with_priority(UPriority::UPRIORITY_CS2)
+            .with_ttl(1500)
+            .build()
+            .unwrap();
+        let attributes = message.attributes.get_or_default();
+        assert_eq!(
+            topic_and_key("up", attributes).unwrap(),
+            (
+                "up.D100.1.D100".to_string(),
+                Some("WBA12345678901234".to_string())
+            )
+        );
+
+        let headers = message_headers(attributes);
+        let restored = attributes_from_headers(headers.iter().filter_map(|header| {
+            header
+                .value
+                .and_then(|value| std::str::from_utf8(value).ok())
+                .map(|value| (header.key, value))
+        }))
+        .unwrap();
+        assert_eq!(&restored, attributes);
+    }
+
+    #[test]
+    fn test_attributes_require_uprotocol_version() {
+        let headers = vec![(attributes::NAME_TYPE, "1")];
+        assert!(attributes_from_headers(headers).is_err());
+    }
+}
@@ -0,0 +1,294 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use clap::Args;
+use log::{debug, info, warn};
+use mqtt_connection::{ConnectionState, MqttClientOptions, MqttVersion};
+use paho_mqtt::{AsyncClient, AsyncReceiver, DisconnectOptionsBuilder, MessageBuilder, QOS_1};
+use tokio::sync::watch;
+use up_rust::{
+    local_transport::LocalTransport, UCode, UListener, UMessage, UStatus, UTransport, UUri,
+};
+
+mod mapping;
+
+// the maximum number of received messages that are buffered before being dispatched to listeners
+const MESSAGE_BUFFER_SIZE: usize = 100;
+
+#[derive(Args)]
+pub struct Mqtt5TransportConfig {
+    #[command(flatten)]
+    mqtt_client_options: MqttClientOptions,
+}
+
+/// A uProtocol transport implementation for exchanging messages via an arbitrary MQTT 5 broker,
+/// e.g. Eclipse Mosquitto or EMQX.
+///
+/// Messages are published to topics that are derived from their source and sink URIs,
+/// the message attributes are conveyed in the PUBLISH packet's user properties and the
+/// payload is published as is.
+pub struct Mqtt5Transport {
+    mqtt_client: AsyncClient,
+    connection_state: watch::Sender<ConnectionState>,
+    persistent_buffer: bool,
+    message_expiry: Option<Duration>,
+    local_transport: Arc<LocalTransport>,
+    // the number of listeners registered per topic filter
+    subscriptions: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+impl Mqtt5Transport {
+    /// Creates a new transport.
+    ///
+    /// The transport returned keeps trying to (re-)connect to the configured MQTT broker
+    /// in the background and subscribes to the topics of the registered listeners whenever
+    /// the connection has been established. The state of the connection can be tracked by
+    /// means of [`Self::connection_state`].
+    pub async fn new(config: &Mqtt5TransportConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let options = &config.mqtt_client_options;
+        let mut message_stream = None;
+        let (mqtt_client, connection_state) = options.connect(&MqttVersion::V5, |client| {
+            message_stream = Some(client.get_stream(MESSAGE_BUFFER_SIZE));
+        })?;
+
+        let subscriptions = Arc::new(Mutex::new(HashMap::<String, usize>::new()));
+        tokio::spawn(Self::subscribe_to_topics(
+            mqtt_client.clone(),
+            connection_state.subscribe(),
+            subscriptions.clone(),
+        ));
+        let local_transport = Arc::new(LocalTransport::default());
+        if let Some(message_stream) = message_stream {
+            tokio::spawn(Self::dispatch_messages(
+                message_stream,
+                local_transport.clone(),
+            ));
+        }
+        Ok(Mqtt5Transport {
+            mqtt_client,
+            connection_state,
+            persistent_buffer: options.has_persistence(),
+            message_expiry: options.message_expiry(),
+            local_transport,
+            subscriptions,
+        })
+    }
+
+    /// Gets a channel for keeping track of the state of the connection to the MQTT broker.
+    pub fn connection_state(&self) -> watch::Receiver<ConnectionState> {
+        self.connection_state.subscribe()
+    }
+
+    /// Checks if messages that are sent while the connection to the MQTT broker is not established
+    /// are stored in files until they can be delivered.
+    pub fn has_persistent_buffer(&self) -> bool {
+        self.persistent_buffer
+    }
+
+    /// Subscribes to the topics of the registered listeners whenever a connection has been established.
+    ///
+    /// This is necessary because subscriptions do not survive a reconnect unless a persistent
+    /// session is used.
+    async fn subscribe_to_topics(
+        mqtt_client: AsyncClient,
+        mut connection_state: watch::Receiver<ConnectionState>,
+        subscriptions: Arc<Mutex<HashMap<String, usize>>>,
+    ) {
+        loop {
+            let state = *connection_state.borrow_and_update();
+            match state {
+                ConnectionState::Disconnected => break,
+                ConnectionState::Connected => {
+                    let topic_filters: Vec<String> = subscriptions
+                        .lock()
+                        .map(|subscriptions| subscriptions.keys().cloned().collect())
+                        .unwrap_or_default();
+                    for topic_filter in topic_filters {
+                        debug!("subscribing to topic [{}]", topic_filter);
+                        if let Err(e) = mqtt_client.subscribe(&topic_filter, QOS_1).await {
+                            warn!("failed to subscribe to topic [{}]: {}", topic_filter, e);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            if connection_state.changed().await.is_err() {
+                break;
+            }
+        }
+    }
+
+    /// Dispatches the uProtocol messages received from the MQTT broker to the registered listeners.
+    async fn dispatch_messages(
+        message_stream: AsyncReceiver<Option<paho_mqtt::Message>>,
+        local_transport: Arc<LocalTransport>,
+    ) {
+        // the stream yields None whenever the connection to the MQTT broker has been lost
+        // and ends once the MQTT client has been dropped
+        while let Ok(received) = message_stream.recv().await {
+            let Some(message) = received else {
+                continue;
+            };
+            match mapping::attributes_from_user_properties(message.properties().user_iter()) {
+                Ok(attributes) => {
+                    let umessage = UMessage {
+                        attributes: Some(attributes).into(),
+                        payload: Some(bytes::Bytes::copy_from_slice(message.payload())),
+                        ..Default::default()
+                    };
+                    let _ = local_transport.send(umessage).await;
+                }
+                Err(e) => {
+                    debug!(
+                        "ignoring message received on topic [{}]: {}",
+                        message.topic(),
+                        e
+                    );
+                }
+            }
+        }
+        debug!("stopped dispatching messages");
+    }
+
+    /// Disconnects from the MQTT broker.
+    ///
+    /// Messages that have already been handed over to the MQTT client are being
+    /// delivered before the connection is closed, unless the given timeout elapses.
+    pub async fn disconnect(&self, timeout: Duration) -> Result<(), Box<dyn std::error::Error>> {
+        info!(
+            "disconnecting from MQTT broker [uri: {}]",
+            self.mqtt_client.server_uri()
+        );
+        // prevent the client from reconnecting
+        self.connection_state
+            .send_replace(ConnectionState::Disconnected);
+        let disconnect_options = DisconnectOptionsBuilder::new().timeout(timeout).finalize();
+        self.mqtt_client
+            .disconnect(disconnect_options)
+            .await
+            .map(|_response| ())
+            .map_err(Box::from)
+    }
+}
+
+#[async_trait::async_trait]
+impl UTransport for Mqtt5Transport {
+    async fn send(&self, message: UMessage) -> Result<(), UStatus> {
+        let attributes = message.attributes.get_or_default();
+        let topic = mapping::topic_name(attributes)?;
+        let properties = mapping::message_properties(attributes, self.message_expiry)
+            .map_err(|e| UStatus::fail_with_code(UCode::INTERNAL, e.to_string()))?;
+        let msg = MessageBuilder::new()
+            .topic(topic.as_str())
+            .qos(QOS_1)
+            .properties(properties)
+            .payload(message.payload.unwrap_or_default().to_vec())
+            .finalize();
+        match self.mqtt_client.publish(msg).await {
+            Ok(_t) => {
+                debug!("successfully published message [topic: {}]", topic);
+                Ok(())
+            }
+            Err(e) => {
+                warn!("error publishing message [topic: {}]: {}", topic, e);
+                Err(UStatus::fail_with_code(UCode::UNAVAILABLE, e.to_string()))
+            }
+        }
+    }
+
+    async fn register_listener(
+        &self,
+        source_filter: &UUri,
+        sink_filter: Option<&UUri>,
+        listener: Arc<dyn UListener>,
+    ) -> Result<(), UStatus> {
+        let topic_filter = mapping::topic_filter(source_filter, sink_filter)?;
+        self.local_transport
+            .register_listener(source_filter, sink_filter, listener.clone())
+            .await?;
+        let is_new_subscription = self
+            .subscriptions
+            .lock()
+            .map(|mut subscriptions| {
+                let listeners = subscriptions.entry(topic_filter.clone()).or_insert(0);
+                *listeners += 1;
+                *listeners == 1
+            })
+            .unwrap_or(false);
+        // otherwise, the subscription is made once the connection has been established
+        if !is_new_subscription || *self.connection_state.borrow() != ConnectionState::Connected {
+            return Ok(());
+        }
+        debug!("subscribing to topic [{}]", topic_filter);
+        if let Err(e) = self.mqtt_client.subscribe(&topic_filter, QOS_1).await {
+            warn!("failed to subscribe to topic [{}]: {}", topic_filter, e);
+            if let Ok(mut subscriptions) = self.subscriptions.lock() {
+                subscriptions.remove(&topic_filter);
+            }
+            let _ = self
+                .local_transport
+                .unregister_listener(source_filter, sink_filter, listener)
+                .await;
+            return Err(UStatus::fail_with_code(UCode::UNAVAILABLE, e.to_string()));
+        }
+        Ok(())
+    }
+
+    async fn unregister_listener(
+        &self,
+        source_filter: &UUri,
+        sink_filter: Option<&UUri>,
+        listener: Arc<dyn UListener>,
+    ) -> Result<(), UStatus> {
+        let topic_filter = mapping::topic_filter(source_filter, sink_filter)?;
+        self.local_transport
+            .unregister_listener(source_filter, sink_filter, listener)
+            .await?;
+        let is_last_listener = self
+            .subscriptions
+            .lock()
+            .map(
+                |mut subscriptions| match subscriptions.get_mut(&topic_filter) {
+                    Some(listeners) if *listeners > 1 => {
+                        *listeners -= 1;
+                        false
+                    }
+                    Some(_) => {
+                        subscriptions.remove(&topic_filter);
+                        true
+                    }
+                    None => false,
+                },
+            )
+            .unwrap_or(false);
+        if is_last_listener {
+            debug!("unsubscribing from topic [{}]", topic_filter);
+            if let Err(e) = self.mqtt_client.unsubscribe(&topic_filter).await {
+                warn!("failed to unsubscribe from topic [{}]: {}", topic_filter, e);
+            }
+        }
+        Ok(())
+    }
+}
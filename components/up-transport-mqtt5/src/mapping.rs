@@ -0,0 +1,221 @@
+// SPDX-FileCopyrightText: 2026 Contributors to the Eclipse Foundation
+//
+// See the NOTICE file(s) distributed with this work for additional
+// information regarding copyright ownership.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Mapping of uProtocol messages to MQTT 5 topic names and properties.
+//!
+//! The topic name of a message is derived from its source and (optional) sink URI as defined by the
+//! uProtocol MQTT 5 transport specification. Each URI is mapped to the topic segments
+//! `{authority}/{ue_type}/{ue_instance}/{ue_version_major}/{resource_id}` with all numbers being encoded
+//! as upper case hex strings, e.g. `vehicle1/D100/0/1/D100`. The sink URI's segments are appended to
+//! the source URI's segments, if the message has a sink. Wildcards are mapped to the MQTT single level
+//! wildcard `+`.
+//!
+//! The message attributes are conveyed as MQTT 5 user properties, using the attributes' protobuf
+//! field numbers as property names.
+
+use std::time::Duration;
+
+use hono_wire_format::attributes;
+use paho_mqtt::{Properties, PropertyCode};
+use up_rust::{UAttributes, UCode, UStatus, UUri};
+
+const TOPIC_WILDCARD: &str = "+";
+
+/// Maps a URI to the corresponding segments of a topic name or topic filter.
+fn uri_to_topic_segments(uri: &UUri) -> Result<String, UStatus> {
+    if uri.authority_name.is_empty() {
+        return Err(UStatus::fail_with_code(
+            UCode::INVALID_ARGUMENT,
+            "URI must contain an authority",
+        ));
+    }
+    let authority = if uri.has_wildcard_authority() {
+        TOPIC_WILDCARD.to_string()
+    } else {
+        uri.authority_name.to_owned()
+    };
+    let entity_type = if uri.has_wildcard_entity_type() {
+        TOPIC_WILDCARD.to_string()
+    } else {
+        format!("{:X}", uri.uentity_type_id())
+    };
+    let entity_instance = if uri.has_wildcard_entity_instance() {
+        TOPIC_WILDCARD.to_string()
+    } else {
+        format!("{:X}", uri.uentity_instance_id())
+    };
+    let version = if uri.has_wildcard_version() {
+        TOPIC_WILDCARD.to_string()
+    } else {
+        format!("{:X}", uri.ue_version_major)
+    };
+    let resource = if uri.has_wildcard_resource_id() {
+        TOPIC_WILDCARD.to_string()
+    } else {
+        format!("{:X}", uri.resource_id)
+    };
+    Ok(format!(
+        "{authority}/{entity_type}/{entity_instance}/{version}/{resource}"
+    ))
+}
+
+/// Gets the topic filter to use for receiving messages matching the given source and sink filters.
+pub(crate) fn topic_filter(
+    source_filter: &UUri,
+    sink_filter: Option<&UUri>,
+) -> Result<String, UStatus> {
+    let source = uri_to_topic_segments(source_filter)?;
+    match sink_filter {
+        Some(sink_filter) => Ok(format!(
+            "{}/{}",
+            source,
+            uri_to_topic_segments(sink_filter)?
+        )),
+        None => Ok(source),
+    }
+}
+
+/// Gets the name of the topic to publish a message to.
+pub(crate) fn topic_name(attributes: &UAttributes) -> Result<String, UStatus> {
+    let source = attributes
+        .source
+        .as_ref()
+        .ok_or_else(|| UStatus::fail_with_code(UCode::INVALID_ARGUMENT, "message has no source"))?;
+    topic_filter(source, attributes.sink.as_ref())
+}
+
+/// Gets the number of seconds after which a message should expire.
+///
+/// The message's time-to-live takes precedence over the default expiry.
+fn message_expiry_interval(
+    attributes: &UAttributes,
+    default_expiry: Option<Duration>,
+) -> Option<u32> {
+    match attributes.ttl {
+        Some(ttl) if ttl > 0 => Some(ttl.div_ceil(1000)),
+        _ => default_expiry
+            .map(|expiry| u32::try_from(expiry.as_secs()).unwrap_or(u32::MAX))
+            .filter(|seconds| *seconds > 0),
+    }
+}
+
+/// Creates the MQTT 5 properties to include in the PUBLISH packet for a uProtocol message.
+pub(crate) fn message_properties(
+    attributes: &UAttributes,
+    default_expiry: Option<Duration>,
+) -> Result<Properties, paho_mqtt::Error> {
+    let mut properties = Properties::new();
+    if let Some(seconds) = message_expiry_interval(attributes, default_expiry) {
+        properties.push_u32(PropertyCode::MessageExpiryInterval, seconds)?;
+    }
+    for (name, value) in attributes::to_pairs(attributes) {
+        properties.push_string_pair(PropertyCode::UserProperty, name, &value)?;
+    }
+    Ok(properties)
+}
+
+/// Creates uProtocol message attributes from the user properties of an MQTT 5 PUBLISH packet.
+///
+/// Unknown user properties are ignored.
+///
+/// # Errors
+///
+/// Returns an error if the properties do not indicate a uProtocol message
+/// or if any of the attribute values is invalid.
+pub(crate) fn attributes_from_user_properties<I>(user_properties: I) -> Result<UAttributes, String>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    attributes::from_pairs(user_properties)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use up_rust::{UMessageBuilder, UPriority};
+
+    use super::*;
+
+    #[test]
+    fn test_topic_filter_maps_wildcards() {
+        let source_filter = UUri::from_str("up://*/D100/1/D100").unwrap();
+        assert_eq!(
+            topic_filter(&source_filter, None).unwrap(),
+            "+/D100/0/1/D100"
+        );
+
+        let sink_filter = UUri::from_str("up://fms-consumer/FFFFFFFF/FF/FFFF").unwrap();
+        assert_eq!(
+            topic_filter(&source_filter, Some(&sink_filter)).unwrap(),
+            "+/D100/0/1/D100/fms-consumer/+/+/+/+"
+        );
+
+        let instance_filter = UUri::from_str("up://vehicle1/FFFF0A10/2/0").unwrap();
+        assert_eq!(
+            topic_filter(&instance_filter, None).unwrap(),
+            "vehicle1/A10/+/2/0"
+        );
+
+        let local_uri = UUri::from_str("/D100/1/D100").unwrap();
+        assert!(topic_filter(&local_uri, None).is_err());
+    }
+
+    #[test]
+    fn test_attributes_survive_roundtrip() {
+        let topic = UUri::from_str("up://vehicle1/D100/1/D100").unwrap();
+        let message = UMessageBuilder::publish(topic)
+            .with_priority(UPriority::UPRIORITY_CS2)
+            .with_ttl(1500)
+            .build()
+            .unwrap();
+        let attributes = message.attributes.get_or_default();
+        assert_eq!(topic_name(attributes).unwrap(), "vehicle1/D100/0/1/D100");
+
+        let properties = message_properties(attributes, None).unwrap();
+        assert_eq!(
+            properties.get_int(PropertyCode::MessageExpiryInterval),
+            Some(2)
+        );
+        let restored = attributes_from_user_properties(properties.user_iter()).unwrap();
+        assert_eq!(&restored, attributes);
+    }
+
+    #[test]
+    fn test_message_expiry_uses_default() {
+        let mut attributes = UAttributes::new();
+        assert_eq!(message_expiry_interval(&attributes, None), None);
+        assert_eq!(
+            message_expiry_interval(&attributes, Some(Duration::from_secs(30))),
+            Some(30)
+        );
+        // the message's TTL is rounded up to full seconds
+        attributes.ttl = Some(1500);
+        assert_eq!(
+            message_expiry_interval(&attributes, Some(Duration::from_secs(30))),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn test_attributes_require_uprotocol_version() {
+        let properties = vec![(attributes::NAME_TYPE.to_string(), "1".to_string())];
+        assert!(attributes_from_user_properties(properties).is_err());
+    }
+}